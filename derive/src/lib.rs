@@ -0,0 +1,134 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Fields, Index, parse_macro_input, parse_quote,
+};
+
+/// Derives `HandleRemap` by remapping every field. Fields that hold no
+/// handle and whose type does not implement the trait can be marked with
+/// `#[handle_remap(skip)]`.
+#[proc_macro_derive(HandleRemap, attributes(handle_remap))]
+pub fn derive_handle_remap(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Generates the implementation for a struct or an enum
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let element = format_ident!("__HandleRemapElement");
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#element: 'static));
+    let where_clause = generics.make_where_clause();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, calls) = remap_fields(&data.fields, where_clause)?;
+            quote! {
+                let #name #pattern = self;
+                #(#calls)*
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let (pattern, calls) =
+                    remap_fields(&variant.fields, where_clause)?;
+                arms.push(quote! {
+                    #name::#ident #pattern => { #(#calls)* }
+                });
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "HandleRemap cannot be derived for unions",
+            ));
+        }
+    };
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, type_generics, _) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::stable_index_vector::HandleRemap<#element>
+            for #name #type_generics #where_clause
+        {
+            #[allow(unused_variables)]
+            fn remap_handles(
+                &mut self,
+                remap: &::stable_index_vector::IdRemap<#element>,
+            ) {
+                #body
+            }
+        }
+    })
+}
+
+/// Builds the pattern binding the remapped fields and the calls remapping
+/// them, adding the bound of each remapped field type to the where clause
+fn remap_fields(
+    fields: &Fields,
+    where_clause: &mut syn::WhereClause,
+) -> syn::Result<(TokenStream2, Vec<TokenStream2>)> {
+    let element = format_ident!("__HandleRemapElement");
+    let mut bindings = Vec::new();
+    let mut calls = Vec::new();
+    for (position, field) in fields.iter().enumerate() {
+        let binding = format_ident!("__field_{}", position);
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(position);
+                quote!(#index)
+            }
+        };
+        if is_skipped(field)? {
+            bindings.push(quote!(#member: _));
+            continue;
+        }
+        let ty = &field.ty;
+        where_clause.predicates.push(parse_quote!(
+            #ty: ::stable_index_vector::HandleRemap<#element>
+        ));
+        bindings.push(quote!(#member: #binding));
+        calls.push(quote! {
+            ::stable_index_vector::HandleRemap::<#element>::remap_handles(
+                #binding, remap,
+            );
+        });
+    }
+    let pattern = match fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#bindings,)* .. }),
+    };
+    Ok((pattern, calls))
+}
+
+/// Tells if the field is marked with `#[handle_remap(skip)]`
+fn is_skipped(field: &syn::Field) -> syn::Result<bool> {
+    let mut skipped = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("handle_remap") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skipped = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported handle_remap attribute"))
+            }
+        })?;
+    }
+    Ok(skipped)
+}
@@ -0,0 +1,219 @@
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::{Allocator, Global};
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::collections::TryReserveError;
+#[cfg(not(feature = "allocator-api2"))]
+pub use std::collections::TryReserveError;
+use std::alloc::{Layout, LayoutError};
+
+/// The allocator of the internal vectors of a Vector. Without the
+/// `allocator-api2` feature, only the global allocator implements it.
+#[cfg(not(feature = "allocator-api2"))]
+pub trait Allocator: sealed::Sealed {}
+
+/// The global allocator, used by the internal vectors by default.
+#[cfg(not(feature = "allocator-api2"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Global;
+
+#[cfg(not(feature = "allocator-api2"))]
+impl Allocator for Global {}
+
+#[cfg(not(feature = "allocator-api2"))]
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Global {}
+}
+
+/// The type of the internal vectors allocating from an Allocator, or of
+/// their draining and owning iterators: the allocator-api2 types with the
+/// `allocator-api2` feature, the standard ones without
+#[cfg(feature = "allocator-api2")]
+macro_rules! buffer {
+    (Drain<$lt:lifetime, $t:ty, $a:ty>) => {
+        allocator_api2::vec::Drain<$lt, $t, $a>
+    };
+    (IntoIter<$t:ty, $a:ty>) => {
+        allocator_api2::vec::IntoIter<$t, $a>
+    };
+    ($t:ty, $a:ty) => {
+        allocator_api2::vec::Vec<$t, $a>
+    };
+}
+
+#[cfg(not(feature = "allocator-api2"))]
+macro_rules! buffer {
+    (Drain<$lt:lifetime, $t:ty, $a:ty>) => {
+        std::vec::Drain<$lt, $t>
+    };
+    (IntoIter<$t:ty, $a:ty>) => {
+        std::vec::IntoIter<$t>
+    };
+    ($t:ty, $a:ty) => {
+        Vec<$t>
+    };
+}
+
+pub(crate) use buffer;
+
+/// The capacity of an internal vector, as grown by a GrowthStrategy
+pub(crate) trait Buffer {
+    /// Returns the number of elements
+    fn len(&self) -> usize;
+
+    /// Returns the number of elements the vector can hold without
+    /// reallocating
+    fn capacity(&self) -> usize;
+
+    /// Reserves room for at least @p additional more elements
+    fn reserve(&mut self, additional: usize);
+
+    /// Reserves room for exactly @p additional more elements
+    fn reserve_exact(&mut self, additional: usize);
+
+    /// Like `reserve`, but reports allocation failures instead of aborting
+    fn try_reserve(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError>;
+
+    /// Like `reserve_exact`, but reports allocation failures instead of
+    /// aborting
+    fn try_reserve_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError>;
+}
+
+// The inherent methods of the vector, which take precedence over the ones
+// of the trait
+macro_rules! forward_buffer {
+    () => {
+        fn len(&self) -> usize {
+            self.len()
+        }
+
+        fn capacity(&self) -> usize {
+            self.capacity()
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.reserve(additional);
+        }
+
+        fn reserve_exact(&mut self, additional: usize) {
+            self.reserve_exact(additional);
+        }
+
+        fn try_reserve(
+            &mut self,
+            additional: usize,
+        ) -> Result<(), TryReserveError> {
+            self.try_reserve(additional)
+        }
+
+        fn try_reserve_exact(
+            &mut self,
+            additional: usize,
+        ) -> Result<(), TryReserveError> {
+            self.try_reserve_exact(additional)
+        }
+    };
+}
+
+#[cfg(feature = "allocator-api2")]
+impl<U, A: Allocator> Buffer for allocator_api2::vec::Vec<U, A> {
+    forward_buffer!();
+}
+
+#[cfg(not(feature = "allocator-api2"))]
+impl<U> Buffer for Vec<U> {
+    forward_buffer!();
+}
+
+/// Creates an empty internal vector allocating from @p alloc
+#[cfg(feature = "allocator-api2")]
+pub(crate) fn new_buffer<U, A: Allocator + Clone>(alloc: &A) -> buffer!(U, A) {
+    allocator_api2::vec::Vec::new_in(alloc.clone())
+}
+
+/// Creates an empty internal vector allocating from @p alloc
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) fn new_buffer<U, A: Allocator + Clone>(_: &A) -> buffer!(U, A) {
+    Vec::new()
+}
+
+/// Takes the allocation of @p vec for an internal vector using the global
+/// allocator
+#[cfg(feature = "allocator-api2")]
+pub(crate) fn from_vec<U>(vec: Vec<U>) -> buffer!(U, Global) {
+    let mut vec = std::mem::ManuallyDrop::new(vec);
+    let (ptr, len, capacity) = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+    // SAFETY: Global forwards to the global allocator, which allocated the
+    // buffer of the standard vector, and the vector no longer owns it
+    unsafe { allocator_api2::vec::Vec::from_raw_parts(ptr, len, capacity) }
+}
+
+/// Takes the allocation of @p vec for an internal vector using the global
+/// allocator
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) fn from_vec<U>(vec: Vec<U>) -> buffer!(U, Global) {
+    vec
+}
+
+/// Takes the allocation of the internal vector @p buffer for a standard
+/// one
+#[cfg(feature = "allocator-api2")]
+pub(crate) fn into_vec<U>(buffer: buffer!(U, Global)) -> Vec<U> {
+    let (ptr, len, capacity) = buffer.into_raw_parts();
+    // SAFETY: the buffer was allocated by the global allocator, through
+    // Global, and the internal vector no longer owns it
+    unsafe { Vec::from_raw_parts(ptr, len, capacity) }
+}
+
+/// Takes the allocation of the internal vector @p buffer for a standard
+/// one
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) fn into_vec<U>(buffer: buffer!(U, Global)) -> Vec<U> {
+    buffer
+}
+
+/// Makes the internal vector @p buffer a copy of @p source, cloning into
+/// its elements and allocation like `Vec::clone_from`
+pub(crate) fn clone_from_slice<U: Clone>(
+    buffer: &mut buffer!(U, Global),
+    source: &[U],
+) {
+    buffer.truncate(source.len());
+    let (init, tail) = source.split_at(buffer.len());
+    buffer.clone_from_slice(init);
+    buffer.extend_from_slice(tail);
+}
+
+/// Converts the error of a standard collection failing to allocate
+/// @p layout
+#[cfg(feature = "allocator-api2")]
+pub(crate) fn convert_error(
+    _: std::collections::TryReserveError,
+    layout: Result<Layout, LayoutError>,
+) -> TryReserveError {
+    use allocator_api2::collections::TryReserveErrorKind;
+    match layout {
+        Ok(layout) => {
+            TryReserveErrorKind::AllocError { layout, non_exhaustive: () }
+        }
+        Err(error) => error.into(),
+    }
+    .into()
+}
+
+/// Converts the error of a standard collection failing to allocate
+/// @p layout
+#[cfg(not(feature = "allocator-api2"))]
+pub(crate) fn convert_error(
+    error: TryReserveError,
+    _: Result<Layout, LayoutError>,
+) -> TryReserveError {
+    error
+}
@@ -0,0 +1,249 @@
+use crate::{ID, MAX_GENERATION};
+use crate::extras::SlotExtras;
+use crate::handle::Handle;
+use crate::metadata::Metadata;
+use crate::raw::RawParts;
+use crate::vector::Vector;
+
+/// A fixed-capacity Vector storing up to @p N objects inline, without any
+/// heap allocation. IDs and handles behave like the ones of Vector, and
+/// inserting into a full vector gives the object back.
+#[derive(Clone, Debug)]
+pub struct ArrayVector<T, const N: usize> {
+    /// The objects, densely packed at the start of the array.
+    data: [Option<T>; N],
+    /// The ID of the object at each data index. Past the objects, the IDs
+    /// of the free slots.
+    reverse_ids: [ID; N],
+    /// The data index of each ID.
+    indices: [usize; N],
+    /// The generation of each ID.
+    validity_ids: [ID; N],
+    /// The number of objects.
+    len: usize,
+    /// The number of IDs handed out so far.
+    slots: usize,
+}
+
+impl<T, const N: usize> Default for ArrayVector<T, N> {
+    fn default() -> Self {
+        Self {
+            data: std::array::from_fn(|_| None),
+            reverse_ids: [0; N],
+            indices: [0; N],
+            validity_ids: [0; N],
+            len: 0,
+            slots: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> ArrayVector<T, N> {
+    /// Inserts an object, reusing a free ID if there is one
+    ///
+    /// @param object The object to insert
+    /// @return The ID to retrieve the object, or the object if the vector
+    /// is full
+    pub fn push(&mut self, object: T) -> Result<ID, T> {
+        if self.len == N {
+            return Err(object);
+        }
+        let id = if self.len < self.slots {
+            let id = self.reverse_ids[self.len];
+            self.validity_ids[id] =
+                self.validity_ids[id].saturating_add(1).min(MAX_GENERATION);
+            id
+        } else {
+            self.reverse_ids[self.len] = self.slots;
+            self.indices[self.slots] = self.len;
+            self.slots += 1;
+            self.slots - 1
+        };
+        self.data[self.len] = Some(object);
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// Removes the object with the provided ID, moving the last object in
+    /// its place
+    ///
+    /// @return The object, or None if the ID does not reference an object
+    pub fn remove_by_id(&mut self, id: ID) -> Option<T> {
+        if !self.contains_id(id) {
+            return None;
+        }
+        let data_index = self.indices[id];
+        let last = self.len - 1;
+        self.data.swap(data_index, last);
+        self.reverse_ids.swap(data_index, last);
+        self.indices[self.reverse_ids[data_index]] = data_index;
+        self.indices[id] = last;
+        self.validity_ids[id] =
+            self.validity_ids[id].saturating_add(1).min(MAX_GENERATION);
+        self.len -= 1;
+        self.data[last].take()
+    }
+
+    /// Removes the object referenced by the handle
+    ///
+    /// @return The object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.resolve(handle)?;
+        self.remove_by_id(handle.id)
+    }
+
+    /// Creates a handle pointing to the provided ID
+    ///
+    /// @return A handle to the object, or None if there is no object
+    #[must_use]
+    pub fn create_handle(&self, id: ID) -> Option<Handle<T>> {
+        if !self.contains_id(id) {
+            return None;
+        }
+        Some(Handle::new(id, self.validity_ids[id]))
+    }
+
+    /// Returns the object referenced by the handle
+    #[must_use]
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        self.data[self.resolve(handle)?].as_ref()
+    }
+
+    /// Returns the object referenced by the handle
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        self.data[self.resolve(handle)?].as_mut()
+    }
+
+    /// Tells if the handle references a live object
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    /// Tells if the ID references a live object, whatever its generation
+    #[must_use]
+    pub fn contains_id(&self, id: ID) -> bool {
+        id < self.slots && self.indices[id] < self.len
+    }
+
+    /// Returns the number of objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tells if there is no object
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tells if no more object can be inserted
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the maximum number of objects
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns an iterator over the objects, in data order
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.data[..self.len].iter().flatten()
+    }
+
+    /// Returns an iterator over mutable references to the objects, in data
+    /// order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.data[..self.len].iter_mut().flatten()
+    }
+
+    /// Returns an iterator over the objects and their handles, in data order
+    pub fn iter_with_handles(
+        &self,
+    ) -> impl Iterator<Item = (Handle<T>, &T)> + '_ {
+        self.iter().zip(&self.reverse_ids).map(|(object, id)| {
+            (Handle::new(*id, self.validity_ids[*id]), object)
+        })
+    }
+
+    /// Moves the objects to a heap allocated Vector, keeping their IDs and
+    /// generations so existing handles stay valid
+    pub fn into_vector(self) -> Vector<T> {
+        let metadata = self.reverse_ids[..self.slots]
+            .iter()
+            .map(|id| Metadata::new(*id, self.validity_ids[*id]))
+            .collect();
+        let parts = RawParts {
+            data: self.data.into_iter().take(self.len).flatten().collect(),
+            metadata,
+            indices: self.indices[..self.slots].to_vec(),
+            retired: 0,
+            identity: None,
+            generation_floor: 0,
+            extras: SlotExtras::default(),
+        };
+        Vector::from_raw_parts(parts).expect("The array slots are consistent")
+    }
+
+    /// Returns the data index of the object referenced by the handle
+    fn resolve(&self, handle: &Handle<T>) -> Option<usize> {
+        (self.contains_id(handle.id)
+            && self.validity_ids[handle.id] == handle.generation())
+            .then(|| self.indices[handle.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_until_full() {
+        let mut vec: ArrayVector<char, 2> = ArrayVector::default();
+
+        assert_eq!(vec.push('a'), Ok(0));
+        assert_eq!(vec.push('b'), Ok(1));
+        assert!(vec.is_full());
+        assert_eq!(vec.push('c'), Err('c'));
+        assert_eq!(vec.iter().collect::<String>(), "ab");
+    }
+
+    #[test]
+    fn test_handles_follow_generations() {
+        let mut vec: ArrayVector<char, 4> = ArrayVector::default();
+        let a = vec.push('a').unwrap();
+        let b = vec.push('b').unwrap();
+        let old = vec.create_handle(a).unwrap();
+        let handle_b = vec.create_handle(b).unwrap();
+
+        assert_eq!(vec.remove(&old), Some('a'));
+        assert_eq!(vec.get(&handle_b), Some(&'b'));
+        assert_eq!(vec.push('c'), Ok(a));
+        let new = vec.create_handle(a).unwrap();
+        assert!(!vec.contains(&old));
+        assert_eq!(vec.remove(&old), None);
+        *vec.get_mut(&new).unwrap() = 'C';
+
+        let entries: Vec<_> = vec.iter_with_handles().collect();
+        assert_eq!(entries, vec![(handle_b, &'b'), (new, &'C')]);
+    }
+
+    #[test]
+    fn test_into_vector_keeps_handles() {
+        let mut array: ArrayVector<char, 3> = ArrayVector::default();
+        let a = array.push('a').unwrap();
+        array.push('b').unwrap();
+        array.remove_by_id(a);
+        let c = array.push('c').unwrap();
+        let handle = array.create_handle(c).unwrap();
+
+        let mut vec = array.into_vector();
+        assert_eq!(vec.get(&handle), Some(&'c'));
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.push('d'), 2);
+    }
+}
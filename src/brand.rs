@@ -0,0 +1,184 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::marker::PhantomData;
+
+/// An invariant lifetime, so two brands can never be unified.
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/// Access to a vector whose structure can not change, lent by
+/// `Vector::branded`. Handles checked once through it are branded with its
+/// lifetime and then access their object without any further check.
+///
+/// ```
+/// use stable_index_vector::Vector;
+///
+/// let mut vec: Vector<u32> = (0..4).collect();
+/// let handle = vec.create_handle(2).unwrap();
+///
+/// vec.branded(|mut vec| {
+///     let checked = vec.check(&handle).unwrap();
+///     for _ in 0..10 {
+///         *vec.get_mut(checked) += 1;
+///     }
+///     assert_eq!(*vec.get(checked), 12);
+/// });
+/// ```
+///
+/// A handle checked in one scope can not be used in another one:
+///
+/// ```compile_fail
+/// use stable_index_vector::Vector;
+///
+/// let mut a: Vector<u32> = (0..4).collect();
+/// let mut b: Vector<u32> = (0..1).collect();
+/// let handle = a.create_handle(3).unwrap();
+///
+/// a.branded(|a| {
+///     let checked = a.check(&handle).unwrap();
+///     b.branded(|b| *b.get(checked));
+/// });
+/// ```
+#[derive(Debug)]
+pub struct BrandedVector<'brand, 'a, T> {
+    /// The vector, borrowed for the whole scope.
+    vector: &'a mut Vector<T>,
+    _brand: Brand<'brand>,
+}
+
+/// A handle checked by a BrandedVector. It is only accepted by the
+/// BrandedVector with the same brand, whose objects can not move, so it
+/// always references a live object.
+#[derive(Debug)]
+pub struct BrandedHandle<'brand, T> {
+    /// The data index of the object.
+    index: usize,
+    _brand: Brand<'brand>,
+    /// Prevent type collisions, like for Handle.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Copy for BrandedHandle<'_, T> {}
+
+impl<T> Clone for BrandedHandle<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Vector<T> {
+    /// Calls the closure with access to the vector in which handles are
+    /// checked once and then used without any bounds or generation check.
+    /// No object can be inserted or removed during the call.
+    ///
+    /// @param f The closure, receiving the branded access
+    /// @return The result of the closure
+    pub fn branded<R, F>(&mut self, f: F) -> R
+    where
+        F: for<'brand> FnOnce(BrandedVector<'brand, '_, T>) -> R,
+    {
+        f(BrandedVector {
+            vector: self,
+            _brand: PhantomData,
+        })
+    }
+}
+
+impl<'brand, T> BrandedVector<'brand, '_, T> {
+    /// Checks that the handle references a live object
+    ///
+    /// @param handle The handle to check
+    /// @return The branded handle, or None if the handle is stale
+    #[must_use]
+    pub fn check(
+        &self,
+        handle: &Handle<T>,
+    ) -> Option<BrandedHandle<'brand, T>> {
+        let index = self.vector.resolve(handle)?;
+        Some(BrandedHandle::new(index))
+    }
+
+    /// Returns an iterator over branded handles to all the objects, in data
+    /// order
+    pub fn handles(
+        &self,
+    ) -> impl Iterator<Item = BrandedHandle<'brand, T>> + use<'brand, T> {
+        (0..self.vector.len()).map(BrandedHandle::new)
+    }
+
+    /// Returns the object referenced by the branded handle
+    #[must_use]
+    pub fn get(&self, handle: BrandedHandle<'brand, T>) -> &T {
+        // SAFETY: the handle was checked in this scope, during which no
+        // object can be removed
+        unsafe { self.vector.data.get_unchecked(handle.index) }
+    }
+
+    /// Returns the object referenced by the branded handle mutably
+    pub fn get_mut(&mut self, handle: BrandedHandle<'brand, T>) -> &mut T {
+        self.vector.assert_not_frozen();
+        self.vector.touch(handle.index);
+        // SAFETY: the handle was checked in this scope, during which no
+        // object can be removed
+        unsafe { self.vector.data.get_unchecked_mut(handle.index) }
+    }
+
+    /// Returns the plain handle of the object referenced by the branded
+    /// handle
+    #[must_use]
+    pub fn handle(&self, handle: BrandedHandle<'brand, T>) -> Handle<T> {
+        // SAFETY: the handle was checked in this scope, during which no
+        // object can be removed
+        let md = unsafe { self.vector.metadata.get_unchecked(handle.index) };
+        Handle::new(md.reverse_id, md.validity_id)
+    }
+
+    /// Returns the number of objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    /// Tells if the vector is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+}
+
+impl<T> BrandedHandle<'_, T> {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            _brand: PhantomData,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branded_access() {
+        let mut vec: Vector<u32> = (0..5).collect();
+        vec.erase_by_id(1);
+        let stale = Handle::new(1, 0);
+        let live = vec.create_handle(4).unwrap();
+
+        let sum = vec.branded(|mut vec| {
+            assert!(vec.check(&stale).is_none());
+            let checked = vec.check(&live).unwrap();
+            *vec.get_mut(checked) = 40;
+            assert_eq!(vec.handle(checked), live);
+            let handles: Vec<_> = vec.handles().collect();
+            handles
+                .into_iter()
+                .map(|handle| *vec.get(handle))
+                .sum::<u32>()
+        });
+
+        assert_eq!(sum, 45);
+        assert_eq!(vec.get(&live), Some(&40));
+    }
+}
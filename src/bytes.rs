@@ -0,0 +1,513 @@
+//! A stable binary format for vectors, independent of any serialization
+//! framework.
+//!
+//! All integers are little endian. Version 3 of the layout is:
+//!
+//! 1. magic: the 4 bytes `SIVB`
+//! 2. version: `u16`, currently 3
+//! 3. object count: `u64`
+//! 4. slot count: `u64`
+//! 5. retired count: `u64`
+//! 6. identity: a 0 byte, or a 1 byte followed by the `u128` identity
+//! 7. generation floor: `u64`
+//! 8. slots, per slot: the `reverse_id` and `validity_id` of the metadata,
+//!    then the reuse count, insertion tick, modification count and group of
+//!    the slot state, each as a `u64`
+//! 9. indices, per slot: a `u64`
+//! 10. objects, per object: its `BinaryElement` encoding
+//!
+//! Version 2 is identical except it has no generation floor, which decodes
+//! as 0, and version 1 has no identity field either. Both are still
+//! accepted when decoding.
+//!
+//! Multi-byte elements use the same little endian convention, `bool` is a
+//! single 0 or 1 byte and `String` is a `u64` byte length followed by UTF-8.
+//! A `Handle` is its ID followed by its validity ID, each as a `u64`, and a
+//! `StampedHandle` is the handle followed by the identity encoded as in the
+//! header.
+
+use crate::{ID, metadata::Metadata, raw::RawParts, raw::RawPartsError};
+use crate::extras::SlotExtras;
+use crate::handle::Handle;
+use crate::identity::{StampedHandle, VectorId};
+use crate::vector::Vector;
+use std::fmt;
+
+/// The magic bytes starting every encoded vector.
+pub const BYTES_MAGIC: [u8; 4] = *b"SIVB";
+
+/// The version of the layout written by `to_bytes`.
+pub const BYTES_VERSION: u16 = 3;
+
+/// An object that can be stored in the stable binary format.
+pub trait BinaryElement: Sized {
+    /// Appends the encoding of the object to @p out
+    fn write_bytes(&self, out: &mut Vec<u8>);
+
+    /// Decodes an object from the start of @p input, advancing it past the
+    /// consumed bytes
+    ///
+    /// @return The object, or None if the bytes are not a valid encoding
+    fn read_bytes(input: &mut &[u8]) -> Option<Self>;
+}
+
+/// The reasons an encoded vector can be rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BytesError {
+    /// The input does not start with `BYTES_MAGIC`.
+    BadMagic,
+    /// The input was written with an unknown version of the layout.
+    UnsupportedVersion(u16),
+    /// The input ended before the announced content.
+    UnexpectedEnd,
+    /// The object at this data index could not be decoded.
+    InvalidElement(usize),
+    /// The input has bytes after the announced content.
+    TrailingBytes,
+    /// The decoded state is inconsistent.
+    Corrupted(RawPartsError),
+}
+
+impl fmt::Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytesError::BadMagic => write!(f, "missing magic bytes"),
+            BytesError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            BytesError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            BytesError::InvalidElement(index) => {
+                write!(f, "invalid object at data index {index}")
+            }
+            BytesError::TrailingBytes => write!(f, "trailing bytes"),
+            BytesError::Corrupted(error) => {
+                write!(f, "corrupted state: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BytesError {}
+
+impl From<RawPartsError> for BytesError {
+    fn from(error: RawPartsError) -> Self {
+        BytesError::Corrupted(error)
+    }
+}
+
+impl<T: BinaryElement> Vector<T> {
+    /// Encodes the vector in the stable binary format, keeping all IDs and
+    /// validity IDs. The IDs reserved by an IdReserver are encoded as free
+    /// slots, like the other reserved IDs.
+    ///
+    /// @return The encoded bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (metadata, indices) = self.encoded_slots();
+        let mut out = Vec::new();
+        out.extend_from_slice(&BYTES_MAGIC);
+        out.extend_from_slice(&BYTES_VERSION.to_le_bytes());
+        write_u64(&mut out, self.data.len());
+        write_u64(&mut out, metadata.len());
+        write_u64(&mut out, self.retired_count());
+        self.identity().write_bytes(&mut out);
+        write_u64(&mut out, self.generation_floor());
+        let extras = self.slot_extras();
+        for md in metadata.iter() {
+            write_u64(&mut out, md.reverse_id);
+            write_u64(&mut out, md.validity_id);
+            write_u64(&mut out, extras.reuse_count(md.reverse_id));
+            let tick = extras.insertion_tick(md.reverse_id);
+            out.extend_from_slice(&tick.to_le_bytes());
+            write_u64(&mut out, extras.modification_count(md.reverse_id));
+            write_u64(&mut out, extras.group(md.reverse_id));
+        }
+        for &index in indices.iter() {
+            write_u64(&mut out, index);
+        }
+        for object in &self.data {
+            object.write_bytes(&mut out);
+        }
+        out
+    }
+
+    /// Decodes a vector written by `to_bytes`
+    ///
+    /// @param bytes The encoded bytes
+    /// @return The vector, or the reason the bytes were rejected
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BytesError> {
+        Ok(Self::from_raw_parts(decode(bytes)?)?)
+    }
+
+    /// Decodes a vector written by `to_bytes`, salvaging the consistent
+    /// slots when the state is corrupted
+    ///
+    /// @param bytes The encoded bytes
+    /// @return The vector and the broken IDs, or the reason the bytes could
+    /// not be read at all
+    pub fn from_bytes_lenient(
+        bytes: &[u8],
+    ) -> Result<(Self, Vec<ID>), BytesError> {
+        Ok(Self::from_raw_parts_lenient(decode(bytes)?))
+    }
+}
+
+/// Size in bytes of an encoded metadata entry and its index
+const SLOT_SIZE: usize = 7 * 8;
+
+/// Reads the raw state out of the encoded bytes
+fn decode<T: BinaryElement>(
+    mut input: &[u8],
+) -> Result<RawParts<T>, BytesError> {
+    let input = &mut input;
+    if take(input, 4)? != BYTES_MAGIC {
+        return Err(BytesError::BadMagic);
+    }
+    let version = u16::from_le_bytes(take(input, 2)?.try_into().unwrap());
+    if version == 0 || version > BYTES_VERSION {
+        return Err(BytesError::UnsupportedVersion(version));
+    }
+    let object_count = read_u64(input)?;
+    let slot_count = read_u64(input)?;
+    let retired = read_u64(input)?;
+    let identity = match version {
+        1 => None,
+        _ => Option::<VectorId>::read_bytes(input)
+            .ok_or(BytesError::UnexpectedEnd)?,
+    };
+    let generation_floor = match version {
+        1 | 2 => 0,
+        _ => read_u64(input)?,
+    };
+    if slot_count.saturating_mul(SLOT_SIZE) > input.len() {
+        return Err(BytesError::UnexpectedEnd);
+    }
+
+    let mut metadata = Vec::with_capacity(slot_count);
+    let mut extras = SlotExtras::default();
+    for _ in 0..slot_count {
+        let md = Metadata::new(read_u64(input)?, read_u64(input)?);
+        let id = md.reverse_id;
+        let reuse_count = read_u64(input)?;
+        let insertion_tick = read_raw_u64(input)?;
+        let modification_count = read_u64(input)?;
+        let group = read_u64(input)?;
+        // The slots with an ID out of range are rejected later, their state
+        // is dropped so it can not make the decoding allocate without bound
+        if id < slot_count {
+            extras.set_reuse_count(id, reuse_count);
+            extras.set_insertion_tick(id, insertion_tick);
+            extras.set_modification_count(id, modification_count);
+            extras.set_group(id, group);
+        }
+        metadata.push(md);
+    }
+    let mut indices = Vec::with_capacity(slot_count);
+    for _ in 0..slot_count {
+        indices.push(read_u64(input)?);
+    }
+    let mut data = Vec::new();
+    for index in 0..object_count {
+        if input.is_empty() {
+            return Err(BytesError::UnexpectedEnd);
+        }
+        let object = T::read_bytes(input);
+        data.push(object.ok_or(BytesError::InvalidElement(index))?);
+    }
+    if !input.is_empty() {
+        return Err(BytesError::TrailingBytes);
+    }
+    Ok(RawParts {
+        data,
+        metadata,
+        indices,
+        retired,
+        identity,
+        generation_floor,
+        extras,
+    })
+}
+
+/// Splits @p len bytes off the start of @p input
+fn take<'a>(
+    input: &mut &'a [u8],
+    len: usize,
+) -> Result<&'a [u8], BytesError> {
+    if input.len() < len {
+        return Err(BytesError::UnexpectedEnd);
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+/// Writes an ID or count as a `u64`
+fn write_u64(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u64).to_le_bytes());
+}
+
+/// Reads a little endian `u64`
+fn read_raw_u64(input: &mut &[u8]) -> Result<u64, BytesError> {
+    let bytes = take(input, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Reads an ID or count written by `write_u64`
+fn read_u64(input: &mut &[u8]) -> Result<usize, BytesError> {
+    let value = read_raw_u64(input)?;
+    usize::try_from(value).map_err(|_| BytesError::UnexpectedEnd)
+}
+
+macro_rules! impl_binary_element {
+    ($($ty:ty),*) => {
+        $(
+            impl BinaryElement for $ty {
+                fn write_bytes(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+                    let bytes = take(input, size_of::<$ty>()).ok()?;
+                    Some(<$ty>::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_element!(
+    u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64
+);
+
+impl BinaryElement for bool {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match take(input, 1).ok()? {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl BinaryElement for String {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.len());
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let len = read_u64(input).ok()?;
+        let bytes = take(input, len).ok()?;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl BinaryElement for Option<VectorId> {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(id) => {
+                out.push(1);
+                id.write_bytes(out);
+            }
+        }
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match take(input, 1).ok()? {
+            [0] => Some(None),
+            [1] => Some(Some(VectorId::read_bytes(input)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<T> BinaryElement for Handle<T> {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        write_u64(out, self.id);
+        write_u64(out, self.generation());
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let id = read_u64(input).ok()?;
+        let validity_id = read_u64(input).ok()?;
+        Handle::try_new(id, validity_id)
+    }
+}
+
+impl<T> BinaryElement for StampedHandle<T> {
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        self.handle.write_bytes(out);
+        self.vector_id.write_bytes(out);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let handle = Handle::read_bytes(input)?;
+        let vector_id = Option::<VectorId>::read_bytes(input)?;
+        Some(StampedHandle::new(handle, vector_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut vec = Vector::default();
+        let id_a = vec.push(String::from("a"));
+        let id_b = vec.push(String::from("bee"));
+        let id_c = vec.push(String::from("sea"));
+        vec.erase_by_id(id_a);
+        vec.retire_id(id_c);
+        let h_b = vec.create_handle(id_b).unwrap();
+
+        let decoded = Vector::<String>::from_bytes(&vec.to_bytes()).unwrap();
+
+        assert_eq!(decoded.get(&h_b).map(String::as_str), Some("bee"));
+        assert!(decoded.is_retired(id_c));
+        assert_eq!(decoded.get_next_id(), id_a);
+    }
+
+    #[test]
+    fn test_bytes_layout_is_stable() {
+        let mut vec = Vector::default();
+        vec.push(7u8);
+
+        let bytes = vec.to_bytes();
+
+        assert_eq!(&bytes[..6], b"SIVB\x03\x00");
+        assert_eq!(&bytes[6..14], &1u64.to_le_bytes());
+        assert_eq!(bytes[30], 0);
+        assert_eq!(&bytes[31..39], &0u64.to_le_bytes());
+        assert_eq!(bytes.len(), 6 + 3 * 8 + 1 + 8 + SLOT_SIZE + 1);
+        assert_eq!(bytes[bytes.len() - 1], 7);
+    }
+
+    #[test]
+    fn test_bytes_errors() {
+        let mut vec = Vector::default();
+        vec.push(1u32);
+        let bytes = vec.to_bytes();
+
+        assert_eq!(
+            Vector::<u32>::from_bytes(b"nope"),
+            Err(BytesError::BadMagic)
+        );
+        assert_eq!(
+            Vector::<u32>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BytesError::InvalidElement(0))
+        );
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(
+            Vector::<u32>::from_bytes(&longer),
+            Err(BytesError::TrailingBytes)
+        );
+        let mut newer = bytes;
+        newer[4] = 4;
+        assert_eq!(
+            Vector::<u32>::from_bytes(&newer),
+            Err(BytesError::UnsupportedVersion(4))
+        );
+    }
+
+    #[test]
+    fn test_bytes_lenient() {
+        let mut vec = Vector::default();
+        vec.push(1u16);
+        vec.push(2u16);
+        let mut bytes = vec.to_bytes();
+        // Corrupt the reverse ID of the first slot
+        bytes[39] = 1;
+
+        assert!(Vector::<u16>::from_bytes(&bytes).is_err());
+        let (vec, broken) = Vector::<u16>::from_bytes_lenient(&bytes).unwrap();
+        assert_eq!(broken, vec![0]);
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_bytes_version_1_is_accepted() {
+        let mut vec = Vector::default();
+        vec.assign_identity();
+        vec.push(9u8);
+        let mut bytes = vec.to_bytes();
+        bytes[4] = 1;
+        bytes.drain(30..55);
+
+        let decoded = Vector::<u8>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.identity(), None);
+        assert_eq!(decoded.as_slice(), [9]);
+    }
+
+    #[test]
+    fn test_bytes_version_2_is_accepted() {
+        let mut vec = Vector::default();
+        let identity = vec.assign_identity();
+        vec.push(9u8);
+        let mut bytes = vec.to_bytes();
+        bytes[4] = 2;
+        bytes.drain(47..55);
+
+        let decoded = Vector::<u8>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.identity(), Some(identity));
+        assert_eq!(decoded.as_slice(), [9]);
+    }
+
+    #[test]
+    fn test_bytes_keep_reset_generations() {
+        let mut vec = Vector::default();
+        let old: Vec<Handle<u32>> = (0..2)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+        vec.reset();
+        vec.push(100);
+
+        let mut vec = Vector::<u32>::from_bytes(&vec.to_bytes()).unwrap();
+        vec.push(200);
+
+        assert_eq!(vec.get(&old[0]), None);
+        assert_eq!(vec.get(&old[1]), None);
+    }
+
+    #[test]
+    fn test_bytes_keep_pending_reservations() {
+        let mut vec: Vector<u32> = (0..2).collect();
+        let reserved = vec.id_reserver().reserve(2);
+
+        let mut vec = Vector::<u32>::from_bytes(&vec.to_bytes()).unwrap();
+        assert_eq!(vec.indices.len(), 4);
+        vec.push(10);
+        vec.push(20);
+
+        for reservation in reserved {
+            assert!(!vec.contains(&reservation.handle()));
+        }
+    }
+
+    #[test]
+    fn test_stamped_handle_bytes() {
+        let mut vec = Vector::default();
+        let identity = vec.assign_identity();
+        let id = vec.push(1u8);
+        let stamped = vec.stamp(vec.create_handle(id).unwrap());
+
+        let mut bytes = Vec::new();
+        stamped.write_bytes(&mut bytes);
+        let decoded = StampedHandle::<u8>::read_bytes(&mut &bytes[..]);
+
+        assert_eq!(decoded, Some(stamped));
+        assert_eq!(decoded.unwrap().vector_id, Some(identity));
+        let restored = Vector::<u8>::from_bytes(&vec.to_bytes()).unwrap();
+        assert_eq!(restored.resolve_stamped(&stamped), Some(stamped.handle));
+    }
+}
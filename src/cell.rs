@@ -0,0 +1,131 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use qcell::{LCell, LCellOwner};
+
+/// Vectors of LCell allow mutating objects through a shared reference to
+/// the vector. The owner of the cells grants the access, and the borrow
+/// checker enforces through it that only one object is mutated at a time,
+/// with no runtime check such as the ones of RefCell.
+///
+/// ```
+/// use qcell::{LCell, LCellOwner};
+/// use stable_index_vector::Vector;
+///
+/// LCellOwner::scope(|mut owner| {
+///     let mut vec = Vector::default();
+///     let a = vec.push(LCell::new(1));
+///     let b = vec.push(LCell::new(2));
+///     let a = vec.create_handle(a).unwrap();
+///     let b = vec.create_handle(b).unwrap();
+///
+///     let shared = &vec;
+///     *shared.borrow_mut(&a, &mut owner).unwrap() += 10;
+///     *shared.borrow_mut(&b, &mut owner).unwrap() += 20;
+///
+///     assert_eq!(shared.borrow(&a, &owner), Some(&11));
+///     assert_eq!(shared.borrow(&b, &owner), Some(&22));
+/// });
+/// ```
+impl<'id, T> Vector<LCell<'id, T>> {
+    /// Returns a reference to the content of the cell referenced by the
+    /// handle
+    ///
+    /// @param handle The handle referencing the cell
+    /// @param owner The owner of the cells
+    /// @return The content, or None if the handle is stale
+    pub fn borrow<'a>(
+        &'a self,
+        handle: &Handle<LCell<'id, T>>,
+        owner: &'a LCellOwner<'id>,
+    ) -> Option<&'a T> {
+        Some(owner.ro(self.get(handle)?))
+    }
+
+    /// Returns a mutable reference to the content of the cell referenced by
+    /// the handle, through a shared reference to the vector
+    ///
+    /// @param handle The handle referencing the cell
+    /// @param owner The owner of the cells
+    /// @return The content, or None if the handle is stale
+    pub fn borrow_mut<'a>(
+        &'a self,
+        handle: &Handle<LCell<'id, T>>,
+        owner: &'a mut LCellOwner<'id>,
+    ) -> Option<&'a mut T> {
+        Some(owner.rw(self.get(handle)?))
+    }
+
+    /// Returns mutable references to the contents of two distinct cells,
+    /// through a shared reference to the vector
+    ///
+    /// @param a The handle referencing the first cell
+    /// @param b The handle referencing the second cell
+    /// @param owner The owner of the cells
+    /// @return The contents, or None if a handle is stale or both handles
+    /// reference the same cell
+    pub fn borrow_mut2<'a>(
+        &'a self,
+        a: &Handle<LCell<'id, T>>,
+        b: &Handle<LCell<'id, T>>,
+        owner: &'a mut LCellOwner<'id>,
+    ) -> Option<(&'a mut T, &'a mut T)> {
+        if a.id == b.id {
+            return None;
+        }
+        Some(owner.rw2(self.get(a)?, self.get(b)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutation_through_shared_vector() {
+        LCellOwner::scope(|mut owner| {
+            let mut vec = Vector::default();
+            for i in 0..4 {
+                vec.push(LCell::new(i));
+            }
+
+            let shared = &vec;
+            for cell in shared.iter() {
+                *owner.rw(cell) *= 10;
+            }
+
+            let values: Vec<i32> =
+                shared.iter().map(|cell| *owner.ro(cell)).collect();
+            assert_eq!(values, vec![0, 10, 20, 30]);
+        });
+    }
+
+    #[test]
+    fn test_borrow_two_cells() {
+        LCellOwner::scope(|mut owner| {
+            let mut vec = Vector::default();
+            let a = vec.push(LCell::new(1));
+            let b = vec.push(LCell::new(2));
+            let a = vec.create_handle(a).unwrap();
+            let b = vec.create_handle(b).unwrap();
+
+            let (x, y) = vec.borrow_mut2(&a, &b, &mut owner).unwrap();
+            std::mem::swap(x, y);
+
+            assert_eq!(vec.borrow(&a, &owner), Some(&2));
+            assert_eq!(vec.borrow(&b, &owner), Some(&1));
+            assert!(vec.borrow_mut2(&a, &a, &mut owner).is_none());
+        });
+    }
+
+    #[test]
+    fn test_stale_handle() {
+        LCellOwner::scope(|mut owner| {
+            let mut vec = Vector::default();
+            let id = vec.push(LCell::new('a'));
+            let handle = vec.create_handle(id).unwrap();
+            vec.erase_by_handle(&handle);
+
+            assert!(vec.borrow_mut(&handle, &mut owner).is_none());
+        });
+    }
+}
@@ -0,0 +1,151 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::marker::PhantomData;
+
+/// A structural change queued in a CommandBuffer.
+#[derive(Debug)]
+enum Command<T> {
+    /// Insert the object.
+    Push(T),
+    /// Remove the object referenced by the handle, if it is still live.
+    Erase(Handle<T>),
+}
+
+/// A queue of insertions and removals applied later to a Vector with
+/// `Vector::apply`, so they can be requested while the vector is borrowed,
+/// e.g. while iterating over it.
+#[derive(Debug)]
+pub struct CommandBuffer<T> {
+    /// The changes, in the order they are applied.
+    commands: Vec<Command<T>>,
+    /// The number of queued insertions.
+    pushes: usize,
+}
+
+/// A placeholder for the handle of an object queued in a CommandBuffer,
+/// resolved once the buffer is applied.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PendingHandle<T> {
+    /// The rank of the insertion among the insertions of the buffer.
+    index: usize,
+    /// Prevent type collisions, like for Handle.
+    _marker: PhantomData<T>,
+}
+
+impl<T> Copy for PendingHandle<T> {}
+
+impl<T> Clone for PendingHandle<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> PendingHandle<T> {
+    /// Returns the rank of the insertion among the insertions of the buffer
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the handle the object got when the buffer was applied
+    ///
+    /// @param handles The handles returned by `Vector::apply`
+    /// @return The handle of the object
+    #[must_use]
+    pub fn resolve(&self, handles: &[Handle<T>]) -> Handle<T> {
+        handles[self.index]
+    }
+}
+
+impl<T> CommandBuffer<T> {
+    /// Queues the insertion of the object
+    ///
+    /// @param object The object to insert
+    /// @return The placeholder of the handle of the object
+    pub fn push(&mut self, object: T) -> PendingHandle<T> {
+        self.commands.push(Command::Push(object));
+        self.pushes += 1;
+        PendingHandle { index: self.pushes - 1, _marker: PhantomData }
+    }
+
+    /// Queues the removal of the object referenced by the handle. The
+    /// removal is skipped if the handle is stale by the time it is applied.
+    ///
+    /// @param handle The handle referencing the object to remove
+    pub fn erase(&mut self, handle: Handle<T>) {
+        self.commands.push(Command::Erase(handle));
+    }
+
+    /// Returns the number of queued changes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Tells if no change is queued
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Forgets all the queued changes
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.pushes = 0;
+    }
+}
+
+impl<T> Default for CommandBuffer<T> {
+    fn default() -> Self {
+        Self { commands: Vec::new(), pushes: 0 }
+    }
+}
+
+impl<T> Vector<T> {
+    /// Applies the queued changes, in the order they were queued
+    ///
+    /// @param commands The changes to apply
+    /// @return The handles of the inserted objects, to resolve the
+    /// placeholders with
+    pub fn apply(&mut self, commands: CommandBuffer<T>) -> Vec<Handle<T>> {
+        let mut handles = Vec::with_capacity(commands.pushes);
+        for command in commands.commands {
+            match command {
+                Command::Push(object) => {
+                    let id = self.push(object);
+                    handles.push(Handle::new(id, self.get_validity_id(id)));
+                }
+                Command::Erase(handle) => {
+                    self.remove(&handle);
+                }
+            }
+        }
+        handles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_commands() {
+        let mut vec: Vector<u32> = (0..4).collect();
+        let mut commands = CommandBuffer::default();
+        let mut spawned = Vec::new();
+        for (handle, &object) in vec.iter_with_handles() {
+            if object % 2 == 0 {
+                commands.erase(handle);
+                spawned.push(commands.push(object + 10));
+            }
+        }
+        let stale = vec.create_handle(0).unwrap();
+        commands.erase(stale);
+        assert_eq!(commands.len(), 5);
+
+        let handles = vec.apply(commands);
+        assert_eq!(handles.len(), 2);
+        assert_eq!(vec.len(), 4);
+        assert_eq!(vec.get(&spawned[0].resolve(&handles)), Some(&10));
+        assert_eq!(vec.get(&spawned[1].resolve(&handles)), Some(&12));
+        assert!(!vec.contains(&stale));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::vector::Vector;
+use crate::view::SharedView;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A vector whose state can be shared in O(1) with `snapshot_arc`. The
+/// snapshots and the vector share the same allocation until the vector is
+/// written while a snapshot is alive, the first write then cloning it.
+#[derive(Debug)]
+pub struct CowVector<T> {
+    /// The current state, shared with the live snapshots
+    vector: Arc<Vector<T>>,
+}
+
+impl<T> CowVector<T> {
+    /// Factory constructor
+    pub fn new(vector: Vector<T>) -> Self {
+        Self {
+            vector: Arc::new(vector),
+        }
+    }
+
+    /// Returns a read-only view of the current state, without copying it
+    pub fn snapshot_arc(&self) -> SharedView<T> {
+        SharedView::from_arc(Arc::clone(&self.vector))
+    }
+
+    /// Tells if a snapshot still shares the current state, in which case
+    /// the next write clones it
+    #[must_use]
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.vector) > 1
+    }
+
+    /// Returns the vector to write it, cloning it first if a snapshot
+    /// shares it
+    pub fn write(&mut self) -> &mut Vector<T>
+    where
+        T: Clone,
+    {
+        Arc::make_mut(&mut self.vector)
+    }
+
+    /// Consumes the copy-on-write vector to return the current state,
+    /// cloning it if a snapshot shares it
+    pub fn into_inner(self) -> Vector<T>
+    where
+        T: Clone,
+    {
+        Arc::unwrap_or_clone(self.vector)
+    }
+}
+
+impl<T> Clone for CowVector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            vector: Arc::clone(&self.vector),
+        }
+    }
+}
+
+impl<T> Default for CowVector<T> {
+    fn default() -> Self {
+        Self::new(Vector::default())
+    }
+}
+
+impl<T> Deref for CowVector<T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vector
+    }
+}
+
+impl<T> From<Vector<T>> for CowVector<T> {
+    fn from(vector: Vector<T>) -> Self {
+        Self::new(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cow_snapshots() {
+        let mut cow = CowVector::new((0..3).collect::<Vector<i32>>());
+        let handle = cow.create_handle(1).unwrap();
+        let data = cow.data.as_ptr();
+
+        let snapshot = cow.snapshot_arc();
+        assert!(cow.is_shared());
+        assert_eq!(snapshot.data.as_ptr(), data);
+
+        cow.write().replace(&handle, 10).unwrap();
+        assert!(!cow.is_shared());
+        assert_eq!(snapshot.get(&handle), Some(&1));
+        assert_eq!(cow.get(&handle), Some(&10));
+
+        drop(snapshot);
+        cow.write().push(3);
+        assert_eq!(cow.into_inner().len(), 4);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// A cursor walking the objects of a vector in data order, produced by
+/// `Vector::cursor_mut`. Removing the current object moves the last object
+/// in its place, which then becomes the current object, so no object is
+/// skipped.
+#[derive(Debug)]
+pub struct CursorMut<'a, T> {
+    /// The walked vector
+    vector: &'a mut Vector<T>,
+    /// The data index of the current object
+    data_index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Factory constructor
+    pub(crate) fn new(vector: &'a mut Vector<T>) -> Self {
+        Self {
+            vector,
+            data_index: 0,
+        }
+    }
+
+    /// Returns the current object, or None once the cursor went past the
+    /// last object
+    pub fn current(&mut self) -> Option<&mut T> {
+        if self.data_index >= self.vector.data.len() {
+            return None;
+        }
+        self.vector.touch(self.data_index);
+        Some(&mut self.vector.data[self.data_index])
+    }
+
+    /// Returns a handle to the current object
+    #[must_use]
+    pub fn current_handle(&self) -> Option<Handle<T>> {
+        self.vector.create_handle_from_data(self.data_index)
+    }
+
+    /// Moves the cursor to the next object
+    pub fn move_next(&mut self) {
+        if self.data_index < self.vector.data.len() {
+            self.data_index += 1;
+        }
+    }
+
+    /// Removes the current object. The cursor then points at the object that
+    /// took its place.
+    ///
+    /// @return The removed object, or None if there is no current object
+    pub fn remove_current(&mut self) -> Option<T> {
+        let id = self.current_handle()?.id;
+        Some(self.vector.remove_slot(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_during_walk() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = [1, 2, 4, 6, 7, 8]
+            .into_iter()
+            .map(|value| {
+                let id = vec.push(value);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        let mut cursor = vec.cursor_mut();
+        let mut visited = 0;
+        while let Some(value) = cursor.current() {
+            visited += 1;
+            if *value % 2 == 0 {
+                cursor.remove_current();
+            } else {
+                *value *= 10;
+                cursor.move_next();
+            }
+        }
+
+        assert_eq!(visited, 6);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(&handles[0]), Some(&10));
+        assert_eq!(vec.get(&handles[4]), Some(&70));
+    }
+
+    #[test]
+    fn test_empty_cursor() {
+        let mut vec: Vector<u8> = Vector::default();
+        let mut cursor = vec.cursor_mut();
+
+        assert!(cursor.current().is_none());
+        assert!(cursor.current_handle().is_none());
+        assert!(cursor.remove_current().is_none());
+        cursor.move_next();
+    }
+}
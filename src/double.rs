@@ -0,0 +1,295 @@
+use crate::ID;
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::ops::Deref;
+
+/// A structural change written to the back copy during a frame, replayed
+/// on the other copy by `DoubleBuffered::flip`.
+#[derive(Clone, Debug)]
+enum FrameOp<T> {
+    /// Insert the object, cloned when it was written.
+    Push(T),
+    /// Remove the object with the ID.
+    Erase(ID),
+}
+
+/// The changes written during a frame.
+#[derive(Clone, Debug)]
+struct FrameLog<T> {
+    /// The structural changes, in the order they were written.
+    ops: Vec<FrameOp<T>>,
+    /// The handles of the objects that may have been modified in place.
+    modified: Vec<Handle<T>>,
+    /// Whether every object may have been modified in place.
+    all_modified: bool,
+}
+
+impl<T> Default for FrameLog<T> {
+    fn default() -> Self {
+        Self { ops: Vec::new(), modified: Vec::new(), all_modified: false }
+    }
+}
+
+impl<T: Clone> FrameLog<T> {
+    /// Brings @p target up to date with @p source, which went through the
+    /// changes of the log while both copies were identical
+    fn replay(self, source: &Vector<T>, target: &mut Vector<T>) {
+        // The same changes give the same IDs and generations on both sides
+        for op in self.ops {
+            match op {
+                FrameOp::Push(object) => {
+                    target.push(object);
+                }
+                FrameOp::Erase(id) => target.erase_by_id(id),
+            }
+        }
+        if self.all_modified {
+            for (stale, object) in target.iter_mut().zip(source.iter()) {
+                stale.clone_from(object);
+            }
+            return;
+        }
+        for handle in self.modified {
+            if let (Some(object), Some(stale)) =
+                (source.get(&handle), target.get_mut(&handle))
+            {
+                stale.clone_from(object);
+            }
+        }
+    }
+}
+
+/// Two copies of a Vector for frame based simulations: the front copy holds
+/// the state of the last frame and can be read while the next frame is
+/// written to the back copy. `flip` publishes the back copy and replays the
+/// changes of the frame on the other one, so both copies always agree on
+/// the IDs and the generations of the objects.
+#[derive(Clone, Debug)]
+pub struct DoubleBuffered<T> {
+    /// The state of the last frame.
+    front: Vector<T>,
+    /// The state being written.
+    back: Vector<T>,
+    /// The changes written to the back copy since the last flip.
+    log: FrameLog<T>,
+}
+
+/// The write access to the back copy of a DoubleBuffered, recording the
+/// changes so they can be replayed on the other copy. Reads go through
+/// the back copy directly.
+#[derive(Debug)]
+pub struct FrameWriter<'a, T> {
+    /// The state being written.
+    back: &'a mut Vector<T>,
+    /// The changes of the frame.
+    log: &'a mut FrameLog<T>,
+}
+
+impl<T: Clone> DoubleBuffered<T> {
+    /// Creates the two copies from the initial state
+    ///
+    /// @param vector The initial state, readable and writable at once
+    /// @return The double buffered vector
+    #[must_use]
+    pub fn new(vector: Vector<T>) -> Self {
+        Self {
+            back: vector.clone(),
+            front: vector,
+            log: FrameLog::default(),
+        }
+    }
+
+    /// Makes the written state the readable one, then replays the changes
+    /// of the frame on the other copy so the next frame starts from the
+    /// state just published. Only the objects inserted or modified during
+    /// the frame are cloned.
+    pub fn flip(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        std::mem::take(&mut self.log).replay(&self.front, &mut self.back);
+    }
+}
+
+impl<T> DoubleBuffered<T> {
+    /// Returns the state of the last frame
+    #[must_use]
+    pub fn read(&self) -> &Vector<T> {
+        &self.front
+    }
+
+    /// Returns the state of the next frame, to write it
+    pub fn write(&mut self) -> FrameWriter<'_, T> {
+        FrameWriter { back: &mut self.back, log: &mut self.log }
+    }
+
+    /// Returns the state of the last frame and the state of the next one at
+    /// once, so they can be used by different threads
+    pub fn split(&mut self) -> (&Vector<T>, FrameWriter<'_, T>) {
+        (
+            &self.front,
+            FrameWriter { back: &mut self.back, log: &mut self.log },
+        )
+    }
+
+    /// Consumes the double buffered vector to return the state of the last
+    /// frame
+    pub fn into_front(self) -> Vector<T> {
+        self.front
+    }
+}
+
+impl<T: Clone> FrameWriter<'_, T> {
+    /// Inserts an object, like `Vector::push`
+    ///
+    /// @param object The object to insert, cloned for the other copy
+    /// @return The ID of the object
+    pub fn push(&mut self, object: T) -> ID {
+        self.log.ops.push(FrameOp::Push(object.clone()));
+        self.back.push(object)
+    }
+}
+
+impl<T> FrameWriter<'_, T> {
+    /// Removes the object with the provided ID, like `Vector::erase_by_id`
+    ///
+    /// @param id The ID of the object to remove
+    pub fn erase_by_id(&mut self, id: ID) {
+        self.back.erase_by_id(id);
+        self.log.ops.push(FrameOp::Erase(id));
+    }
+
+    /// Removes the object referenced by the handle, like
+    /// `Vector::erase_by_handle`
+    ///
+    /// @param handle The handle referencing the object to remove
+    pub fn erase_by_handle(&mut self, handle: &Handle<T>) {
+        self.erase_by_id(handle.id);
+    }
+
+    /// Removes the object referenced by the handle and returns it
+    ///
+    /// @param handle The handle referencing the object to remove
+    /// @return The removed object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let object = self.back.remove(handle)?;
+        self.log.ops.push(FrameOp::Erase(handle.id));
+        Some(object)
+    }
+
+    /// Returns a mutable reference to the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object, or None if the handle is stale
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        let object = self.back.get_mut(handle)?;
+        self.log.modified.push(*handle);
+        Some(object)
+    }
+
+    /// Returns an iterator over mutable references to the objects. All of
+    /// them are cloned by the next flip.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.log.all_modified = true;
+        self.back.iter_mut()
+    }
+}
+
+impl<T> Deref for FrameWriter<'_, T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.back
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::thread;
+
+    #[test]
+    fn test_flip_publishes_changes() {
+        let mut buffers = DoubleBuffered::new((0..3).collect::<Vector<u32>>());
+        let first = buffers.read().create_handle(0).unwrap();
+
+        let mut writer = buffers.write();
+        writer.erase_by_handle(&first);
+        let spawned = writer.push(10);
+        let spawned = writer.create_handle(spawned).unwrap();
+        let transient = writer.push(20);
+        writer.erase_by_id(transient);
+        writer.iter_mut().for_each(|object| *object += 1);
+        assert_eq!(buffers.read().get(&first), Some(&0));
+        assert_eq!(buffers.read().get(&spawned), None);
+
+        buffers.flip();
+        assert_eq!(buffers.read().get(&first), None);
+        assert_eq!(buffers.read().get(&spawned), Some(&11));
+        assert_eq!(buffers.write().get(&spawned), Some(&11));
+        let (front, back) = buffers.split();
+        assert_eq!(front.metadata, back.metadata);
+        assert_eq!(front.data, back.data);
+
+        let id = buffers.write().push(30);
+        assert_eq!(buffers.read().get_next_id(), id);
+    }
+
+    #[test]
+    fn test_flip_replays_modifications() {
+        let mut buffers = DoubleBuffered::new((0..4).collect::<Vector<u32>>());
+        let handle = buffers.read().create_handle(2).unwrap();
+        *buffers.write().get_mut(&handle).unwrap() = 20;
+        let removed = buffers.read().create_handle(1).unwrap();
+        assert_eq!(buffers.write().remove(&removed), Some(1));
+
+        buffers.flip();
+        buffers.flip();
+        assert_eq!(buffers.read().get(&handle), Some(&20));
+        assert_eq!(buffers.read().get(&removed), None);
+        let (front, back) = buffers.split();
+        assert_eq!(front.metadata, back.metadata);
+        assert_eq!(front.data, back.data);
+    }
+
+    #[derive(Debug)]
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            Self(Rc::clone(&self.0))
+        }
+    }
+
+    #[test]
+    fn test_flip_clones_only_new_objects() {
+        let clones = Rc::new(Cell::new(0));
+        let vector = (0..100).map(|_| Counted(Rc::clone(&clones))).collect();
+        let mut buffers = DoubleBuffered::new(vector);
+        clones.set(0);
+
+        buffers.write().push(Counted(Rc::clone(&clones)));
+        buffers.flip();
+        assert_eq!(clones.get(), 1);
+        assert_eq!(buffers.read().len(), 101);
+        assert_eq!(buffers.write().len(), 101);
+    }
+
+    #[test]
+    fn test_read_while_writing() {
+        let mut buffers =
+            DoubleBuffered::new((0..100).collect::<Vector<u64>>());
+        for frame in 0..3 {
+            let (front, mut back) = buffers.split();
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    assert_eq!(front.iter().sum::<u64>(), 4950 + frame * 100);
+                });
+                back.iter_mut().for_each(|object| *object += 1);
+            });
+            buffers.flip();
+        }
+        assert_eq!(buffers.into_front().iter().sum::<u64>(), 5250);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::ID;
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// A view into a single ID of a vector, produced by `Vector::entry`.
+#[derive(Debug)]
+pub enum Entry<'a, T> {
+    /// The ID references a live object
+    Occupied(OccupiedEntry<'a, T>),
+    /// The ID references a free or taken slot that can be filled
+    Vacant(VacantEntry<'a, T>),
+}
+
+/// An ID referencing a live object.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, T> {
+    /// The vector holding the object
+    vector: &'a mut Vector<T>,
+    /// The ID of the object
+    id: ID,
+}
+
+/// An ID referencing a slot without object.
+#[derive(Debug)]
+pub struct VacantEntry<'a, T> {
+    /// The vector holding the slot
+    vector: &'a mut Vector<T>,
+    /// The ID of the slot
+    id: ID,
+}
+
+impl<'a, T> Entry<'a, T> {
+    /// Factory constructor
+    pub(crate) fn new(vector: &'a mut Vector<T>, id: ID) -> Self {
+        if vector.get_data_index(id) < vector.len() {
+            Entry::Occupied(OccupiedEntry { vector, id })
+        } else {
+            Entry::Vacant(VacantEntry { vector, id })
+        }
+    }
+
+    /// Returns the ID of the entry
+    #[must_use]
+    pub fn id(&self) -> ID {
+        match self {
+            Entry::Occupied(entry) => entry.id,
+            Entry::Vacant(entry) => entry.id,
+        }
+    }
+
+    /// Inserts the object if the entry is vacant
+    ///
+    /// @param object The object to insert in a vacant entry
+    /// @return The object of the entry
+    pub fn or_insert(self, object: T) -> &'a mut T {
+        self.or_insert_with(|| object)
+    }
+
+    /// Inserts the object created by the closure if the entry is vacant
+    ///
+    /// @param make Creates the object to insert in a vacant entry
+    /// @return The object of the entry
+    pub fn or_insert_with<F: FnOnce() -> T>(self, make: F) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(make()),
+        }
+    }
+
+    /// Updates the object if the entry is occupied
+    ///
+    /// @param update Modifies the object of an occupied entry
+    /// @return The entry
+    #[must_use]
+    pub fn and_modify<F: FnOnce(&mut T)>(self, update: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                update(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, T> OccupiedEntry<'a, T> {
+    /// Returns a handle to the object
+    #[must_use]
+    pub fn handle(&self) -> Handle<T> {
+        Handle::new(self.id, self.vector.get_validity_id(self.id))
+    }
+
+    /// Returns a reference to the object
+    #[must_use]
+    pub fn get(&self) -> &T {
+        &self.vector[self.id]
+    }
+
+    /// Returns a mutable reference to the object
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.vector[self.id]
+    }
+
+    /// Converts the entry into a mutable reference to the object
+    pub fn into_mut(self) -> &'a mut T {
+        &mut self.vector[self.id]
+    }
+
+    /// Removes the object, leaving the slot free
+    pub fn remove(self) -> T {
+        self.vector.remove_slot(self.id)
+    }
+}
+
+impl<'a, T> VacantEntry<'a, T> {
+    /// Returns the ID of the slot
+    #[must_use]
+    pub fn id(&self) -> ID {
+        self.id
+    }
+
+    /// Stores the object in the slot. A taken slot gets its object back with
+    /// the same validity, a free slot is reused with a new validity. In
+    /// bounded mode, if the vector is full, the oldest object is removed
+    /// first, like for `push`.
+    ///
+    /// @param object The object to store
+    /// @return A mutable reference to the stored object
+    pub fn insert(self, object: T) -> &'a mut T {
+        self.vector.fill_slot(self.id, object);
+        &mut self.vector[self.id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventOp;
+
+    #[test]
+    fn test_insert_or_update() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let free = vec.push(2);
+        vec.erase_by_id(free);
+
+        *vec.entry(id).unwrap().or_insert(0) += 10;
+        *vec.entry(free).unwrap().or_insert(5) += 10;
+
+        assert_eq!(vec[id], 11);
+        assert_eq!(vec[free], 15);
+        assert_eq!(vec.len(), 2);
+        assert!(vec.entry(7).is_none());
+    }
+
+    #[test]
+    fn test_occupied_entry() {
+        let mut vec = Vector::default();
+        let id = vec.push('a');
+        let handle = vec.create_handle(id).unwrap();
+
+        let Some(Entry::Occupied(entry)) = vec.entry(id) else {
+            panic!("The entry should be occupied");
+        };
+        assert_eq!(entry.handle(), handle);
+        assert_eq!(entry.remove(), 'a');
+        assert!(matches!(vec.entry(id), Some(Entry::Vacant(_))));
+        assert_eq!(vec.get(&handle), None);
+    }
+
+    #[test]
+    fn test_vacant_free_slot_bumps_validity() {
+        let mut vec = Vector::default();
+        let a = vec.push(0);
+        let b = vec.push(1);
+        vec.push(2);
+        let stale = vec.create_handle(a).unwrap();
+        vec.erase_by_id(a);
+        vec.erase_by_id(b);
+
+        vec.entry(a).unwrap().or_insert(3);
+
+        assert_eq!(vec[a], 3);
+        assert_eq!(vec.get(&stale), None);
+        assert_eq!(vec.push(4), b);
+    }
+
+    #[test]
+    fn test_vacant_taken_slot_keeps_handles() {
+        let mut vec = Vector::default();
+        let id = vec.push(0);
+        let handle = vec.create_handle(id).unwrap();
+        vec.take(&handle);
+
+        vec.entry(id).unwrap().or_insert(1);
+
+        assert_eq!(vec.get(&handle), Some(&1));
+    }
+
+    #[test]
+    fn test_vacant_insert_is_observed() {
+        let mut vec = Vector::default();
+        let taken = vec.push(0);
+        let free = vec.push(1);
+        let handle = vec.create_handle(taken).unwrap();
+        vec.take(&handle);
+        vec.erase_by_id(free);
+        vec.set_event_log(true);
+        vec.drain_events();
+
+        vec.entry(taken).unwrap().or_insert(2);
+        vec.entry(free).unwrap().or_insert(3);
+
+        let events = vec.events();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.op == EventOp::Insert));
+        assert_eq!(events[0].handle, handle);
+        assert_eq!(vec.telemetry().inserts, 4);
+    }
+
+    #[test]
+    fn test_vacant_taken_slot_in_bounded_mode() {
+        let mut vec = Vector::default();
+        vec.set_max_len(Some(2));
+        let reserved = vec.reserve_handle();
+        vec.push(1);
+        vec.push(2);
+
+        vec.entry(reserved.get_id()).unwrap().or_insert(3);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(&reserved.handle()), Some(&3));
+
+        // The object entered the eviction order when it was stored
+        vec.push(4);
+        vec.push(5);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(&reserved.handle()), None);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use arc_swap::{ArcSwap, Guard};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// A published state of a Vector, immutable once readers can see it.
+#[derive(Debug)]
+struct Published<T> {
+    /// The state of the vector at the time of publication.
+    vector: Vector<T>,
+    /// How many times the writer published before this state.
+    epoch: u64,
+}
+
+/// The single writer side of a vector read concurrently. Mutations are
+/// applied to a private copy and become visible to the readers all at once
+/// when `publish` is called.
+///
+/// @note Every publication clones the whole vector, which costs O(n) in the
+/// number of slots, so this suits workloads where reads vastly outnumber
+/// publications.
+#[derive(Debug)]
+pub struct EpochWriter<T> {
+    /// The private copy the mutations are applied to.
+    vector: Vector<T>,
+    /// The state the readers resolve handles against.
+    published: Arc<ArcSwap<Published<T>>>,
+    /// The epoch of the last publication.
+    epoch: u64,
+}
+
+/// A reader of the states published by an EpochWriter. Loading the current
+/// state never blocks nor waits for the writer, and does not touch any
+/// reference count shared with the other readers. The published states do
+/// not count the accesses with stale handles either, so reading never
+/// writes memory shared with the other readers.
+#[derive(Debug)]
+pub struct EpochReader<T> {
+    /// The state published by the writer.
+    published: Arc<ArcSwap<Published<T>>>,
+}
+
+/// A published state pinned by a reader. The writer may publish newer
+/// states in the meantime, this one stays alive until the guard is dropped.
+pub struct EpochGuard<T> {
+    /// The pinned state.
+    guard: Guard<Arc<Published<T>>>,
+}
+
+impl<T> Published<T> {
+    /// Factory constructor, making the state ready to be read concurrently
+    fn new(mut vector: Vector<T>, epoch: u64) -> Self {
+        vector.ignore_stale_accesses();
+        Self { vector, epoch }
+    }
+}
+
+impl<T: Clone> EpochWriter<T> {
+    /// Creates a writer and publishes the initial state of the vector
+    ///
+    /// @param vector The initial state
+    /// @return The writer, at epoch 0
+    #[must_use]
+    pub fn new(vector: Vector<T>) -> Self {
+        let published = Published::new(vector.clone(), 0);
+        Self {
+            vector,
+            published: Arc::new(ArcSwap::from_pointee(published)),
+            epoch: 0,
+        }
+    }
+
+    /// Makes the current state of the private copy visible to the readers
+    ///
+    /// @return The epoch of the new state
+    pub fn publish(&mut self) -> u64 {
+        self.epoch += 1;
+        let published = Published::new(self.vector.clone(), self.epoch);
+        self.published.store(Arc::new(published));
+        self.epoch
+    }
+}
+
+impl<T> EpochWriter<T> {
+    /// Creates a reader of the published states
+    #[must_use]
+    pub fn reader(&self) -> EpochReader<T> {
+        EpochReader {
+            published: Arc::clone(&self.published),
+        }
+    }
+
+    /// Returns the epoch of the last publication
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Consumes the writer to return the private copy, publications
+    /// included or not
+    pub fn into_inner(self) -> Vector<T> {
+        self.vector
+    }
+}
+
+impl<T> Deref for EpochWriter<T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vector
+    }
+}
+
+impl<T> DerefMut for EpochWriter<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vector
+    }
+}
+
+impl<T> EpochReader<T> {
+    /// Pins the last published state
+    ///
+    /// @return The guard giving access to the state
+    #[must_use]
+    pub fn load(&self) -> EpochGuard<T> {
+        EpochGuard {
+            guard: self.published.load(),
+        }
+    }
+
+    /// Resolves the handle against the last published state and clones the
+    /// object
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object, or None if the handle is stale in that state
+    #[must_use]
+    pub fn get_cloned(&self, handle: &Handle<T>) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.load().get(handle).cloned()
+    }
+}
+
+impl<T> Clone for EpochReader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            published: Arc::clone(&self.published),
+        }
+    }
+}
+
+impl<T> EpochGuard<T> {
+    /// Returns the epoch of the pinned state
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.guard.epoch
+    }
+}
+
+impl<T> Deref for EpochGuard<T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard.vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_readers_see_publications() {
+        let mut writer = EpochWriter::new(Vector::default());
+        let reader = writer.reader();
+        let id = writer.push(1);
+        let handle = writer.create_handle(id).unwrap();
+        assert_eq!(reader.get_cloned(&handle), None);
+
+        assert_eq!(writer.publish(), 1);
+        let pinned = reader.load();
+        writer.erase_by_handle(&handle);
+        writer.publish();
+
+        assert_eq!(pinned.get(&handle), Some(&1));
+        assert_eq!(pinned.epoch(), 1);
+        assert_eq!(reader.get_cloned(&handle), None);
+        assert_eq!(reader.load().epoch(), 2);
+    }
+
+    #[test]
+    fn test_readers_do_not_count_stale_accesses() {
+        let mut writer = EpochWriter::new(Vector::default());
+        let reader = writer.reader();
+        let id = writer.push(1);
+        let handle = writer.create_handle(id).unwrap();
+
+        assert_eq!(reader.get_cloned(&handle), None);
+        assert_eq!(reader.load().telemetry().stale_accesses, 0);
+        assert_eq!(writer.get(&Handle::invalid()), None);
+        assert_eq!(writer.telemetry().stale_accesses, 1);
+        writer.publish();
+        assert_eq!(reader.load().get(&Handle::invalid()), None);
+        assert_eq!(reader.load().telemetry().stale_accesses, 1);
+    }
+
+    #[test]
+    fn test_concurrent_readers() {
+        let mut writer = EpochWriter::new((0..100).collect::<Vector<u32>>());
+        let handles: Vec<_> = writer.handles().collect();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                let reader = writer.reader();
+                let handles = &handles;
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        let state = reader.load();
+                        let sum: u32 = handles
+                            .iter()
+                            .filter_map(|handle| state.get(handle))
+                            .sum();
+                        assert_eq!(sum, 4950 + 100 * state.epoch() as u32);
+                    }
+                });
+            }
+            for _ in 0..10 {
+                writer.iter_mut().for_each(|object| *object += 1);
+                writer.publish();
+            }
+        });
+    }
+}
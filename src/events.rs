@@ -0,0 +1,38 @@
+use crate::handle::Handle;
+
+/// The kind of membership change recorded in the event log of a vector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventOp {
+    /// An object was added to the vector.
+    Insert,
+    /// An object was removed from the vector.
+    Erase,
+    /// An object was moved to another vector by `Vector::split_off`. Its
+    /// handle no longer resolves, but the object was not erased.
+    Move,
+}
+
+/// A membership change recorded in the event log of a vector.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct VectorEvent<T> {
+    /// What happened to the object.
+    pub op: EventOp,
+    /// The handle of the object. For an erase or a move, it is the handle
+    /// the object had right before it was removed.
+    pub handle: Handle<T>,
+    /// The tick of the vector when the change happened.
+    pub tick: u64,
+}
+
+impl<T> Copy for VectorEvent<T> {}
+
+impl<T> Clone for VectorEvent<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> VectorEvent<T> {
+    /// Factory constructor
+    pub fn new(op: EventOp, handle: Handle<T>, tick: u64) -> Self {
+        Self { op, handle, tick }
+    }
+}
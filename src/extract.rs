@@ -0,0 +1,92 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// An iterator that lazily removes the objects matching a predicate and
+/// yields them with the handle they had, produced by `Vector::extract_if`.
+/// The objects not visited when the iterator is dropped are kept.
+#[derive(Debug)]
+pub struct ExtractIf<'a, T, F> {
+    /// The vector the objects are removed from
+    vector: &'a mut Vector<T>,
+    /// Tells if an object should be removed
+    predicate: F,
+    /// The data index of the next object to check
+    data_index: usize,
+}
+
+impl<'a, T, F> ExtractIf<'a, T, F> {
+    /// Factory constructor
+    pub(crate) fn new(vector: &'a mut Vector<T>, predicate: F) -> Self {
+        Self {
+            vector,
+            predicate,
+            data_index: 0,
+        }
+    }
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = (Handle<T>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.data_index < self.vector.data.len() {
+            self.vector.touch(self.data_index);
+            if (self.predicate)(&mut self.vector.data[self.data_index]) {
+                // The last object is moved here and checked next
+                let md = self.vector.metadata[self.data_index];
+                let handle = Handle::new(md.reverse_id, md.validity_id);
+                return Some((handle, self.vector.remove_slot(handle.id)));
+            }
+            self.data_index += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.vector.data.len() - self.data_index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_if() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = (0..6)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        let mut archive: Vec<_> =
+            vec.extract_if(|value| *value % 2 == 1).collect();
+        archive.sort_by_key(|(_, value)| *value);
+
+        assert_eq!(archive.len(), 3);
+        for (handle, value) in &archive {
+            assert_eq!(*handle, handles[*value]);
+            assert_eq!(vec.get(handle), None);
+        }
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.get(&handles[4]), Some(&4));
+    }
+
+    #[test]
+    fn test_extract_if_is_lazy() {
+        let mut vec = Vector::default();
+        for i in 0..4 {
+            vec.push(i);
+        }
+
+        let first = vec.extract_if(|_| true).next();
+
+        assert_eq!(first.map(|(_, value)| value), Some(0));
+        assert_eq!(vec.len(), 3);
+    }
+}
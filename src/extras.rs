@@ -0,0 +1,216 @@
+use crate::{ID, group::Group};
+use std::mem::size_of;
+
+/// The optional state of the slots of a Vector, kept out of the metadata so
+/// the vectors not using it do not pay for it. Each field is indexed by ID
+/// and only allocated once a value other than 0 is written to it, the IDs
+/// past its end reading as 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+pub struct SlotExtras {
+    /// The number of times each slot has been recycled for a new object,
+    /// only maintained when reuse tracking is enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reuse_counts: Vec<usize>,
+    /// The tick at which the object of each slot was inserted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub insertion_ticks: Vec<u64>,
+    /// The number of mutable accesses to the object of each slot, only
+    /// maintained when modification tracking is enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modification_counts: Vec<usize>,
+    /// The tick at which the object of each slot was inserted or, when
+    /// modification tracking is enabled, last accessed mutably. Change
+    /// detection state, so it is not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "borsh", borsh(skip))]
+    pub modified_ticks: Vec<u64>,
+    /// The group the object of each slot belongs to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub groups: Vec<Group>,
+}
+
+/// Returns the value of @p id, 0 when it was never written
+fn read<V: Copy + Default>(values: &[V], id: ID) -> V {
+    values.get(id).copied().unwrap_or_default()
+}
+
+/// Writes the value of @p id, only growing @p values for a value other
+/// than 0
+fn write<V: Copy + Default + PartialEq>(
+    values: &mut Vec<V>,
+    id: ID,
+    value: V,
+) {
+    if let Some(slot) = values.get_mut(id) {
+        *slot = value;
+    } else if value != V::default() {
+        values.resize(id + 1, V::default());
+        values[id] = value;
+    }
+}
+
+/// Returns the values of @p old_ids, in order
+fn select<V: Copy + Default>(values: &[V], old_ids: &[ID]) -> Vec<V> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    old_ids.iter().map(|&id| read(values, id)).collect()
+}
+
+impl SlotExtras {
+    /// Returns the reuse count of the slot of @p id
+    pub(crate) fn reuse_count(&self, id: ID) -> usize {
+        read(&self.reuse_counts, id)
+    }
+
+    /// Sets the reuse count of the slot of @p id
+    pub(crate) fn set_reuse_count(&mut self, id: ID, count: usize) {
+        write(&mut self.reuse_counts, id, count);
+    }
+
+    /// Counts one more reuse of the slot of @p id
+    pub(crate) fn add_reuse(&mut self, id: ID) {
+        self.set_reuse_count(id, self.reuse_count(id) + 1);
+    }
+
+    /// Returns the insertion tick of the object of @p id
+    pub(crate) fn insertion_tick(&self, id: ID) -> u64 {
+        read(&self.insertion_ticks, id)
+    }
+
+    /// Sets the insertion tick of the object of @p id
+    pub(crate) fn set_insertion_tick(&mut self, id: ID, tick: u64) {
+        write(&mut self.insertion_ticks, id, tick);
+    }
+
+    /// Returns the modification count of the object of @p id
+    pub(crate) fn modification_count(&self, id: ID) -> usize {
+        read(&self.modification_counts, id)
+    }
+
+    /// Sets the modification count of the object of @p id
+    pub(crate) fn set_modification_count(&mut self, id: ID, count: usize) {
+        write(&mut self.modification_counts, id, count);
+    }
+
+    /// Counts one more mutable access to the object of @p id
+    pub(crate) fn add_modification(&mut self, id: ID) {
+        self.set_modification_count(id, self.modification_count(id) + 1);
+    }
+
+    /// Returns the modified tick of the object of @p id
+    pub(crate) fn modified_tick(&self, id: ID) -> u64 {
+        read(&self.modified_ticks, id)
+    }
+
+    /// Sets the modified tick of the object of @p id
+    pub(crate) fn set_modified_tick(&mut self, id: ID, tick: u64) {
+        write(&mut self.modified_ticks, id, tick);
+    }
+
+    /// Returns the group of the object of @p id
+    pub(crate) fn group(&self, id: ID) -> Group {
+        read(&self.groups, id)
+    }
+
+    /// Moves the object of @p id to @p group
+    pub(crate) fn set_group(&mut self, id: ID, group: Group) {
+        write(&mut self.groups, id, group);
+    }
+
+    /// Returns the state of the IDs renumbered by `Vector::compact`, the
+    /// new ID @p i being the old ID @p old_ids[i]
+    pub(crate) fn select(&self, old_ids: &[ID]) -> Self {
+        Self {
+            reuse_counts: select(&self.reuse_counts, old_ids),
+            insertion_ticks: select(&self.insertion_ticks, old_ids),
+            modification_counts: select(&self.modification_counts, old_ids),
+            modified_ticks: select(&self.modified_ticks, old_ids),
+            groups: select(&self.groups, old_ids),
+        }
+    }
+
+    /// Forgets the state of the IDs from @p id_count on
+    pub(crate) fn truncate(&mut self, id_count: usize) {
+        self.reuse_counts.truncate(id_count);
+        self.insertion_ticks.truncate(id_count);
+        self.modification_counts.truncate(id_count);
+        self.modified_ticks.truncate(id_count);
+        self.groups.truncate(id_count);
+    }
+
+    /// Releases the capacity of the fields beyond @p capacity IDs
+    pub(crate) fn shrink_to(&mut self, capacity: usize) {
+        self.reuse_counts.shrink_to(capacity);
+        self.insertion_ticks.shrink_to(capacity);
+        self.modification_counts.shrink_to(capacity);
+        self.modified_ticks.shrink_to(capacity);
+        self.groups.shrink_to(capacity);
+    }
+
+    /// Returns the highest number of IDs covered by a field
+    pub(crate) fn id_count(&self) -> usize {
+        self.reuse_counts
+            .len()
+            .max(self.insertion_ticks.len())
+            .max(self.modification_counts.len())
+            .max(self.modified_ticks.len())
+            .max(self.groups.len())
+    }
+
+    /// Returns the bytes allocated for the fields
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.reuse_counts.capacity() * size_of::<usize>()
+            + self.insertion_ticks.capacity() * size_of::<u64>()
+            + self.modification_counts.capacity() * size_of::<usize>()
+            + self.modified_ticks.capacity() * size_of::<u64>()
+            + self.groups.capacity() * size_of::<Group>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_grow_on_first_use() {
+        let mut extras = SlotExtras::default();
+        write(&mut extras.reuse_counts, 3, 0);
+        assert!(extras.reuse_counts.is_empty());
+        assert_eq!(extras.reuse_count(3), 0);
+
+        extras.add_reuse(3);
+        assert_eq!(extras.reuse_counts, [0, 0, 0, 1]);
+        assert_eq!(extras.reuse_count(3), 1);
+        assert_eq!(extras.reuse_count(10), 0);
+        assert_eq!(extras.id_count(), 4);
+        extras.set_insertion_tick(5, 7);
+        assert_eq!(extras.insertion_tick(5), 7);
+        assert_eq!(extras.id_count(), 6);
+        extras.add_modification(6);
+        assert_eq!(extras.modification_count(6), 1);
+        assert_eq!(extras.id_count(), 7);
+        extras.set_modified_tick(1, 2);
+        assert_eq!(extras.modified_tick(1), 2);
+        extras.set_group(8, 1);
+        assert_eq!(extras.group(8), 1);
+        assert_eq!(extras.id_count(), 9);
+    }
+
+    #[test]
+    fn test_select_renumbers() {
+        let mut extras = SlotExtras::default();
+        extras.add_reuse(2);
+        extras.add_reuse(2);
+
+        let selected = extras.select(&[2, 0, 5]);
+        assert_eq!(selected.reuse_counts, [2, 0, 0]);
+        let empty = SlotExtras::default().select(&[0, 1]);
+        assert!(empty.reuse_counts.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::handle::Handle;
+
+/// Alias for the group an object belongs to. Every object starts in the
+/// default group 0.
+pub type Group = usize;
+
+/// Mutable access to all the objects of a single group. Views produced by
+/// `Vector::split_groups_mut` are guaranteed to be disjoint and can be sent
+/// to different threads.
+#[derive(Debug)]
+pub struct GroupViewMut<'a, T> {
+    /// The group of the objects in the view
+    group: Group,
+    /// The objects of the group together with their handles, sorted by ID
+    /// so handles are looked up with a binary search
+    entries: Vec<(Handle<T>, &'a mut T)>,
+}
+
+impl<'a, T> GroupViewMut<'a, T> {
+    /// Factory constructor
+    pub(crate) fn new(group: Group) -> Self {
+        Self {
+            group,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an object to the view
+    pub(crate) fn push(&mut self, handle: Handle<T>, object: &'a mut T) {
+        self.entries.push((handle, object));
+    }
+
+    /// Sorts the objects by ID, once all of them were added
+    pub(crate) fn sort_by_id(&mut self) {
+        self.entries.sort_unstable_by_key(|(handle, _)| handle.id);
+    }
+
+    /// Returns the group covered by the view
+    #[must_use]
+    pub fn group(&self) -> Group {
+        self.group
+    }
+
+    /// Return the number of objects in the view
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Tells if the view is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns a mutable reference to the object referenced by the handle
+    /// if it belongs to this view
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        let position = self
+            .entries
+            .binary_search_by_key(&handle.id, |(h, _)| h.id)
+            .ok()?;
+        let (h, object) = &mut self.entries[position];
+        (h.generation() == handle.generation()).then_some(&mut **object)
+    }
+
+    /// Returns an iterator over the objects of the view and their handles,
+    /// in ID order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.entries
+            .iter_mut()
+            .map(|(handle, object)| (*handle, &mut **object))
+    }
+}
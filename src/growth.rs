@@ -0,0 +1,151 @@
+use crate::alloc::{Buffer, TryReserveError};
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+/// The policy used to compute the new capacity of the internal vectors
+/// when they run out of space.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum GrowthStrategy {
+    /// Let the standard library decide, which amortizes by doubling.
+    #[default]
+    Default,
+    /// Grow the capacity by the given percentage of the current capacity.
+    Factor(usize),
+    /// Grow the capacity by a fixed number of slots.
+    Fixed(usize),
+    /// Compute the new capacity from the current capacity and the required
+    /// length. The result is clamped so it is never below the required
+    /// length.
+    Custom(fn(usize, usize) -> usize),
+}
+
+impl GrowthStrategy {
+    /// Computes the capacity to allocate
+    ///
+    /// @param capacity The current capacity of the vector
+    /// @param required The minimum number of slots needed
+    /// @return The new capacity, always at least @p required
+    #[must_use]
+    pub fn new_capacity(&self, capacity: usize, required: usize) -> usize {
+        let target = match *self {
+            GrowthStrategy::Default => capacity.saturating_mul(2).max(4),
+            GrowthStrategy::Factor(percent) => {
+                capacity.saturating_add(capacity.saturating_mul(percent) / 100)
+            }
+            GrowthStrategy::Fixed(step) => capacity.saturating_add(step),
+            GrowthStrategy::Custom(callback) => callback(capacity, required),
+        };
+        target.max(required)
+    }
+
+    /// Makes sure @p vec can hold @p additional more elements, growing it
+    /// according to the policy if needed
+    pub(crate) fn reserve(&self, vec: &mut impl Buffer, additional: usize) {
+        let required = vec.len().saturating_add(additional);
+        if required <= vec.capacity() {
+            return;
+        }
+        match self {
+            GrowthStrategy::Default => vec.reserve(additional),
+            _ => {
+                let capacity = self.new_capacity(vec.capacity(), required);
+                vec.reserve_exact(capacity - vec.len());
+            }
+        }
+    }
+
+    /// Like `reserve`, but reports allocation failures instead of aborting
+    pub(crate) fn try_reserve(
+        &self,
+        vec: &mut impl Buffer,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        let required = vec.len().saturating_add(additional);
+        if required <= vec.capacity() {
+            return Ok(());
+        }
+        match self {
+            GrowthStrategy::Default => vec.try_reserve(additional),
+            _ => {
+                let capacity = self.new_capacity(vec.capacity(), required);
+                vec.try_reserve_exact(capacity - vec.len())
+            }
+        }
+    }
+}
+
+// Callbacks are compared by address, which is the best that can be done
+// for function pointers.
+impl PartialEq for GrowthStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (GrowthStrategy::Default, GrowthStrategy::Default) => true,
+            (GrowthStrategy::Factor(a), GrowthStrategy::Factor(b)) => a == b,
+            (GrowthStrategy::Fixed(a), GrowthStrategy::Fixed(b)) => a == b,
+            (GrowthStrategy::Custom(a), GrowthStrategy::Custom(b)) => {
+                std::ptr::fn_addr_eq(*a, *b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for GrowthStrategy {}
+
+impl Hash for GrowthStrategy {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            GrowthStrategy::Default => {}
+            GrowthStrategy::Factor(value) | GrowthStrategy::Fixed(value) => {
+                value.hash(state);
+            }
+            GrowthStrategy::Custom(callback) => {
+                (*callback as usize).hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::{Global, new_buffer};
+
+    #[test]
+    fn test_factor_growth() {
+        let strategy = GrowthStrategy::Factor(50);
+
+        assert_eq!(strategy.new_capacity(100, 101), 150);
+        assert_eq!(strategy.new_capacity(0, 1), 1);
+    }
+
+    #[test]
+    fn test_fixed_growth() {
+        let strategy = GrowthStrategy::Fixed(16);
+
+        assert_eq!(strategy.new_capacity(32, 33), 48);
+        assert_eq!(strategy.new_capacity(32, 100), 100);
+    }
+
+    #[test]
+    fn test_custom_growth_is_clamped() {
+        let strategy = GrowthStrategy::Custom(|capacity, _| capacity + 1);
+
+        assert_eq!(strategy.new_capacity(10, 11), 11);
+        assert_eq!(strategy.new_capacity(10, 20), 20);
+    }
+
+    #[test]
+    fn test_reserve_exact_capacity() {
+        let strategy = GrowthStrategy::Fixed(8);
+        let mut vec = new_buffer::<u8, _>(&Global);
+
+        for i in 0..9 {
+            strategy.reserve(&mut vec, 1);
+            vec.push(i);
+        }
+
+        assert_eq!(vec.capacity(), 16);
+    }
+}
@@ -1,13 +1,25 @@
-use crate::ID;
+use crate::{ID, INVALID_ID};
+use crate::vector::Vector;
+use std::fmt;
 use std::marker::PhantomData;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+/// A reference to an object of a Vector, checked against the generation of
+/// its slot so it never reaches an object stored after the referenced one
+/// was erased.
+///
+/// The generation `ID::MAX` is reserved, above MAX_GENERATION, and the
+/// generation is stored inverted so that `Option<Handle<T>>` is the size of
+/// a Handle.
+#[derive(PartialEq, Eq, Hash)]
 pub struct Handle<T> {
     /// The ID of the object.
     pub id: ID,
-    /// The validity ID of the object at the time of creation. Used to check
-    /// the validity of the handle.
-    pub validity_id: ID,
+    /// The bitwise complement of the validity ID of the object at the time
+    /// of creation. Used to check the validity of the handle.
+    inverted_generation: NonZeroUsize,
     /// Prevent type collisions so not just any type of Handle can be passed
     /// into any type of Vector.
     pub _marker: PhantomData<T>,
@@ -21,12 +33,37 @@ impl<T> Clone for Handle<T> {
 
 impl<T> Handle<T> {
     /// Factory constructor
+    ///
+    /// @note Panics if @p validity_id is above MAX_GENERATION.
     pub fn new(id: ID, validity_id: ID) -> Self {
-        Self {
+        Self::try_new(id, validity_id)
+            .expect("The generation of a handle is above MAX_GENERATION")
+    }
+
+    /// Factory constructor for untrusted parts
+    ///
+    /// @return The handle, or None if @p validity_id is above
+    /// MAX_GENERATION
+    #[must_use]
+    pub fn try_new(id: ID, validity_id: ID) -> Option<Self> {
+        Some(Self {
             id,
-            validity_id,
+            inverted_generation: NonZeroUsize::new(!validity_id)?,
             _marker: PhantomData,
-        }
+        })
+    }
+
+    /// Creates a handle referencing no object. Unlike the default handle,
+    /// which references ID 0, it can never resolve.
+    #[must_use]
+    pub fn invalid() -> Self {
+        Self::new(INVALID_ID, 0)
+    }
+
+    /// Tells if the handle was created by `Handle::invalid`
+    #[must_use]
+    pub fn is_invalid(&self) -> bool {
+        self.id == INVALID_ID
     }
 
     /// Returns the ID of the associated object
@@ -34,30 +71,247 @@ impl<T> Handle<T> {
     pub fn get_id(&self) -> usize {
         self.id
     }
+
+    /// Returns the generation of the slot at the time the handle was created
+    #[must_use]
+    pub fn generation(&self) -> ID {
+        !self.inverted_generation.get()
+    }
 }
 
 // Default factory constructor
 impl<T> Default for Handle<T> {
     fn default() -> Self {
-        Self {   
-            id: 0,
-            validity_id: 0,
-            _marker: PhantomData,
+        Self::new(0, 0)
+    }
+}
+
+// The stored generation is inverted, so it is printed through the accessor
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("id", &self.id)
+            .field("validity_id", &self.generation())
+            .finish()
+    }
+}
+
+/// A handle to a slot reserved before its object exists, produced by
+/// `Vector::reserve_handle`. It is consumed when the slot is filled, so a
+/// reservation cannot be fulfilled twice.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ReservedHandle<T> {
+    /// The handle the object will have once the slot is filled
+    handle: Handle<T>,
+}
+
+impl<T> ReservedHandle<T> {
+    /// Factory constructor
+    pub(crate) fn new(handle: Handle<T>) -> Self {
+        Self { handle }
+    }
+
+    /// Returns the handle the object will have once the slot is filled
+    #[must_use]
+    pub fn handle(&self) -> Handle<T> {
+        self.handle
+    }
+
+    /// Returns the reserved ID
+    #[must_use]
+    pub fn get_id(&self) -> ID {
+        self.handle.id
+    }
+}
+
+/// The number of IDs reserved by an IdReserver whose slots are not created
+/// yet.
+#[derive(Debug, Default)]
+pub(crate) struct IdReservations(AtomicUsize);
+
+impl IdReservations {
+    /// Reserves @p count IDs
+    ///
+    /// @return The number of IDs reserved before
+    pub(crate) fn reserve(&self, count: usize) -> usize {
+        self.0.fetch_add(count, Ordering::Relaxed)
+    }
+
+    /// Returns the number of IDs reserved
+    pub(crate) fn pending(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Forgets the reserved IDs
+    ///
+    /// @return The number of IDs reserved
+    pub(crate) fn take(&mut self) -> usize {
+        std::mem::take(self.0.get_mut())
+    }
+}
+
+impl Clone for IdReservations {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.pending()))
+    }
+}
+
+/// Reserves IDs of a Vector from several threads at once, produced by
+/// `Vector::id_reserver`. It borrows the vector mutably, so the read-only
+/// views of a vector can not reserve IDs, and gives read access to the
+/// vector while it lives.
+///
+/// ```
+/// use stable_index_vector::Vector;
+///
+/// let mut vec: Vector<u32> = Vector::default();
+/// let reserver = vec.id_reserver();
+/// let reserved = std::thread::scope(|scope| {
+///     scope.spawn(move || reserver.reserve(2)).join().unwrap()
+/// });
+/// for reservation in reserved {
+///     vec.fulfill(reservation, 7).unwrap();
+/// }
+/// assert_eq!(vec.len(), 2);
+/// ```
+///
+/// A view can not reserve IDs:
+///
+/// ```compile_fail
+/// use stable_index_vector::Vector;
+///
+/// let vec: Vector<u32> = Vector::default();
+/// vec.view().id_reserver();
+/// ```
+#[derive(Debug)]
+pub struct IdReserver<'a, T> {
+    /// The vector the IDs are reserved in
+    vector: &'a Vector<T>,
+}
+
+impl<T> Copy for IdReserver<'_, T> {}
+
+impl<T> Clone for IdReserver<'_, T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, T> IdReserver<'a, T> {
+    /// Factory constructor
+    pub(crate) fn new(vector: &'a Vector<T>) -> Self {
+        Self { vector }
+    }
+
+    /// Reserves @p count new IDs. The reservations are fulfilled or
+    /// cancelled later by the owner of the vector, like the ones of
+    /// `Vector::reserve_handle`.
+    ///
+    /// @note The slots of the reserved IDs are created, as taken slots, by
+    /// the next call to a method needing them. Until then `is_taken` does
+    /// not report them.
+    /// @param count The number of IDs to reserve
+    /// @return The reservations, with contiguous IDs
+    pub fn reserve(&self, count: usize) -> Vec<ReservedHandle<T>> {
+        self.vector.reserve_ids_atomic(count)
+    }
+}
+
+impl<T> Deref for IdReserver<'_, T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.vector
+    }
+}
+
+/// A Handle packed into a single `u64`: the ID in the upper 32 bits and the
+/// generation in the lower 32 bits. It is half the size of a Handle, for
+/// handles stored in large numbers.
+///
+/// The ID `u32::MAX` is reserved, like INVALID_ID, and the value is stored
+/// inverted so that `Option<PackedHandle<T>>` is the size of a `u64`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PackedHandle<T> {
+    /// The bitwise complement of the ID and generation of the object
+    inverted: NonZeroU64,
+    /// Prevent type collisions, like for Handle
+    _marker: PhantomData<T>,
+}
+
+impl<T> Copy for PackedHandle<T> {}
+
+impl<T> Clone for PackedHandle<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> PackedHandle<T> {
+    /// Packs a handle
+    ///
+    /// @return The packed handle, or None if the ID does not fit in 32 bits
+    /// without being `u32::MAX`, or the generation does not fit in 32 bits
+    #[must_use]
+    pub fn pack(handle: Handle<T>) -> Option<Self> {
+        let id = u32::try_from(handle.id).ok().filter(|id| *id != u32::MAX)?;
+        let generation = u32::try_from(handle.generation()).ok()?;
+        Self::from_bits((u64::from(id) << 32) | u64::from(generation))
+    }
+
+    /// Unpacks the handle
+    #[must_use]
+    pub fn unpack(self) -> Handle<T> {
+        Handle::new(self.get_id(), self.generation())
+    }
+
+    /// Returns the ID of the associated object
+    #[must_use]
+    pub fn get_id(&self) -> ID {
+        (self.to_bits() >> 32) as ID
+    }
+
+    /// Returns the generation of the slot at the time the handle was created
+    #[must_use]
+    pub fn generation(&self) -> ID {
+        (self.to_bits() & u64::from(u32::MAX)) as ID
+    }
+
+    /// Returns the packed representation
+    #[must_use]
+    pub fn to_bits(self) -> u64 {
+        !self.inverted.get()
+    }
+
+    /// Rebuilds a packed handle from the value returned by `to_bits`
+    ///
+    /// @return The packed handle, or None if the bits use the reserved ID
+    #[must_use]
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        if bits >> 32 == u64::from(u32::MAX) {
+            return None;
         }
+        Some(Self {
+            inverted: NonZeroU64::new(!bits)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T> From<PackedHandle<T>> for Handle<T> {
+    fn from(packed: PackedHandle<T>) -> Self {
+        packed.unpack()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::MAX_GENERATION;
 
     #[test]
     fn test_handle_creation() {
         let handle: Handle<isize> = Handle::new(10, 500);
         
         assert_eq!(handle.id, 10);
-        assert_eq!(handle.validity_id, 500);
         assert_eq!(handle.get_id(), 10);
+        assert_eq!(handle.generation(), 500);
     }
 
     #[test]
@@ -65,7 +319,37 @@ mod tests {
         let handle: Handle<isize> = Handle::default();
         
         assert_eq!(handle.id, 0);
-        assert_eq!(handle.validity_id, 0);
+        assert_eq!(handle.generation(), 0);
+    }
+
+    #[test]
+    fn test_handle_niche() {
+        assert_eq!(
+            std::mem::size_of::<Option<Handle<isize>>>(),
+            std::mem::size_of::<Handle<isize>>()
+        );
+        assert_eq!(Handle::<isize>::try_new(1, ID::MAX), None);
+        let highest = Handle::<isize>::try_new(1, MAX_GENERATION).unwrap();
+        assert_eq!(highest.generation(), MAX_GENERATION);
+        assert_eq!(
+            format!("{:?}", Handle::<isize>::new(2, 3)),
+            "Handle { id: 2, validity_id: 3 }"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_handle_reserved_generation() {
+        Handle::<isize>::new(0, ID::MAX);
+    }
+
+    #[test]
+    fn test_invalid_handle() {
+        let handle: Handle<isize> = Handle::invalid();
+
+        assert!(handle.is_invalid());
+        assert!(!Handle::<isize>::default().is_invalid());
+        assert_eq!(PackedHandle::pack(handle), None);
     }
 
     #[test]
@@ -105,4 +389,54 @@ mod tests {
         assert!(set.contains(&Handle::new(1, 1)));
         assert!(!set.contains(&Handle::new(1, 2)));
     }
+
+    #[test]
+    fn test_packed_handle_round_trip() {
+        let handle: Handle<isize> = Handle::new(7, 3);
+        let packed = PackedHandle::pack(handle).unwrap();
+
+        assert_eq!(std::mem::size_of::<PackedHandle<isize>>(), 8);
+        assert_eq!(packed.get_id(), 7);
+        assert_eq!(packed.generation(), 3);
+        assert_eq!(Handle::from(packed), handle);
+        assert_eq!(PackedHandle::from_bits(packed.to_bits()), Some(packed));
+    }
+
+    #[test]
+    fn test_packed_handle_overflow() {
+        let id: Handle<isize> = Handle::new(1 << 32, 0);
+        let generation: Handle<isize> = Handle::new(0, 1 << 32);
+
+        assert_eq!(PackedHandle::pack(id), None);
+        assert_eq!(PackedHandle::pack(generation), None);
+    }
+
+    #[test]
+    fn test_packed_handle_niche() {
+        let reserved: Handle<isize> = Handle::new(u32::MAX as ID, 0);
+
+        assert_eq!(std::mem::size_of::<Option<PackedHandle<isize>>>(), 8);
+        assert_eq!(PackedHandle::pack(reserved), None);
+        assert_eq!(PackedHandle::<isize>::from_bits(u64::MAX), None);
+        assert!(PackedHandle::<isize>::from_bits(0).is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_handle_serde() {
+        // The element type does not need to be serializable
+        struct Entity;
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Save {
+            target: Handle<Entity>,
+        }
+
+        let save = Save { target: Handle::new(3, 7) };
+        let json = serde_json::to_string(&save).unwrap();
+        let restored: Save = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(json, r#"{"target":{"id":3,"validity_id":7}}"#);
+        assert_eq!(restored.target.id, 3);
+        assert_eq!(restored.target.generation(), 7);
+    }
 }
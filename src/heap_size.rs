@@ -0,0 +1,128 @@
+use std::fmt;
+use std::mem::size_of;
+
+/// An object able to report the memory it owns on the heap, so vectors can
+/// maintain a running total of their deep memory usage.
+pub trait HeapSize {
+    /// Returns the number of heap bytes owned by the object, not counting
+    /// `size_of::<Self>()` itself
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_heap_size_zero {
+    ($($ty:ty),*) => {
+        $(
+            impl HeapSize for $ty {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_heap_size_zero!(
+    (), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128,
+    isize, f32, f64
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>()
+            + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+/// The bytes allocated by a vector, as returned by `Vector::memory_usage`.
+/// The internal vectors are counted by capacity, not by length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemoryUsage {
+    /// The bytes allocated for the objects.
+    pub data: usize,
+    /// The bytes allocated for the metadata of the slots.
+    pub metadata: usize,
+    /// The bytes allocated for the indices of the IDs.
+    pub indices: usize,
+    /// The bytes allocated for the optional state of the slots.
+    pub extras: usize,
+    /// The bytes allocated for the insertion order used by bounded mode.
+    pub insertion_queue: usize,
+    /// The heap bytes owned by the objects, or None if they are not
+    /// tracked.
+    pub heap: Option<usize>,
+}
+
+impl MemoryUsage {
+    /// Returns the sum of all the counted bytes
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.data
+            + self.metadata
+            + self.indices
+            + self.extras
+            + self.insertion_queue
+            + self.heap.unwrap_or(0)
+    }
+}
+
+/// The heap size function of the element type, stored by vectors that
+/// track their deep memory usage.
+pub(crate) struct SizeHook<T>(pub(crate) fn(&T) -> usize);
+
+impl<T> SizeHook<T> {
+    /// Returns the hook of an element type implementing HeapSize
+    pub(crate) fn of() -> Self
+    where
+        T: HeapSize,
+    {
+        Self(T::heap_size)
+    }
+}
+
+impl<T> Copy for SizeHook<T> {}
+
+impl<T> Clone for SizeHook<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> fmt::Debug for SizeHook<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SizeHook")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_sizes() {
+        let mut text = String::with_capacity(16);
+        text.push('a');
+
+        assert_eq!(5u32.heap_size(), 0);
+        assert_eq!(text.heap_size(), 16);
+        assert_eq!(Some(text).heap_size(), 16);
+        assert_eq!(None::<String>.heap_size(), 0);
+        assert_eq!(Box::new(1u64).heap_size(), 8);
+        assert_eq!(Vec::<u16>::with_capacity(4).heap_size(), 8);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::handle::Handle;
+use std::fmt;
+
+/// The function called with each object right before it is removed from a
+/// vector.
+pub(crate) struct EraseHook<T>(pub(crate) fn(Handle<T>, &mut T));
+
+/// The function called with the handle, the old data index and the new
+/// data index of each object moved inside the data vector.
+pub(crate) struct RelocateHook<T>(pub(crate) fn(Handle<T>, usize, usize));
+
+// The bounds derive would put on T are not needed for function pointers
+macro_rules! impl_hook {
+    ($($hook:ident),*) => {
+        $(
+            impl<T> Copy for $hook<T> {}
+
+            impl<T> Clone for $hook<T> {
+                fn clone(&self) -> Self { *self }
+            }
+
+            impl<T> fmt::Debug for $hook<T> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(stringify!($hook))
+                }
+            }
+        )*
+    };
+}
+
+impl_hook!(EraseHook, RelocateHook);
@@ -0,0 +1,68 @@
+use crate::handle::Handle;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Alias for the random 128 bits identifier of a vector instance, laid out
+/// like a version 4 UUID.
+pub type VectorId = u128;
+
+/// A handle stamped with the identity of the vector that issued it, so it
+/// can be rejected when presented to another vector.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct StampedHandle<T> {
+    /// The stamped handle.
+    pub handle: Handle<T>,
+    /// The identity of the issuing vector, if it had one.
+    pub vector_id: Option<VectorId>,
+}
+
+impl<T> Copy for StampedHandle<T> {}
+
+impl<T> Clone for StampedHandle<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> StampedHandle<T> {
+    /// Factory constructor
+    pub fn new(handle: Handle<T>, vector_id: Option<VectorId>) -> Self {
+        Self { handle, vector_id }
+    }
+}
+
+/// Generates a new random vector identity
+pub fn generate_vector_id() -> VectorId {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    let mut halves = [0u64; 2];
+    for (salt, half) in halves.iter_mut().enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(count);
+        hasher.write_usize(salt);
+        *half = hasher.finish();
+    }
+    let id = (u128::from(halves[0]) << 64) | u128::from(halves[1]);
+    // Set the version 4 and RFC 4122 variant bits
+    (id & !(0xF << 76) & !(0x3 << 62)) | (0x4 << 76) | (0x2 << 62)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_ids_are_unique_uuids() {
+        let a = generate_vector_id();
+        let b = generate_vector_id();
+
+        assert_ne!(a, b);
+        assert_eq!((a >> 76) & 0xF, 4);
+        assert_eq!((a >> 62) & 0x3, 2);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::ID;
+use std::fmt;
+use std::hash::Hash;
+
+/// The integer types a Vector can store its IDs, generations and data
+/// indices as. A narrower type shrinks the bookkeeping of every slot, e.g.
+/// `Vector<T, u32>` for vectors that never hold 4 billion slots, while the
+/// API keeps taking and returning IDs.
+pub trait IndexType:
+    Copy + Eq + Ord + Hash + fmt::Debug + Send + Sync + 'static
+{
+    /// The highest ID, generation or data index the type can store.
+    const MAX: ID;
+
+    /// Converts an ID, a generation or a data index
+    ///
+    /// @note Panics if @p index is above MAX, i.e. when the IDs of the type
+    /// are exhausted.
+    fn from_index(index: usize) -> Self;
+
+    /// Returns the stored ID, generation or data index
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_index_type {
+    ($($ty:ty),*) => {
+        $(
+            impl IndexType for $ty {
+                const MAX: ID = <$ty>::MAX as ID;
+
+                fn from_index(index: usize) -> Self {
+                    <$ty>::try_from(index)
+                        .expect("The index type can not store the index")
+                }
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_index_type!(u16, u32, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_round_trip() {
+        assert_eq!(u16::from_index(7).index(), 7);
+        let max = u32::MAX as usize;
+        assert_eq!(u32::from_index(max).index(), max);
+        assert_eq!(<u32 as IndexType>::MAX, u32::MAX as ID);
+        assert_eq!(<usize as IndexType>::MAX, ID::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_overflow() {
+        u16::from_index(1 << 16);
+    }
+}
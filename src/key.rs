@@ -0,0 +1,223 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::ID;
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// A typed key made of an ID and a generation, like Handle, but chosen by
+/// the user so keys of different containers cannot be mixed up even when
+/// they store the same type. Usually implemented through `new_key_type!`.
+pub trait Key: Copy + Eq + Hash + Debug {
+    /// Builds a key from its ID and the generation of its slot
+    fn from_parts(id: ID, validity_id: ID) -> Self;
+
+    /// Returns the ID referenced by the key
+    fn id(&self) -> ID;
+
+    /// Returns the generation of the slot at the time the key was created
+    fn generation(&self) -> ID;
+}
+
+/// Declares key types implementing Key.
+///
+/// ```
+/// use stable_index_vector::{new_key_type, KeyedVector};
+///
+/// new_key_type! {
+///     /// Refers to an enemy
+///     pub struct EnemyId;
+///     struct ItemId;
+/// }
+///
+/// let mut enemies: KeyedVector<EnemyId, &str> = KeyedVector::default();
+/// let goblin = enemies.push("goblin");
+/// assert_eq!(enemies.get(goblin), Some(&"goblin"));
+/// ```
+#[macro_export]
+macro_rules! new_key_type {
+    ($($(#[$meta:meta])* $vis:vis struct $name:ident;)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(
+                Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd,
+                Ord,
+            )]
+            $vis struct $name {
+                id: $crate::ID,
+                validity_id: $crate::ID,
+            }
+
+            impl $crate::Key for $name {
+                fn from_parts(id: $crate::ID, validity_id: $crate::ID) -> Self {
+                    Self { id, validity_id }
+                }
+
+                fn id(&self) -> $crate::ID {
+                    self.id
+                }
+
+                fn generation(&self) -> $crate::ID {
+                    self.validity_id
+                }
+            }
+        )*
+    };
+}
+
+/// A Vector addressed by a typed key instead of IDs and handles. Two keyed
+/// vectors using different key types reject each other's keys at compile
+/// time.
+#[derive(Debug)]
+pub struct KeyedVector<K, T> {
+    /// The objects and their slots
+    vector: Vector<T>,
+    /// Ties the vector to its key type
+    _marker: PhantomData<fn() -> K>,
+}
+
+impl<K, T: Clone> Clone for KeyedVector<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            vector: self.vector.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, T> Default for KeyedVector<K, T> {
+    fn default() -> Self {
+        Self {
+            vector: Vector::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K: Key, T> KeyedVector<K, T> {
+    /// Wraps an existing vector
+    pub fn from_vector(vector: Vector<T>) -> Self {
+        Self {
+            vector,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an object
+    ///
+    /// @return The key of the object
+    pub fn push(&mut self, object: T) -> K {
+        let id = self.vector.push(object);
+        K::from_parts(id, self.vector.get_validity_id(id))
+    }
+
+    /// Removes the object referenced by the key
+    ///
+    /// @return The object, or None if the key is stale
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        self.vector.remove(&Self::handle(key)?)
+    }
+
+    /// Returns the object referenced by the key
+    #[must_use]
+    pub fn get(&self, key: K) -> Option<&T> {
+        self.vector.get(&Self::handle(key)?)
+    }
+
+    /// Returns the object referenced by the key
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        self.vector.get_mut(&Self::handle(key)?)
+    }
+
+    /// Tells if the key references a live object
+    #[must_use]
+    pub fn contains(&self, key: K) -> bool {
+        Self::handle(key)
+            .is_some_and(|handle| self.vector.contains(&handle))
+    }
+
+    /// Returns the number of objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    /// Tells if there is no object
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+
+    /// Returns an iterator over the objects and their keys, in data order
+    pub fn iter(&self) -> impl Iterator<Item = (K, &T)> + '_ {
+        self.vector
+            .iter_with_handles()
+            .map(|(handle, object)| (Self::key(handle), object))
+    }
+
+    /// Returns the underlying vector
+    #[must_use]
+    pub fn as_vector(&self) -> &Vector<T> {
+        &self.vector
+    }
+
+    /// Unwraps the underlying vector
+    pub fn into_vector(self) -> Vector<T> {
+        self.vector
+    }
+
+    // None for the keys no handle can represent, which reference nothing
+    fn handle(key: K) -> Option<Handle<T>> {
+        Handle::try_new(key.id(), key.generation())
+    }
+
+    fn key(handle: Handle<T>) -> K {
+        K::from_parts(handle.id, handle.generation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    new_key_type! {
+        struct EnemyId;
+        struct ItemId;
+    }
+
+    #[test]
+    fn test_keys_follow_generations() {
+        let mut enemies: KeyedVector<EnemyId, &str> = KeyedVector::default();
+        let mut items: KeyedVector<ItemId, &str> = KeyedVector::default();
+        let goblin = enemies.push("goblin");
+        let sword = items.push("sword");
+
+        assert_eq!(goblin.id(), sword.id());
+        assert_eq!(enemies.get(goblin), Some(&"goblin"));
+        assert_eq!(items.get(sword), Some(&"sword"));
+
+        assert_eq!(enemies.remove(goblin), Some("goblin"));
+        let orc = enemies.push("orc");
+        assert_eq!(orc.id(), goblin.id());
+        assert!(!enemies.contains(goblin));
+        assert_eq!(enemies.get(goblin), None);
+        *enemies.get_mut(orc).unwrap() = "troll";
+
+        let entries: Vec<_> = enemies.iter().collect();
+        assert_eq!(entries, vec![(orc, &"troll")]);
+    }
+
+    #[test]
+    fn test_unrepresentable_keys() {
+        let mut enemies: KeyedVector<EnemyId, &str> = KeyedVector::default();
+        enemies.push("goblin");
+        let forged = EnemyId::from_parts(0, ID::MAX);
+
+        assert_eq!(enemies.get(forged), None);
+        assert_eq!(enemies.get_mut(forged), None);
+        assert!(!enemies.contains(forged));
+        assert_eq!(enemies.remove(forged), None);
+        assert_eq!(enemies.get(EnemyId::default()), Some(&"goblin"));
+    }
+}
@@ -1,16 +1,103 @@
+// Lets the derive macros refer to this crate by name from within it
+extern crate self as stable_index_vector;
+
+pub mod alloc;
+pub mod array;
+pub mod brand;
+pub mod bytes;
+pub mod command;
+pub mod cow;
+#[cfg(feature = "qcell")]
+pub mod cell;
+pub mod group;
+pub mod cursor;
+pub mod double;
+pub mod entry;
+#[cfg(feature = "epoch")]
+pub mod epoch;
+pub mod events;
+pub mod extract;
+pub mod extras;
+pub mod growth;
 pub mod handle;
+pub mod heap_size;
+mod hooks;
+pub mod identity;
+pub mod index;
+pub mod key;
+#[cfg(feature = "mmap")]
+pub mod mapped;
 pub mod metadata;
+pub mod patch;
+pub mod projection;
+pub mod queue;
+pub mod raw;
+pub mod remap;
+pub mod reuse;
+pub mod secondary;
+pub mod shared;
+pub mod snapshot;
+#[cfg(any(feature = "serde", feature = "borsh"))]
+mod serialization;
+pub mod small;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod telemetry;
 pub mod vector;
+pub mod view;
 
+pub use crate::alloc::{Allocator, Global};
+pub use crate::array::*;
+pub use crate::brand::*;
+pub use crate::bytes::*;
+pub use crate::command::*;
+pub use crate::cow::*;
+pub use crate::group::*;
+pub use crate::cursor::*;
+pub use crate::double::*;
+pub use crate::entry::*;
+#[cfg(feature = "epoch")]
+pub use crate::epoch::*;
+pub use crate::events::*;
+pub use crate::extract::*;
+pub use crate::extras::*;
+pub use crate::growth::*;
 pub use crate::handle::*;
+pub use crate::heap_size::{HeapSize, MemoryUsage};
+pub use crate::identity::*;
+pub use crate::index::*;
+pub use crate::key::*;
+#[cfg(feature = "mmap")]
+pub use crate::mapped::*;
 pub use crate::metadata::*;
+pub use crate::patch::*;
+pub use crate::projection::*;
+pub use crate::queue::*;
+pub use crate::raw::*;
+pub use crate::remap::*;
+pub use crate::reuse::*;
+pub use crate::secondary::*;
+pub use crate::shared::*;
+pub use crate::snapshot::*;
+pub use crate::small::*;
+#[cfg(feature = "derive")]
+pub use stable_index_vector_derive::HandleRemap;
+pub use crate::telemetry::{TelemetrySnapshot, VectorStats};
 pub use crate::vector::*;
+pub use crate::view::*;
 
 /// Alias to differentiate betweens IDs and index.
 /// An ID allows to access the data through the index vector and is associated
 /// with the same object until it is erased. An index is simply the current
 /// position of the object in the data vector and may change with deletions.
+///
+/// @note Vectors may store their IDs in a narrower IndexType, e.g.
+/// `Vector<T, u32>`, but the API always takes and returns them as `usize`.
 pub type ID = usize;
 
-#[allow(unused)]
+/// The ID of the invalid handle, never given to an object.
 pub const INVALID_ID: usize = usize::MAX;
+
+/// The highest generation a slot can reach. `ID::MAX` is reserved so that
+/// handles have a niche.
+pub const MAX_GENERATION: ID = ID::MAX - 1;
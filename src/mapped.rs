@@ -0,0 +1,570 @@
+use crate::{ID, MAX_GENERATION};
+use crate::extras::SlotExtras;
+use crate::handle::Handle;
+use crate::metadata::Metadata;
+use crate::raw::{RawParts, RawPartsError};
+use crate::vector::Vector;
+use bytemuck::{Pod, Zeroable};
+use memmap2::MmapMut;
+use std::fs::File;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+use std::path::Path;
+
+// SAFETY: Metadata is `repr(C)` and made of two IDs, so it has no padding
+// and every bit pattern is a valid value
+unsafe impl Zeroable for Metadata {}
+unsafe impl Pod for Metadata {}
+
+/// The magic number starting every mapped file.
+const MAPPED_MAGIC: u64 = u64::from_le_bytes(*b"SIVMAP\0\0");
+
+/// The version of the layout of the mapped files.
+const MAPPED_VERSION: u64 = 1;
+
+/// The number of slots a mapped file is grown to at least.
+const MIN_CAPACITY: usize = 16;
+
+/// The fixed-size header starting a mapped file. It is stored with the
+/// native endianness, like the rest of the file, so a file written on a
+/// platform of the other endianness is rejected by its magic number.
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C)]
+struct Header {
+    /// MAPPED_MAGIC.
+    magic: u64,
+    /// MAPPED_VERSION.
+    version: u64,
+    /// The size of the objects in bytes.
+    element_size: u64,
+    /// The alignment of the objects in bytes.
+    element_align: u64,
+    /// The size of the IDs in bytes.
+    id_size: u64,
+    /// The number of slots the regions have room for.
+    capacity: u64,
+    /// The number of objects.
+    len: u64,
+    /// The number of IDs handed out so far.
+    slots: u64,
+    /// The number of retired slots, at the end of the slots.
+    retired: u64,
+    /// The generation of newly created slots.
+    generation_floor: u64,
+}
+
+/// The size in bytes of the header.
+const HEADER_SIZE: usize = size_of::<Header>();
+
+/// The byte offsets of the regions of a mapped file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Layout {
+    /// The number of slots the regions have room for.
+    capacity: usize,
+    /// The offset of the objects.
+    data: usize,
+    /// The offset of the metadata of the slots.
+    metadata: usize,
+    /// The offset of the data index of each ID.
+    indices: usize,
+    /// The size of the file.
+    len: usize,
+}
+
+impl Layout {
+    /// Computes the layout of a file with room for @p capacity slots of
+    /// objects of type @p T
+    ///
+    /// @return The layout, or None if the file would not be addressable
+    fn new<T>(capacity: usize) -> Option<Self> {
+        let data = HEADER_SIZE.next_multiple_of(align_of::<T>());
+        let metadata = capacity
+            .checked_mul(size_of::<T>())?
+            .checked_add(data)?
+            .checked_next_multiple_of(align_of::<Metadata>())?;
+        let indices = capacity
+            .checked_mul(size_of::<Metadata>())?
+            .checked_add(metadata)?;
+        let len =
+            capacity.checked_mul(size_of::<ID>())?.checked_add(indices)?;
+        Some(Self { capacity, data, metadata, indices, len })
+    }
+}
+
+/// The regions of a mapped file, viewed as typed slices.
+struct Regions<'a, T> {
+    /// The header.
+    header: &'a mut Header,
+    /// The objects, then room for more.
+    data: &'a mut [T],
+    /// The metadata of the slots, then room for more.
+    metadata: &'a mut [Metadata],
+    /// The data index of each ID, then room for more.
+    indices: &'a mut [ID],
+}
+
+/// A Vector of plain data objects living in a memory mapped file, so a
+/// large dataset is available right after opening the file instead of
+/// being decoded first. The objects, the metadata and the indices are all
+/// read and written in place, and handles stay valid across runs like the
+/// ones of Vector.
+///
+/// @note Freed slots are reused newest first, and slots reaching
+/// MAX_GENERATION are retired. The file uses the native endianness and ID
+/// width, and must not be modified by anything else while it is mapped.
+#[derive(Debug)]
+pub struct MappedVector<T> {
+    /// The mapped file, kept to grow it.
+    file: File,
+    /// The mapping of the whole file.
+    map: MmapMut,
+    /// The offsets of the regions in the mapping.
+    layout: Layout,
+    /// The type of the objects stored in the file.
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> MappedVector<T> {
+    /// Rejects the zero-sized types at compile time, as their objects
+    /// would not take any room in the file
+    const NOT_ZERO_SIZED: () =
+        assert!(size_of::<T>() > 0, "Zero-sized objects can not be mapped");
+
+    /// Writes the vector to a new mapped file, keeping all IDs and validity
+    /// IDs so handles stay valid
+    ///
+    /// @note The IDs reserved by an IdReserver and the taken slots become
+    /// free slots.
+    /// @param path The file to create or overwrite
+    /// @param vector The objects and slots to store
+    /// @return The mapped vector
+    pub fn create(
+        path: impl AsRef<Path>,
+        vector: &Vector<T>,
+    ) -> io::Result<Self> {
+        let () = Self::NOT_ZERO_SIZED;
+        let (metadata, indices) = vector.encoded_slots();
+        let capacity = metadata.len().max(MIN_CAPACITY);
+        let layout = layout_of::<T>(capacity)?;
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(layout.len as u64)?;
+        let map = map_file(&file)?;
+        let mut mapped = Self { file, map, layout, marker: PhantomData };
+        let regions = mapped.regions();
+        *regions.header = Header {
+            magic: MAPPED_MAGIC,
+            version: MAPPED_VERSION,
+            element_size: size_of::<T>() as u64,
+            element_align: align_of::<T>() as u64,
+            id_size: size_of::<ID>() as u64,
+            capacity: capacity as u64,
+            len: vector.len() as u64,
+            slots: metadata.len() as u64,
+            retired: vector.retired_count() as u64,
+            generation_floor: vector.generation_floor() as u64,
+        };
+        regions.data[..vector.len()].copy_from_slice(&vector.data);
+        regions.metadata[..metadata.len()].copy_from_slice(&metadata);
+        regions.indices[..indices.len()].copy_from_slice(&indices);
+        Ok(mapped)
+    }
+
+    /// Maps a file written by `create`. Only the header is read, so opening
+    /// takes the same time whatever the number of objects.
+    ///
+    /// @note The slots are not checked: a corrupted file can make the
+    /// operations panic, but can not cause undefined behavior.
+    /// @param path The file to map
+    /// @return The mapped vector, or an `InvalidData` error if the file was
+    /// not written by `create` for this object type
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let () = Self::NOT_ZERO_SIZED;
+        let file = File::options().read(true).write(true).open(path)?;
+        let map = map_file(&file)?;
+        let Some(header) = map.get(..HEADER_SIZE) else {
+            return Err(invalid_data("the file is too short"));
+        };
+        let header = bytemuck::from_bytes(header);
+        let layout = check_header::<T>(header, map.len())?;
+        Ok(Self { file, map, layout, marker: PhantomData })
+    }
+
+    /// Splits the mapping into its typed regions
+    fn regions(&mut self) -> Regions<'_, T> {
+        let layout = self.layout;
+        let (head, rest) = self.map.split_at_mut(layout.data);
+        let (data, rest) = rest.split_at_mut(layout.metadata - layout.data);
+        let (metadata, indices) =
+            rest.split_at_mut(layout.indices - layout.metadata);
+        let data_size = layout.capacity * size_of::<T>();
+        let indices_size = layout.capacity * size_of::<ID>();
+        Regions {
+            header: bytemuck::from_bytes_mut(&mut head[..HEADER_SIZE]),
+            data: bytemuck::cast_slice_mut(&mut data[..data_size]),
+            metadata: bytemuck::cast_slice_mut(metadata),
+            indices: bytemuck::cast_slice_mut(&mut indices[..indices_size]),
+        }
+    }
+
+    /// Returns the header
+    fn header(&self) -> &Header {
+        bytemuck::from_bytes(&self.map[..HEADER_SIZE])
+    }
+
+    /// Returns the metadata of the slots
+    fn metadata(&self) -> &[Metadata] {
+        let start = self.layout.metadata;
+        let end = start + self.slot_count() * size_of::<Metadata>();
+        bytemuck::cast_slice(&self.map[start..end])
+    }
+
+    /// Returns the data index of each ID
+    fn indices(&self) -> &[ID] {
+        let start = self.layout.indices;
+        let end = start + self.slot_count() * size_of::<ID>();
+        bytemuck::cast_slice(&self.map[start..end])
+    }
+
+    /// Returns the objects, in data order
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        let start = self.layout.data;
+        let end = start + self.len() * size_of::<T>();
+        bytemuck::cast_slice(&self.map[start..end])
+    }
+
+    /// Returns the objects, in data order, to modify them in place
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        let len = self.len();
+        &mut self.regions().data[..len]
+    }
+
+    /// Returns an iterator over the objects, in data order
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Returns the number of objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.header().len as usize
+    }
+
+    /// Tells if there is no object
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of slots the file has room for before it grows
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.layout.capacity
+    }
+
+    /// Returns the number of IDs handed out so far
+    fn slot_count(&self) -> usize {
+        self.header().slots as usize
+    }
+
+    /// Returns the data index of the object referenced by the handle
+    fn resolve(&self, handle: &Handle<T>) -> Option<usize> {
+        let position = *self.indices().get(handle.id)?;
+        (position < self.len()
+            && self.metadata()[position].validity_id == handle.generation())
+            .then_some(position)
+    }
+
+    /// Creates a handle pointing to the provided ID
+    ///
+    /// @return A handle to the object, or None if there is no object
+    #[must_use]
+    pub fn create_handle(&self, id: ID) -> Option<Handle<T>> {
+        let position = *self.indices().get(id)?;
+        if position >= self.len() {
+            return None;
+        }
+        Handle::try_new(id, self.metadata()[position].validity_id)
+    }
+
+    /// Tells if the handle references a live object
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    /// Returns the object referenced by the handle
+    #[must_use]
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        let position = self.resolve(handle)?;
+        Some(&self.as_slice()[position])
+    }
+
+    /// Returns the object referenced by the handle, to modify it in place
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        let position = self.resolve(handle)?;
+        Some(&mut self.as_mut_slice()[position])
+    }
+
+    /// Inserts an object, reusing a free ID if there is one. The file is
+    /// grown when all its slots are used.
+    ///
+    /// @param object The object to insert
+    /// @return The ID to retrieve the object, or the error of the growth
+    pub fn push(&mut self, object: T) -> io::Result<ID> {
+        let header = *self.header();
+        let (len, slots) = (header.len as usize, header.slots as usize);
+        let free_end = slots - header.retired as usize;
+        if len == free_end && slots == self.capacity() {
+            self.grow()?;
+        }
+        let regions = self.regions();
+        let id = if len < free_end {
+            let md = &mut regions.metadata[len];
+            md.validity_id =
+                md.validity_id.saturating_add(1).min(MAX_GENERATION);
+            md.reverse_id
+        } else {
+            // The new slot goes before the retired ones
+            regions.metadata[slots] =
+                Metadata::new(slots, header.generation_floor as ID);
+            regions.metadata.swap(len, slots);
+            regions.indices[slots] = len;
+            regions.indices[regions.metadata[slots].reverse_id] = slots;
+            regions.header.slots += 1;
+            slots
+        };
+        regions.data[len] = object;
+        regions.header.len += 1;
+        Ok(id)
+    }
+
+    /// Removes the object referenced by the handle, moving the last object
+    /// in its place
+    ///
+    /// @return The object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let position = self.resolve(handle)?;
+        let regions = self.regions();
+        let last = regions.header.len as usize - 1;
+        let last_id = regions.metadata[last].reverse_id;
+        let object = regions.data[position];
+        regions.data[position] = regions.data[last];
+        regions.metadata.swap(position, last);
+        regions.indices.swap(handle.id, last_id);
+        regions.header.len -= 1;
+
+        let md = &mut regions.metadata[last];
+        md.validity_id = md.validity_id.saturating_add(1).min(MAX_GENERATION);
+        if md.validity_id == MAX_GENERATION {
+            // The slot can not be reused without aliasing its old handles
+            let free_end = regions.header.slots - regions.header.retired;
+            let last_free = free_end as usize - 1;
+            regions.metadata.swap(last, last_free);
+            regions.indices[regions.metadata[last].reverse_id] = last;
+            regions.indices[handle.id] = last_free;
+            regions.header.retired += 1;
+        }
+        Some(object)
+    }
+
+    /// Doubles the number of slots the file has room for, moving the
+    /// metadata and the indices to their new offsets
+    fn grow(&mut self) -> io::Result<()> {
+        let old = self.layout;
+        let new = layout_of::<T>((old.capacity * 2).max(MIN_CAPACITY))?;
+        self.file.set_len(new.len as u64)?;
+        self.map = map_file(&self.file)?;
+        // The regions only move towards the end, the last one first so
+        // nothing is overwritten before it is moved
+        let indices_size = old.capacity * size_of::<ID>();
+        self.map
+            .copy_within(old.indices..old.indices + indices_size, new.indices);
+        let metadata_size = old.capacity * size_of::<Metadata>();
+        self.map.copy_within(
+            old.metadata..old.metadata + metadata_size,
+            new.metadata,
+        );
+        self.layout = new;
+        self.regions().header.capacity = new.capacity as u64;
+        Ok(())
+    }
+
+    /// Writes the changes made to the mapping back to the file
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    /// Copies the objects and the slots to a heap allocated Vector, keeping
+    /// their IDs and generations so existing handles stay valid
+    ///
+    /// @return The vector, or the first inconsistency found in the file
+    pub fn to_vector(&self) -> Result<Vector<T>, RawPartsError> {
+        Vector::from_raw_parts(RawParts {
+            data: self.as_slice().to_vec(),
+            metadata: self.metadata().to_vec(),
+            indices: self.indices().to_vec(),
+            retired: self.header().retired as usize,
+            identity: None,
+            generation_floor: self.header().generation_floor as ID,
+            extras: SlotExtras::default(),
+        })
+    }
+}
+
+/// Returns the layout of a file with room for @p capacity slots
+fn layout_of<T>(capacity: usize) -> io::Result<Layout> {
+    Layout::new::<T>(capacity)
+        .ok_or_else(|| io::Error::other("the file would not be addressable"))
+}
+
+/// Maps the whole @p file, to read and write it
+fn map_file(file: &File) -> io::Result<MmapMut> {
+    // SAFETY: the file is only accessed through this mapping while the
+    // mapped vector exists, as documented
+    unsafe { MmapMut::map_mut(file) }
+}
+
+/// Checks that a file of @p size bytes starting with @p header holds
+/// objects of type @p T
+///
+/// @return The layout of the file, or an `InvalidData` error
+fn check_header<T>(header: &Header, size: usize) -> io::Result<Layout> {
+    if header.magic != MAPPED_MAGIC {
+        return Err(invalid_data("the file is not a mapped vector"));
+    }
+    if header.version != MAPPED_VERSION {
+        return Err(invalid_data("unsupported mapped vector version"));
+    }
+    if header.element_size != size_of::<T>() as u64
+        || header.element_align != align_of::<T>() as u64
+        || header.id_size != size_of::<ID>() as u64
+    {
+        return Err(invalid_data("the file holds another object type"));
+    }
+    let counts_fit = header.len <= header.slots
+        && header.retired <= header.slots - header.len
+        && header.slots <= header.capacity;
+    let layout = usize::try_from(header.capacity)
+        .ok()
+        .and_then(Layout::new::<T>)
+        .filter(|layout| counts_fit && layout.len <= size);
+    layout.ok_or_else(|| invalid_data("inconsistent mapped vector header"))
+}
+
+/// Creates an `InvalidData` error
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Returns a file path unique to the test
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "stable-index-vector-{}-{name}.bin",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_handles_survive_reopening() {
+        let path = temp_path("reopen");
+        let mut vec: Vector<u32> = (0..4).collect();
+        vec.erase_by_id(1);
+        vec.retire_id(2);
+        let handle = vec.create_handle(3).unwrap();
+
+        let mapped = MappedVector::create(&path, &vec).unwrap();
+        assert_eq!(mapped.get(&handle), Some(&3));
+        drop(mapped);
+        let mut mapped = MappedVector::<u32>::open(&path).unwrap();
+        assert_eq!(mapped.len(), 2);
+        assert_eq!(mapped.get(&handle), Some(&3));
+        assert_eq!(mapped.push(10).unwrap(), 1);
+        assert_eq!(mapped.push(20).unwrap(), 4);
+        *mapped.get_mut(&handle).unwrap() = 30;
+        mapped.flush().unwrap();
+        drop(mapped);
+
+        let mapped = MappedVector::<u32>::open(&path).unwrap();
+        assert_eq!(mapped.get(&handle), Some(&30));
+        let reopened = mapped.to_vector().unwrap();
+        assert!(reopened.is_retired(2));
+        assert_eq!(reopened.len(), 4);
+        assert_eq!(reopened.get(&handle), Some(&30));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_grows() {
+        let path = temp_path("grow");
+        let mut mapped =
+            MappedVector::create(&path, &Vector::<u64>::default()).unwrap();
+        assert_eq!(mapped.capacity(), MIN_CAPACITY);
+        let ids: Vec<ID> =
+            (0..100).map(|i| mapped.push(i * 3).unwrap()).collect();
+        let handles: Vec<Handle<u64>> =
+            ids.iter().map(|&id| mapped.create_handle(id).unwrap()).collect();
+        assert_eq!(mapped.remove(&handles[10]), Some(30));
+        assert!(mapped.capacity() >= 100);
+        drop(mapped);
+
+        let mapped = MappedVector::<u64>::open(&path).unwrap();
+        assert_eq!(mapped.len(), 99);
+        assert!(!mapped.contains(&handles[10]));
+        for (i, handle) in handles.iter().enumerate().skip(11) {
+            assert_eq!(mapped.get(handle), Some(&(i as u64 * 3)));
+        }
+        assert_eq!(mapped.iter().sum::<u64>(), 3 * 4950 - 30);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_exhausted_slots_are_retired() {
+        let path = temp_path("retire");
+        let mut vec = Vector::default();
+        vec.set_generation_limit(MAX_GENERATION);
+        let id = vec.push(1u8);
+        vec.push(2u8);
+        let mut parts = vec.into_raw_parts();
+        parts.metadata[id].validity_id = MAX_GENERATION - 1;
+        let vec = Vector::from_raw_parts(parts).unwrap();
+
+        let mut mapped = MappedVector::create(&path, &vec).unwrap();
+        let handle = mapped.create_handle(id).unwrap();
+        assert_eq!(mapped.remove(&handle), Some(1));
+        assert_eq!(mapped.push(3).unwrap(), 2);
+        let vec = mapped.to_vector().unwrap();
+        assert!(vec.is_retired(id));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_rejects_other_files() {
+        let path = temp_path("reject");
+        std::fs::write(&path, b"not a vector").unwrap();
+        let error = MappedVector::<u32>::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        MappedVector::create(&path, &Vector::<u32>::default()).unwrap();
+        let error = MappedVector::<u64>::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(MappedVector::<u32>::open(&path).is_ok());
+
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_len(HEADER_SIZE as u64).unwrap();
+        let error = MappedVector::<u32>::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,23 +1,50 @@
-use crate::ID;
+use crate::{ID, index::IndexType};
 
-/// The struct holding additional information about an object.
+/// The struct holding additional information about an object. The reverse
+/// ID and the validity ID are stored as the index type of the vector.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct Metadata {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+// A fixed layout, so the metadata can be stored in mapped files
+#[repr(C)]
+pub struct Metadata<Idx = ID> {
     /// The reverse ID, allowing the retrieve the ID of the object from the
     /// data vector.
-    pub reverse_id: ID,
+    pub reverse_id: Idx,
     /// An identifier that is changed when the object is erased, used to
     /// ensure a handle is still valid.
-    pub validity_id: ID,
+    pub validity_id: Idx,
+}
+
+impl<Idx: IndexType> Metadata<Idx> {
+    /// Factory constructor for any index type
+    pub(crate) fn with_ids(reverse_id: ID, validity_id: ID) -> Self {
+        Self {
+            reverse_id: Idx::from_index(reverse_id),
+            validity_id: Idx::from_index(validity_id),
+        }
+    }
+
+    /// Returns the reverse ID
+    #[must_use]
+    pub fn id(&self) -> ID {
+        self.reverse_id.index()
+    }
+
+    /// Returns the validity ID
+    #[must_use]
+    pub fn generation(&self) -> ID {
+        self.validity_id.index()
+    }
 }
 
 impl Metadata {
     // Factory constructor
     pub fn new(reverse_id: ID, validity_id: ID) -> Self {
-        Self {
-            reverse_id,
-            validity_id,
-        }
+        Self::with_ids(reverse_id, validity_id)
     }
 }
 
@@ -52,6 +79,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_metadata_traits() {
         let m1 = Metadata::new(1, 1);
         
@@ -0,0 +1,245 @@
+use crate::ID;
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::fmt;
+
+/// The changes turning a Vector into another one, as computed by
+/// `Vector::diff`. Every change is keyed by the handle of the object, so
+/// the patch can be replayed on a copy sharing the same IDs.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Patch<T> {
+    /// The objects to insert, with the handle they have in the target.
+    pub inserted: Vec<(Handle<T>, T)>,
+    /// The handles of the objects to remove.
+    pub removed: Vec<Handle<T>>,
+    /// The new value of the objects present on both sides.
+    pub modified: Vec<(Handle<T>, T)>,
+}
+
+impl<T> Patch<T> {
+    /// Tells if the patch has no change
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+    }
+}
+
+impl<T> Default for Patch<T> {
+    fn default() -> Self {
+        Self { inserted: Vec::new(), removed: Vec::new(), modified: Vec::new() }
+    }
+}
+
+/// The reasons a whole patch can be rejected by
+/// `Vector::apply_patch_bounded`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PatchError {
+    /// An insertion targets an ID at or above the limit given to
+    /// `apply_patch_bounded`, which would create that many slots.
+    IdOutOfBounds { id: ID, limit: ID },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::IdOutOfBounds { id, limit } => {
+                write!(f, "inserted ID {id} is not below the limit {limit}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl<T: PartialEq + Clone> Vector<T> {
+    /// Computes the changes turning this vector into @p other. An ID live on
+    /// both sides with different generations is a removal followed by an
+    /// insertion, since its old handles do not reach the new object.
+    ///
+    /// @param other The target state, usually a copy of this vector that
+    /// evolved separately
+    /// @return The patch, removals and modifications in the data order of
+    /// this vector, insertions in the data order of @p other
+    #[must_use]
+    pub fn diff(&self, other: &Vector<T>) -> Patch<T> {
+        let mut patch = Patch::default();
+        for (handle, object) in self.iter_with_handles() {
+            // Resolved directly so the misses are not counted as stale
+            // accesses
+            match other.resolve(&handle).map(|index| &other.data[index]) {
+                Some(target) if target != object => {
+                    patch.modified.push((handle, target.clone()));
+                }
+                Some(_) => {}
+                None => patch.removed.push(handle),
+            }
+        }
+        for (handle, object) in other.iter_with_handles() {
+            if !self.contains(&handle) {
+                patch.inserted.push((handle, object.clone()));
+            }
+        }
+        patch
+    }
+}
+
+impl<T> Vector<T> {
+    /// Replays a patch: removes the objects, inserts the new ones in the
+    /// slots of their handles, which take the generation of the handle, and
+    /// replaces the modified ones. A live object with an older generation
+    /// than an inserted handle is removed first.
+    ///
+    /// @note Inserting at an ID creates all the slots up to it, so patches
+    /// received from untrusted peers should go through
+    /// `apply_patch_bounded`.
+    /// @param patch The changes, usually computed by `diff` on a copy
+    /// sharing the IDs of this vector
+    /// @return The handles of the changes that could not be applied because
+    /// the handle is stale or the slot is taken, retired or newer
+    pub fn apply_patch(&mut self, patch: Patch<T>) -> Vec<Handle<T>> {
+        let mut rejected = Vec::new();
+        for handle in patch.removed {
+            if self.remove(&handle).is_none() {
+                rejected.push(handle);
+            }
+        }
+        for (handle, object) in patch.inserted {
+            if self.contains_id(handle.id)
+                && self.get_validity_id(handle.id) < handle.generation()
+            {
+                self.erase_by_id(handle.id);
+            }
+            if self.insert_at(&handle, object).is_err() {
+                rejected.push(handle);
+            }
+        }
+        for (handle, object) in patch.modified {
+            if self.replace(&handle, object).is_err() {
+                rejected.push(handle);
+            }
+        }
+        rejected
+    }
+
+    /// Replays a patch like `apply_patch`, unless it inserts at an ID at or
+    /// above @p id_limit
+    ///
+    /// @param patch The changes, usually received from a peer
+    /// @param id_limit The IDs the patch may insert at are below this limit
+    /// @return The handles of the changes that could not be applied, or an
+    /// error without applying anything if an insertion is out of bounds
+    pub fn apply_patch_bounded(
+        &mut self,
+        patch: Patch<T>,
+        id_limit: ID,
+    ) -> Result<Vec<Handle<T>>, PatchError> {
+        if let Some((handle, _)) =
+            patch.inserted.iter().find(|(handle, _)| handle.id >= id_limit)
+        {
+            return Err(PatchError::IdOutOfBounds {
+                id: handle.id,
+                limit: id_limit,
+            });
+        }
+        Ok(self.apply_patch(patch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let mut local = Vector::default();
+        let a = local.push(1);
+        let b = local.push(2);
+        let c = local.push(3);
+        let h_a = local.create_handle(a).unwrap();
+        let h_b = local.create_handle(b).unwrap();
+        let h_c = local.create_handle(c).unwrap();
+
+        let mut remote = local.clone();
+        remote.replace(&h_a, 10).unwrap();
+        remote.erase_by_handle(&h_b);
+        remote.erase_by_handle(&h_c);
+        let d = remote.push(4);
+        let h_d = remote.create_handle(d).unwrap();
+        assert_eq!(h_d.id, h_c.id);
+
+        let patch = local.diff(&remote);
+        assert_eq!(patch.modified, vec![(h_a, 10)]);
+        assert_eq!(patch.removed, vec![h_b, h_c]);
+        assert_eq!(patch.inserted, vec![(h_d, 4)]);
+        assert!(local.diff(&local.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut authority = Vector::default();
+        let a = authority.push('a');
+        let b = authority.push('b');
+        let h_a = authority.create_handle(a).unwrap();
+        let h_b = authority.create_handle(b).unwrap();
+        let mut replica = authority.clone();
+
+        authority.replace(&h_a, 'A').unwrap();
+        authority.erase_by_handle(&h_b);
+        let c = authority.push('c');
+        let d = authority.push('d');
+        let h_c = authority.create_handle(c).unwrap();
+        let h_d = authority.create_handle(d).unwrap();
+
+        let patch = replica.diff(&authority);
+        assert_eq!(replica.apply_patch(patch), Vec::new());
+        assert!(replica.diff(&authority).is_empty());
+        assert_eq!(replica.get(&h_a), Some(&'A'));
+        assert_eq!(replica.get(&h_c), Some(&'c'));
+        assert_eq!(replica.get(&h_d), Some(&'d'));
+        assert!(!replica.contains(&h_b));
+    }
+
+    #[test]
+    fn test_apply_patch_rejections() {
+        let mut vec = Vector::default();
+        let a = vec.push(1);
+        let h_a = vec.create_handle(a).unwrap();
+        let stale = Handle::new(a, h_a.generation() + 5);
+        vec.erase_by_handle(&h_a);
+        let b = vec.push(2);
+        let h_b = vec.create_handle(b).unwrap();
+        let far = Handle::new(4, 0);
+
+        let patch = Patch {
+            inserted: vec![(h_a, 3), (far, 4)],
+            removed: vec![h_a],
+            modified: vec![(stale, 5), (h_b, 6)],
+        };
+        assert_eq!(vec.apply_patch(patch), vec![h_a, h_a, stale]);
+        assert_eq!(vec.get(&far), Some(&4));
+        assert_eq!(vec.get(&h_b), Some(&6));
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    fn test_apply_patch_bounds_ids() {
+        let mut vec = Vector::default();
+        let a = vec.push(1);
+        let h_a = vec.create_handle(a).unwrap();
+        let hostile = Handle::new(ID::MAX - 1, 0);
+
+        let patch = Patch {
+            inserted: vec![(Handle::new(1, 0), 2), (hostile, 3)],
+            removed: vec![h_a],
+            modified: Vec::new(),
+        };
+        assert_eq!(
+            vec.apply_patch_bounded(patch, 64),
+            Err(PatchError::IdOutOfBounds { id: ID::MAX - 1, limit: 64 })
+        );
+        assert_eq!(vec.get(&h_a), Some(&1));
+        assert_eq!(vec.indices.len(), 1);
+    }
+}
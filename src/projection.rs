@@ -0,0 +1,159 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// A read-only view over the objects of a vector that match a projection,
+/// typically the objects of a given variant when the element type is an
+/// enum. Handles yielded by the view resolve against the main vector.
+pub struct Projection<'a, T, F> {
+    /// The projected vector
+    vector: &'a Vector<T>,
+    /// The function selecting and projecting the objects
+    project: F,
+}
+
+impl<'a, T, V: ?Sized + 'a, F> Projection<'a, T, F>
+where
+    F: Fn(&T) -> Option<&V>,
+{
+    /// Factory constructor
+    pub fn new(vector: &'a Vector<T>, project: F) -> Self {
+        Self { vector, project }
+    }
+
+    /// Returns the projection of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The projected object, or None if the handle is stale or the
+    /// object does not match the projection
+    pub fn get(&self, handle: &Handle<T>) -> Option<&'a V> {
+        (self.project)(self.vector.get(handle)?)
+    }
+
+    /// Returns an iterator over the matching objects and their handles
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &'a V)> + '_ {
+        let live = &self.vector.metadata[..self.vector.len()];
+        live.iter().zip(self.vector.iter()).filter_map(|(md, object)| {
+            let projected = (self.project)(object)?;
+            Some((Handle::new(md.reverse_id, md.validity_id), projected))
+        })
+    }
+
+    /// Return the number of matching objects
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.vector.iter().filter(|o| (self.project)(o).is_some()).count()
+    }
+}
+
+/// A mutable view over the objects of a vector that match a projection.
+pub struct ProjectionMut<'a, T, F> {
+    /// The projected vector
+    vector: &'a mut Vector<T>,
+    /// The function selecting and projecting the objects
+    project: F,
+}
+
+impl<'a, T, V: ?Sized + 'a, F> ProjectionMut<'a, T, F>
+where
+    F: Fn(&mut T) -> Option<&mut V>,
+{
+    /// Factory constructor
+    pub fn new(vector: &'a mut Vector<T>, project: F) -> Self {
+        Self { vector, project }
+    }
+
+    /// Returns the projection of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The projected object, or None if the handle is stale or the
+    /// object does not match the projection
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut V> {
+        (self.project)(self.vector.get_mut(handle)?)
+    }
+
+    /// Returns an iterator over the matching objects and their handles
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut V)> {
+        let project = &self.project;
+        self.vector
+            .iter_mut_with_handles()
+            .filter_map(move |(handle, object)| {
+                Some((handle, project(object)?))
+            })
+    }
+}
+
+/// Builds a projection function selecting a single field variant of an enum,
+/// to be given to `Vector::project` or `Vector::project_mut`.
+///
+/// ```
+/// use stable_index_vector::{Vector, variant};
+///
+/// enum Shape {
+///     Circle(f32),
+///     Square(f32),
+/// }
+///
+/// let mut shapes = Vector::default();
+/// shapes.push(Shape::Circle(1.0));
+/// shapes.push(Shape::Square(2.0));
+///
+/// let circles = shapes.project(variant!(Shape::Circle));
+/// assert_eq!(circles.count(), 1);
+/// ```
+#[macro_export]
+macro_rules! variant {
+    ($($path:ident)::+) => {
+        |object| match object {
+            $($path)::+(value) => Some(value),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Entity {
+        Particle(u32),
+        Light(f32),
+    }
+
+    #[test]
+    fn test_projection() {
+        let mut vec = Vector::default();
+        let id_p = vec.push(Entity::Particle(1));
+        vec.push(Entity::Light(0.5));
+        vec.push(Entity::Particle(2));
+        let h_p = vec.create_handle(id_p).unwrap();
+
+        let particles = vec.project(variant!(Entity::Particle));
+        let mut values: Vec<u32> = particles.iter().map(|(_, p)| *p).collect();
+        values.sort();
+
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!(particles.count(), 2);
+        assert_eq!(particles.get(&h_p), Some(&1));
+
+        let lights = vec.project(variant!(Entity::Light));
+        assert_eq!(lights.get(&h_p), None);
+        let (h_l, _) = lights.iter().next().unwrap();
+        assert_eq!(vec.get(&h_l), Some(&Entity::Light(0.5)));
+    }
+
+    #[test]
+    fn test_projection_mut() {
+        let mut vec = Vector::default();
+        let id_p = vec.push(Entity::Particle(1));
+        vec.push(Entity::Light(0.5));
+        let h_p = vec.create_handle(id_p).unwrap();
+
+        let mut particles = vec.project_mut(variant!(Entity::Particle));
+        particles.iter_mut().for_each(|(_, p)| *p += 10);
+        *particles.get_mut(&h_p).unwrap() += 1;
+
+        assert_eq!(vec.get(&h_p), Some(&Entity::Particle(12)));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::collections::VecDeque;
+
+/// A FIFO queue with stable handles. Objects are popped in insertion order,
+/// and any object can still be accessed or removed by handle while queued.
+#[derive(Clone, Debug)]
+pub struct StableQueue<T> {
+    /// The storage of the queued objects.
+    vector: Vector<T>,
+    /// The handles in insertion order. Entries of removed objects are stale
+    /// and skipped lazily.
+    order: VecDeque<Handle<T>>,
+}
+
+impl<T> StableQueue<T> {
+    /// Appends the object at the back of the queue
+    ///
+    /// @param object The object to enqueue
+    /// @return A handle to the object
+    pub fn push_back(&mut self, object: T) -> Handle<T> {
+        let id = self.vector.push(object);
+        let handle = Handle::new(id, self.vector.get_validity_id(id));
+        self.order.push_back(handle);
+        handle
+    }
+
+    /// Removes the oldest object of the queue
+    ///
+    /// @return The oldest object and its handle, or None if the queue is
+    /// empty
+    pub fn pop_front(&mut self) -> Option<(Handle<T>, T)> {
+        self.skip_stale();
+        let handle = self.order.pop_front()?;
+        Some((handle, self.vector.remove_slot(handle.id)))
+    }
+
+    /// Returns the oldest object of the queue without removing it
+    pub fn front(&mut self) -> Option<(Handle<T>, &T)> {
+        self.skip_stale();
+        let handle = *self.order.front()?;
+        Some((handle, self.vector.get(&handle)?))
+    }
+
+    /// Removes the object referenced by the handle, wherever it is in the
+    /// queue
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.vector.resolve(handle)?;
+        let object = self.vector.remove_slot(handle.id);
+        if self.order.len() > 2 * self.vector.len() {
+            let vector = &self.vector;
+            self.order.retain(|h| vector.resolve(h).is_some());
+        }
+        Some(object)
+    }
+
+    /// Returns a reference to the object referenced by the handle
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        self.vector.get(handle)
+    }
+
+    /// Returns a mutable reference to the object referenced by the handle
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        self.vector.get_mut(handle)
+    }
+
+    /// Return the number of queued objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    /// Tells if the queue is currently empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+
+    /// Returns an iterator over the queued objects, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.order
+            .iter()
+            .filter_map(|h| Some((*h, self.vector.get(h)?)))
+    }
+
+    /// Drops the stale entries at the front of the order
+    fn skip_stale(&mut self) {
+        while let Some(handle) = self.order.front() {
+            if self.vector.resolve(handle).is_some() {
+                break;
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+impl<T> Default for StableQueue<T> {
+    fn default() -> Self {
+        Self {
+            vector: Vector::default(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_order() {
+        let mut queue = StableQueue::default();
+        queue.push_back(1);
+        queue.push_back(2);
+        queue.push_back(3);
+
+        assert_eq!(queue.pop_front().map(|(_, v)| v), Some(1));
+        assert_eq!(queue.pop_front().map(|(_, v)| v), Some(2));
+        queue.push_back(4);
+        assert_eq!(queue.pop_front().map(|(_, v)| v), Some(3));
+        assert_eq!(queue.pop_front().map(|(_, v)| v), Some(4));
+        assert!(queue.pop_front().is_none());
+    }
+
+    #[test]
+    fn test_remove_mid_queue() {
+        let mut queue = StableQueue::default();
+        let h1 = queue.push_back('a');
+        let h2 = queue.push_back('b');
+        let h3 = queue.push_back('c');
+
+        assert_eq!(queue.remove(&h2), Some('b'));
+        assert_eq!(queue.remove(&h2), None);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.iter().map(|(_, c)| *c).collect::<String>(), "ac");
+
+        assert_eq!(queue.remove(&h1), Some('a'));
+        assert_eq!(queue.front(), Some((h3, &'c')));
+        *queue.get_mut(&h3).unwrap() = 'd';
+        assert_eq!(queue.pop_front(), Some((h3, 'd')));
+        assert!(queue.is_empty());
+    }
+}
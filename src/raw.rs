@@ -0,0 +1,75 @@
+use crate::{ID, extras::SlotExtras, identity::VectorId, metadata::Metadata};
+use std::fmt;
+
+/// The decomposed state of a Vector, as produced by
+/// `Vector::into_raw_parts`. This is what serialization formats persist.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+pub struct RawParts<T> {
+    /// The live objects, in data order.
+    pub data: Vec<T>,
+    /// The metadata of every slot: live slots first, then free slots, then
+    /// retired slots.
+    pub metadata: Vec<Metadata>,
+    /// The metadata index for each ID.
+    pub indices: Vec<ID>,
+    /// The number of retired slots at the end of the metadata vector.
+    pub retired: usize,
+    /// The identity of the vector, if it was assigned one.
+    pub identity: Option<VectorId>,
+    /// The generation of the slots created from now on, raised by `reset`
+    /// above the generation of every handle it invalidated.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub generation_floor: ID,
+    /// The optional state of the slots, indexed by ID.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extras: SlotExtras,
+}
+
+/// The reasons raw parts can be rejected when rebuilding a Vector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RawPartsError {
+    /// The metadata and indices vectors do not have the same length.
+    LengthMismatch { metadata: usize, indices: usize },
+    /// There are more objects, or more objects and retired slots, than
+    /// slots.
+    TooManyObjects { objects: usize, slots: usize },
+    /// The slot at this metadata position does not round-trip through the
+    /// indices vector.
+    InconsistentSlot { position: usize },
+    /// The slot at this metadata position has a generation above
+    /// MAX_GENERATION.
+    GenerationOutOfRange { position: usize },
+    /// The optional state of the slots covers more IDs than there are.
+    TooManyExtras { extras: usize, ids: usize },
+}
+
+impl fmt::Display for RawPartsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RawPartsError::LengthMismatch { metadata, indices } => write!(
+                f,
+                "{metadata} metadata entries for {indices} indices"
+            ),
+            RawPartsError::TooManyObjects { objects, slots } => {
+                write!(f, "{objects} objects do not fit in {slots} slots")
+            }
+            RawPartsError::InconsistentSlot { position } => {
+                write!(f, "inconsistent slot at metadata position {position}")
+            }
+            RawPartsError::GenerationOutOfRange { position } => write!(
+                f,
+                "generation out of range at metadata position {position}"
+            ),
+            RawPartsError::TooManyExtras { extras, ids } => {
+                write!(f, "slot state for {extras} IDs out of {ids}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RawPartsError {}
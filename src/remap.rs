@@ -0,0 +1,233 @@
+use crate::ID;
+use crate::handle::Handle;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+
+/// The mapping from the handles of moved objects to their new handles,
+/// produced by the operations that move objects to other IDs.
+#[derive(Debug)]
+pub struct IdRemap<T> {
+    /// The validity ID and new handle of each moved object, by old ID
+    entries: HashMap<ID, (ID, Handle<T>)>,
+}
+
+impl<T> Clone for IdRemap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<T> Default for IdRemap<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<T> IdRemap<T> {
+    /// Records that the object referenced by @p old now lives at @p new
+    pub(crate) fn insert(&mut self, old: Handle<T>, new: Handle<T>) {
+        self.entries.insert(old.id, (old.generation(), new));
+    }
+
+    /// Returns the new handle of the object referenced by the old handle
+    ///
+    /// @param old The handle the object had before it was moved
+    /// @return The new handle, or None if @p old was stale or the object
+    /// was not moved
+    #[must_use]
+    pub fn remap(&self, old: &Handle<T>) -> Option<Handle<T>> {
+        match self.entries.get(&old.id) {
+            Some(&(validity_id, new)) if validity_id == old.generation() => {
+                Some(new)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the new handle of the object that had the provided ID
+    ///
+    /// @param id The ID the object had before it was moved
+    /// @return The new handle, or None if the object was not moved
+    #[must_use]
+    pub fn get(&self, id: ID) -> Option<Handle<T>> {
+        self.entries.get(&id).map(|&(_, new)| new)
+    }
+
+    /// Returns the number of moved objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Tells if no object was moved
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the old and new handles of the moved objects
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, Handle<T>)> + '_ {
+        self.entries.iter().map(|(&id, &(validity_id, new))| {
+            (Handle::new(id, validity_id), new)
+        })
+    }
+}
+
+/// An object holding handles that can be rewritten with the remap table
+/// produced by `compact`, `append` or `split_off`. Handles to other element
+/// types and handles missing from the table are left untouched.
+///
+/// With the `derive` feature, `#[derive(HandleRemap)]` implements it by
+/// remapping every field, except the ones marked with
+/// `#[handle_remap(skip)]`.
+pub trait HandleRemap<T> {
+    /// Rewrites the handles to objects of type @p T
+    ///
+    /// @param remap The mapping from the old handles to the new ones
+    fn remap_handles(&mut self, remap: &IdRemap<T>);
+}
+
+impl<T: 'static, U: 'static> HandleRemap<T> for Handle<U> {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        let this: &mut dyn Any = self;
+        if let Some(handle) = this.downcast_mut::<Handle<T>>()
+            && let Some(new) = remap.remap(handle)
+        {
+            *handle = new;
+        }
+    }
+}
+
+macro_rules! impl_handle_remap_noop {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> HandleRemap<T> for $ty {
+                fn remap_handles(&mut self, _remap: &IdRemap<T>) {}
+            }
+        )*
+    };
+}
+
+impl_handle_remap_noop!(
+    (), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128,
+    isize, f32, f64, String
+);
+
+impl<T, H: HandleRemap<T>> HandleRemap<T> for Option<H> {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        if let Some(inner) = self {
+            inner.remap_handles(remap);
+        }
+    }
+}
+
+impl<T, H: HandleRemap<T> + ?Sized> HandleRemap<T> for Box<H> {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        (**self).remap_handles(remap);
+    }
+}
+
+impl<T, H: HandleRemap<T>> HandleRemap<T> for [H] {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        for inner in self {
+            inner.remap_handles(remap);
+        }
+    }
+}
+
+impl<T, H: HandleRemap<T>, const N: usize> HandleRemap<T> for [H; N] {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        self.as_mut_slice().remap_handles(remap);
+    }
+}
+
+impl<T, H: HandleRemap<T>> HandleRemap<T> for Vec<H> {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        self.as_mut_slice().remap_handles(remap);
+    }
+}
+
+impl<T, H: HandleRemap<T>> HandleRemap<T> for VecDeque<H> {
+    fn remap_handles(&mut self, remap: &IdRemap<T>) {
+        for inner in self {
+            inner.remap_handles(remap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_checks_validity() {
+        let mut remap: IdRemap<u8> = IdRemap::default();
+        remap.insert(Handle::new(3, 1), Handle::new(0, 0));
+
+        assert_eq!(remap.remap(&Handle::new(3, 1)), Some(Handle::new(0, 0)));
+        assert_eq!(remap.remap(&Handle::new(3, 0)), None);
+        assert_eq!(remap.remap(&Handle::new(4, 1)), None);
+        assert_eq!(remap.get(3), Some(Handle::new(0, 0)));
+        assert_eq!(remap.len(), 1);
+    }
+
+    #[test]
+    fn test_remap_handles_in_containers() {
+        let mut remap: IdRemap<u8> = IdRemap::default();
+        remap.insert(Handle::new(1, 0), Handle::new(5, 2));
+        let mut handles: Vec<Option<Handle<u8>>> =
+            vec![Some(Handle::new(1, 0)), None];
+        let mut other: Handle<i8> = Handle::new(1, 0);
+
+        handles.remap_handles(&remap);
+        other.remap_handles(&remap);
+
+        assert_eq!(handles, vec![Some(Handle::new(5, 2)), None]);
+        assert_eq!(other, Handle::new(1, 0));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_handle_remap() {
+        struct Target;
+        struct Other;
+
+        #[derive(crate::HandleRemap)]
+        struct Component {
+            target: Handle<Target>,
+            others: Vec<Handle<Other>>,
+            name: String,
+            #[handle_remap(skip)]
+            _cache: std::cell::Cell<u8>,
+        }
+
+        #[derive(crate::HandleRemap)]
+        enum Order {
+            Idle,
+            Follow(Handle<Target>),
+        }
+
+        let mut remap: IdRemap<Target> = IdRemap::default();
+        remap.insert(Handle::new(0, 0), Handle::new(3, 1));
+        let mut component = Component {
+            target: Handle::new(0, 0),
+            others: vec![Handle::new(0, 0)],
+            name: "unit".to_string(),
+            _cache: std::cell::Cell::new(0),
+        };
+        let mut order = Order::Follow(Handle::new(0, 0));
+
+        component.remap_handles(&remap);
+        order.remap_handles(&remap);
+        Order::Idle.remap_handles(&remap);
+
+        assert_eq!(component.target.id, 3);
+        assert_eq!(component.others[0].id, 0);
+        assert_eq!(component.name, "unit");
+        assert!(matches!(order, Order::Follow(h) if h.id == 3));
+    }
+}
@@ -0,0 +1,13 @@
+/// The policy choosing which free slot is reused when an object is added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ReusePolicy {
+    /// Reuse the most recently freed slot first, which costs nothing.
+    #[default]
+    Lifo,
+    /// Reuse the least recently freed slot first, spreading generation
+    /// churn over all the free slots. Costs a shift of the free slots.
+    Fifo,
+    /// Reuse the free slot with the lowest ID, keeping live IDs compact.
+    /// Costs a scan of the free slots.
+    LowestId,
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+
+use crate::ID;
+use crate::handle::Handle;
+
+/// Auxiliary data attached to the objects of a Vector, stored densely by
+/// ID. Every value remembers the generation of the handle it was inserted
+/// with, so stale handles read as absent.
+#[derive(Debug)]
+pub struct SecondaryMap<T, V> {
+    /// The generation and value attached to each ID
+    slots: Vec<Option<(ID, V)>>,
+    /// The number of values in the map
+    len: usize,
+    /// Ties the map to the element type of the primary vector
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T, V: Clone> Clone for SecondaryMap<T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, V> Default for SecondaryMap<T, V> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, V> SecondaryMap<T, V> {
+    /// Attaches a value to the object referenced by the handle. A value
+    /// attached through an older generation of the slot is replaced, while
+    /// a stale or invalid handle is rejected.
+    ///
+    /// @param handle The handle referencing the object
+    /// @param value The value to attach
+    /// @return The value previously attached through the same generation
+    pub fn insert(&mut self, handle: &Handle<T>, value: V) -> Option<V> {
+        if handle.is_invalid() {
+            return None;
+        }
+        if handle.id >= self.slots.len() {
+            self.slots.resize_with(handle.id + 1, || None);
+        }
+        let slot = &mut self.slots[handle.id];
+        match slot {
+            Some((generation, _)) if *generation > handle.generation() => None,
+            Some((generation, previous))
+                if *generation == handle.generation() =>
+            {
+                Some(std::mem::replace(previous, value))
+            }
+            _ => {
+                if slot.is_none() {
+                    self.len += 1;
+                }
+                *slot = Some((handle.generation(), value));
+                None
+            }
+        }
+    }
+
+    /// Detaches the value of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The value, or None if there is none for this generation
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<V> {
+        self.get(handle)?;
+        self.len -= 1;
+        self.slots[handle.id].take().map(|(_, value)| value)
+    }
+
+    /// Returns the value attached to the object referenced by the handle
+    #[must_use]
+    pub fn get(&self, handle: &Handle<T>) -> Option<&V> {
+        match self.slots.get(handle.id)? {
+            Some((generation, value)) if *generation == handle.generation() => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value attached to the object referenced by the handle
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut V> {
+        match self.slots.get_mut(handle.id)? {
+            Some((generation, value)) if *generation == handle.generation() => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Tells if a value is attached to the object referenced by the handle
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the number of values in the map, including the ones attached
+    /// through handles that became stale since
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tells if the map holds no value
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all the values
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the values and the handles they were
+    /// attached through, in ID order
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &V)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(id, slot)| {
+            let (generation, value) = slot.as_ref()?;
+            Some((Handle::new(id, *generation), value))
+        })
+    }
+
+    /// Returns an iterator over the values and the handles they were
+    /// attached through, in ID order
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (Handle<T>, &mut V)> + '_ {
+        self.slots.iter_mut().enumerate().filter_map(|(id, slot)| {
+            let (generation, value) = slot.as_mut()?;
+            Some((Handle::new(id, *generation), value))
+        })
+    }
+}
+
+/// Auxiliary data attached to a small fraction of the objects of a Vector,
+/// stored in a hash map by ID. It follows the same generation rules as
+/// SecondaryMap without paying for the IDs that carry no value.
+#[derive(Debug)]
+pub struct SparseSecondaryMap<T, V> {
+    /// The generation and value attached to each ID
+    slots: HashMap<ID, (ID, V)>,
+    /// Ties the map to the element type of the primary vector
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T, V: Clone> Clone for SparseSecondaryMap<T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, V> Default for SparseSecondaryMap<T, V> {
+    fn default() -> Self {
+        Self {
+            slots: HashMap::new(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, V> SparseSecondaryMap<T, V> {
+    /// Attaches a value to the object referenced by the handle. A value
+    /// attached through an older generation of the slot is replaced, while
+    /// a stale or invalid handle is rejected.
+    ///
+    /// @param handle The handle referencing the object
+    /// @param value The value to attach
+    /// @return The value previously attached through the same generation
+    pub fn insert(&mut self, handle: &Handle<T>, value: V) -> Option<V> {
+        if handle.is_invalid() {
+            return None;
+        }
+        match self.slots.get_mut(&handle.id) {
+            Some((generation, _)) if *generation > handle.generation() => None,
+            Some((generation, previous))
+                if *generation == handle.generation() =>
+            {
+                Some(std::mem::replace(previous, value))
+            }
+            _ => {
+                self.slots.insert(handle.id, (handle.generation(), value));
+                None
+            }
+        }
+    }
+
+    /// Detaches the value of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The value, or None if there is none for this generation
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<V> {
+        self.get(handle)?;
+        self.slots.remove(&handle.id).map(|(_, value)| value)
+    }
+
+    /// Returns the value attached to the object referenced by the handle
+    #[must_use]
+    pub fn get(&self, handle: &Handle<T>) -> Option<&V> {
+        match self.slots.get(&handle.id)? {
+            (generation, value) if *generation == handle.generation() => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value attached to the object referenced by the handle
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut V> {
+        match self.slots.get_mut(&handle.id)? {
+            (generation, value) if *generation == handle.generation() => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Tells if a value is attached to the object referenced by the handle
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the number of values in the map, including the ones attached
+    /// through handles that became stale since
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Tells if the map holds no value
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Removes all the values
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    /// Returns an iterator over the values and the handles they were
+    /// attached through, in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &V)> + '_ {
+        self.slots.iter().map(|(id, (generation, value))| {
+            (Handle::new(*id, *generation), value)
+        })
+    }
+
+    /// Returns an iterator over the values and the handles they were
+    /// attached through, in arbitrary order
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (Handle<T>, &mut V)> + '_ {
+        self.slots.iter_mut().map(|(id, (generation, value))| {
+            (Handle::new(*id, *generation), value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vector;
+
+    #[test]
+    fn test_stale_handles_read_as_absent() {
+        let mut vec = Vector::default();
+        let id = vec.push('a');
+        let old = vec.create_handle(id).unwrap();
+        let mut names = SecondaryMap::default();
+
+        assert_eq!(names.insert(&old, "alpha"), None);
+        assert_eq!(names.get(&old), Some(&"alpha"));
+
+        vec.erase_by_handle(&old);
+        let id = vec.push('b');
+        let new = vec.create_handle(id).unwrap();
+        assert_eq!(names.get(&new), None);
+        assert_eq!(names.insert(&new, "beta"), None);
+        assert_eq!(names.get(&old), None);
+        assert_eq!(names.insert(&old, "stale"), None);
+        assert_eq!(names.get(&new), Some(&"beta"));
+        assert_eq!(names.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_remove_iter() {
+        let mut names: SecondaryMap<u8, &str> = SecondaryMap::default();
+        let a = Handle::new(4, 0);
+        let b = Handle::new(1, 2);
+
+        names.insert(&a, "a");
+        names.insert(&b, "b");
+        assert_eq!(names.insert(&a, "A"), Some("a"));
+        *names.get_mut(&b).unwrap() = "B";
+
+        let entries: Vec<_> = names.iter().collect();
+        assert_eq!(entries, vec![(b, &"B"), (a, &"A")]);
+        assert_eq!(names.remove(&a), Some("A"));
+        assert_eq!(names.remove(&a), None);
+        assert!(!names.contains(&a));
+        assert_eq!(names.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_handle_is_rejected() {
+        let mut names: SecondaryMap<u8, &str> = SecondaryMap::default();
+        let mut tags: SparseSecondaryMap<u8, &str> = Default::default();
+        let invalid = Handle::invalid();
+
+        assert_eq!(names.insert(&invalid, "none"), None);
+        assert_eq!(tags.insert(&invalid, "none"), None);
+        assert!(!names.contains(&invalid));
+        assert!(tags.is_empty());
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn test_sparse_map_follows_generations() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..100).map(|i| vec.push(i)).collect();
+        let old = vec.create_handle(ids[42]).unwrap();
+        let mut tags = SparseSecondaryMap::default();
+
+        assert_eq!(tags.insert(&old, "boss"), None);
+        assert_eq!(tags.insert(&old, "miniboss"), Some("boss"));
+        assert_eq!(tags.len(), 1);
+
+        vec.erase_by_handle(&old);
+        let id = vec.push(42);
+        let new = vec.create_handle(id).unwrap();
+        assert_eq!(tags.get(&new), None);
+        assert_eq!(tags.remove(&new), None);
+        assert_eq!(tags.insert(&new, "minion"), None);
+        assert_eq!(tags.insert(&old, "stale"), None);
+        *tags.get_mut(&new).unwrap() = "elite";
+
+        let entries: Vec<_> = tags.iter().collect();
+        assert_eq!(entries, vec![(new, &"elite")]);
+        assert_eq!(tags.remove(&new), Some("elite"));
+        assert!(tags.is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::ID;
+use crate::handle::Handle;
+use crate::raw::RawParts;
+use crate::vector::Vector;
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "serde")]
+use serde::de::Error;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "borsh")]
+use std::io;
+
+/// Vectors are serialized as their raw parts, so the IDs and generations
+/// survive a round trip and handles created before serialization stay
+/// valid. Configuration such as the growth strategy or the bounded mode is
+/// not persisted, and reserved IDs are persisted as free slots.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Vector<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let (metadata, indices) = self.encoded_slots();
+        let mut state = serializer.serialize_struct("RawParts", 7)?;
+        state.serialize_field("data", self.as_slice())?;
+        state.serialize_field("metadata", &*metadata)?;
+        state.serialize_field("indices", &*indices)?;
+        state.serialize_field("retired", &self.retired_count())?;
+        state.serialize_field("identity", &self.identity())?;
+        state.serialize_field("generation_floor", &self.generation_floor())?;
+        state.serialize_field("extras", self.slot_extras())?;
+        state.end()
+    }
+}
+
+/// The raw parts are validated like with `Vector::from_raw_parts`, so
+/// corrupted input is rejected instead of producing dangling IDs.
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vector<T> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let parts = <RawParts<T>>::deserialize(deserializer)?;
+        Vector::from_raw_parts(parts).map_err(D::Error::custom)
+    }
+}
+
+/// The canonical borsh encoding is the one of the raw parts, in field
+/// order: data, metadata, indices, retired count, identity, generation
+/// floor, then the optional state of the slots. Each metadata is encoded in
+/// declaration order and IDs are encoded as u64, so the encoding does not
+/// depend on the platform.
+#[cfg(feature = "borsh")]
+impl<T: BorshSerialize> BorshSerialize for Vector<T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let (metadata, indices) = self.encoded_slots();
+        BorshSerialize::serialize(self.as_slice(), writer)?;
+        BorshSerialize::serialize(&*metadata, writer)?;
+        BorshSerialize::serialize(&*indices, writer)?;
+        BorshSerialize::serialize(&self.retired_count(), writer)?;
+        BorshSerialize::serialize(&self.identity(), writer)?;
+        BorshSerialize::serialize(&self.generation_floor(), writer)?;
+        BorshSerialize::serialize(self.slot_extras(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: BorshDeserialize> BorshDeserialize for Vector<T> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let parts = RawParts::deserialize_reader(reader)?;
+        Vector::from_raw_parts(parts)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// The serialized form of a Handle, whose generation is stored inverted.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Handle")]
+struct HandleParts {
+    id: ID,
+    validity_id: ID,
+}
+
+/// Handles are serialized as their ID and validity ID, and the element type
+/// does not need to be serializable.
+#[cfg(feature = "serde")]
+impl<T> Serialize for Handle<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let parts = HandleParts { id: self.id, validity_id: self.generation() };
+        parts.serialize(serializer)
+    }
+}
+
+/// The reserved generation is rejected instead of panicking.
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for Handle<T> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let parts = HandleParts::deserialize(deserializer)?;
+        Handle::try_new(parts.id, parts.validity_id)
+            .ok_or_else(|| D::Error::custom("reserved handle generation"))
+    }
+}
+
+/// Handles are encoded as their ID and validity ID, as two u64.
+#[cfg(feature = "borsh")]
+impl<T> BorshSerialize for Handle<T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&self.id, writer)?;
+        BorshSerialize::serialize(&self.generation(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T> BorshDeserialize for Handle<T> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let id = ID::deserialize_reader(reader)?;
+        let validity_id = ID::deserialize_reader(reader)?;
+        Handle::try_new(id, validity_id).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "reserved handle generation",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_round_trip_keeps_handles() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..4).map(|i| vec.push(i.to_string())).collect();
+        vec.erase_by_id(ids[1]);
+        vec.retire_id(ids[2]);
+        let reused = vec.push("reused".to_string());
+        let handle = vec.create_handle(ids[3]).unwrap();
+        let reused = vec.create_handle(reused).unwrap();
+
+        let json = serde_json::to_string(&vec).unwrap();
+        let restored: Vector<String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, vec);
+        assert_eq!(restored.metadata, vec.metadata);
+        assert_eq!(restored.get(&handle).map(String::as_str), Some("3"));
+        assert_eq!(restored.get(&reused).map(String::as_str), Some("reused"));
+        assert!(restored.is_retired(ids[2]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_corrupted_input_is_rejected() {
+        let json = r#"{"data":[1,2],"metadata":[],"indices":[],
+            "retired":0,"identity":null}"#;
+
+        assert!(serde_json::from_str::<Vector<i32>>(json).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_round_trip() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..3u32).map(|i| vec.push(i)).collect();
+        vec.erase_by_id(ids[0]);
+        let handle = vec.create_handle(ids[2]).unwrap();
+
+        let bytes = borsh::to_vec(&vec).unwrap();
+        let restored: Vector<u32> = borsh::from_slice(&bytes).unwrap();
+
+        assert_eq!(restored, vec);
+        assert_eq!(restored.metadata, vec.metadata);
+        assert_eq!(restored.get(&handle), Some(&2));
+        let handle_bytes = borsh::to_vec(&handle).unwrap();
+        assert_eq!(
+            handle_bytes,
+            [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_rejects_reserved_generation() {
+        let mut bytes = vec![0u8; 8];
+        bytes.extend(u64::MAX.to_le_bytes());
+
+        assert!(borsh::from_slice::<Handle<u32>>(&bytes).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_reserved_generation() {
+        let json = format!(r#"{{"id":0,"validity_id":{}}}"#, u64::MAX);
+
+        assert!(serde_json::from_str::<Handle<u32>>(&json).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_encoding_is_stable() {
+        let mut vec = Vector::default();
+        vec.push(7u8);
+
+        let bytes = borsh::to_vec(&vec).unwrap();
+
+        let mut expected = vec![1, 0, 0, 0, 7];
+        expected.extend([1, 0, 0, 0]);
+        expected.extend([0u8; 16]);
+        expected.extend([1, 0, 0, 0]);
+        expected.extend([0u8; 8]);
+        expected.extend([0u8; 8]);
+        expected.push(0);
+        expected.extend([0u8; 8]);
+        expected.extend([0u8; 16]);
+        assert_eq!(bytes, expected);
+    }
+
+    /// Resets a vector holding two objects, pushes one and returns the
+    /// handles created before the reset
+    fn reset_with_handles(vec: &mut Vector<u32>) -> Vec<Handle<u32>> {
+        let old = (0..2)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+        vec.reset();
+        vec.push(100);
+        old
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_keeps_reset_generations() {
+        let mut vec = Vector::default();
+        let old = reset_with_handles(&mut vec);
+
+        let json = serde_json::to_string(&vec).unwrap();
+        let mut restored: Vector<u32> = serde_json::from_str(&json).unwrap();
+        restored.push(200);
+
+        assert_eq!(restored.get(&old[0]), None);
+        assert_eq!(restored.get(&old[1]), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_keeps_pending_reservations() {
+        let mut vec: Vector<u32> = (0..2).collect();
+        let reserved = vec.id_reserver().reserve(2);
+
+        let json = serde_json::to_string(&vec).unwrap();
+        let mut restored: Vector<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.indices.len(), 4);
+        restored.push(10);
+        restored.push(20);
+
+        for reservation in reserved {
+            assert!(!restored.contains(&reservation.handle()));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_accepts_missing_generation_floor() {
+        let json = r#"{"data":[],"metadata":[],"indices":[],
+            "retired":0,"identity":null}"#;
+
+        let vec: Vector<i32> = serde_json::from_str(json).unwrap();
+        assert!(vec.is_empty());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_keeps_reset_generations() {
+        let mut vec = Vector::default();
+        let old = reset_with_handles(&mut vec);
+
+        let bytes = borsh::to_vec(&vec).unwrap();
+        let mut restored: Vector<u32> = borsh::from_slice(&bytes).unwrap();
+        restored.push(200);
+
+        assert_eq!(restored.get(&old[0]), None);
+        assert_eq!(restored.get(&old[1]), None);
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_keeps_pending_reservations() {
+        let mut vec: Vector<u32> = (0..2).collect();
+        let reserved = vec.id_reserver().reserve(2);
+
+        let bytes = borsh::to_vec(&vec).unwrap();
+        let mut restored: Vector<u32> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(restored.indices.len(), 4);
+        restored.push(10);
+        restored.push(20);
+
+        for reservation in reserved {
+            assert!(!restored.contains(&reservation.handle()));
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::ID;
+use crate::handle::Handle;
+use crate::vector::Vector;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The number of shards of a default SharedVector.
+pub const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A vector with stable handles that can be accessed from several threads
+/// through a shared reference. The objects are spread over shards, each
+/// behind its own lock, so threads working on objects of different shards
+/// do not wait for each other.
+///
+/// @note The ID of a handle encodes its shard: the shard is the ID modulo
+/// the shard count, so handles only make sense for the vector that created
+/// them.
+#[derive(Debug)]
+pub struct SharedVector<T> {
+    /// The shards, each holding the objects of the IDs it is the modulo of.
+    shards: Box<[RwLock<Vector<T>>]>,
+    /// The shard receiving the next insertion, taken modulo the shard count.
+    next_shard: AtomicUsize,
+}
+
+/// Read access to an object of a SharedVector, holding its shard locked for
+/// reading.
+pub struct SharedReadGuard<'a, T> {
+    /// The locked shard.
+    shard: RwLockReadGuard<'a, Vector<T>>,
+    /// The data index of the object in the shard.
+    index: usize,
+}
+
+/// Write access to an object of a SharedVector, holding its shard locked
+/// for writing.
+pub struct SharedWriteGuard<'a, T> {
+    /// The locked shard.
+    shard: RwLockWriteGuard<'a, Vector<T>>,
+    /// The data index of the object in the shard.
+    index: usize,
+}
+
+impl<T> SharedVector<T> {
+    /// Creates an empty vector with @p shard_count shards
+    ///
+    /// @param shard_count The number of shards, at least one
+    /// @return The empty vector
+    #[must_use]
+    pub fn new(shard_count: usize) -> Self {
+        assert!(shard_count != 0, "The shard count must not be zero");
+        Self {
+            shards: (0..shard_count)
+                .map(|_| RwLock::new(Vector::default()))
+                .collect(),
+            next_shard: AtomicUsize::new(0),
+        }
+    }
+
+    /// Inserts the object in the next shard, in round robin
+    ///
+    /// @param object The object to insert
+    /// @return The handle to the object
+    pub fn push(&self, object: T) -> Handle<T> {
+        let shard =
+            self.next_shard.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        let mut vector = self.lock_write(shard);
+        let id = vector.push(object);
+        Handle::new(self.global_id(shard, id), vector.get_validity_id(id))
+    }
+
+    /// Removes the object referenced by the handle and returns it
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object, or None if the handle is stale
+    pub fn remove(&self, handle: &Handle<T>) -> Option<T> {
+        let (shard, local) = self.split(handle)?;
+        self.lock_write(shard).remove(&local)
+    }
+
+    /// Locks the shard of the object for reading and gives access to the
+    /// object
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The guard, or None if the handle is stale
+    pub fn read(&self, handle: &Handle<T>) -> Option<SharedReadGuard<'_, T>> {
+        let (shard, local) = self.split(handle)?;
+        let shard = self.lock_read(shard);
+        let index = shard.resolve(&local)?;
+        Some(SharedReadGuard { shard, index })
+    }
+
+    /// Locks the shard of the object for writing and gives access to the
+    /// object
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The guard, or None if the handle is stale
+    pub fn write(&self, handle: &Handle<T>) -> Option<SharedWriteGuard<'_, T>> {
+        let (shard, local) = self.split(handle)?;
+        let mut shard = self.lock_write(shard);
+        let index = shard.resolve(&local)?;
+        shard.touch(index);
+        Some(SharedWriteGuard { shard, index })
+    }
+
+    /// Tells if the handle references a live object
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.split(handle).is_some_and(|(shard, local)| {
+            self.lock_read(shard).contains(&local)
+        })
+    }
+
+    /// Returns the number of live objects, locking the shards one by one
+    #[must_use]
+    pub fn len(&self) -> usize {
+        (0..self.shards.len())
+            .map(|shard| self.lock_read(shard).len())
+            .sum()
+    }
+
+    /// Tells if there is no live object, locking the shards one by one
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|shard| self.lock_read(shard).is_empty())
+    }
+
+    /// Returns the number of shards
+    #[must_use]
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Consumes the vector into its shards
+    pub fn into_shards(self) -> Vec<Vector<T>> {
+        self.shards
+            .into_iter()
+            .map(|shard| shard.into_inner().expect("Poisoned shard lock"))
+            .collect()
+    }
+
+    /// Returns the ID of a shard local ID
+    fn global_id(&self, shard: usize, id: ID) -> ID {
+        id * self.shards.len() + shard
+    }
+
+    /// Splits a handle into its shard and its handle in the shard
+    fn split(&self, handle: &Handle<T>) -> Option<(usize, Handle<T>)> {
+        if handle.is_invalid() {
+            return None;
+        }
+        let count = self.shards.len();
+        let local = Handle::new(handle.id / count, handle.generation());
+        Some((handle.id % count, local))
+    }
+
+    /// Locks the shard for reading
+    fn lock_read(&self, shard: usize) -> RwLockReadGuard<'_, Vector<T>> {
+        self.shards[shard].read().expect("Poisoned shard lock")
+    }
+
+    /// Locks the shard for writing
+    fn lock_write(&self, shard: usize) -> RwLockWriteGuard<'_, Vector<T>> {
+        self.shards[shard].write().expect("Poisoned shard lock")
+    }
+}
+
+impl<T> Default for SharedVector<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHARD_COUNT)
+    }
+}
+
+impl<T> Deref for SharedReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.shard.data[self.index]
+    }
+}
+
+impl<T> Deref for SharedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.shard.data[self.index]
+    }
+}
+
+impl<T> DerefMut for SharedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.shard.data[self.index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_shared_access() {
+        let vec = SharedVector::new(4);
+        let handles: Vec<_> = (0..8).map(|i| vec.push(i)).collect();
+        assert_eq!(vec.len(), 8);
+
+        thread::scope(|scope| {
+            for chunk in handles.chunks(2) {
+                let vec = &vec;
+                scope.spawn(move || {
+                    for handle in chunk {
+                        *vec.write(handle).unwrap() *= 10;
+                    }
+                });
+            }
+        });
+
+        for (i, handle) in handles.iter().enumerate() {
+            assert_eq!(*vec.read(handle).unwrap(), i * 10);
+        }
+        assert_eq!(vec.remove(&handles[5]), Some(50));
+        assert!(vec.read(&handles[5]).is_none());
+        assert!(!vec.contains(&handles[5]));
+        assert!(vec.read(&Handle::invalid()).is_none());
+        assert_eq!(vec.into_shards().iter().map(Vector::len).sum::<usize>(), 7);
+    }
+
+    #[test]
+    fn test_stale_handle_in_reused_slot() {
+        let vec = SharedVector::new(1);
+        let old = vec.push('a');
+        vec.remove(&old);
+        let new = vec.push('b');
+
+        assert_eq!(old.id, new.id);
+        assert!(vec.read(&old).is_none());
+        assert_eq!(*vec.read(&new).unwrap(), 'b');
+    }
+}
@@ -0,0 +1,154 @@
+use crate::ID;
+use crate::array::ArrayVector;
+use crate::handle::Handle;
+use crate::vector::Vector;
+
+/// A Vector that keeps up to @p N objects inline and moves them to the heap
+/// the first time it grows past that, keeping their IDs and handles.
+// Keeping the inline storage out of a box is the whole point
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum SmallVector<T, const N: usize> {
+    /// The objects fit in the inline storage
+    Inline(ArrayVector<T, N>),
+    /// The objects outgrew the inline storage
+    Heap(Vector<T>),
+}
+
+impl<T, const N: usize> Default for SmallVector<T, N> {
+    fn default() -> Self {
+        SmallVector::Inline(ArrayVector::default())
+    }
+}
+
+impl<T, const N: usize> SmallVector<T, N> {
+    /// Inserts an object, moving the objects to the heap if the inline
+    /// storage is full
+    ///
+    /// @param object The object to insert
+    /// @return The ID to retrieve the object
+    pub fn push(&mut self, object: T) -> ID {
+        let object = match self {
+            SmallVector::Inline(array) => match array.push(object) {
+                Ok(id) => return id,
+                Err(object) => object,
+            },
+            SmallVector::Heap(vector) => return vector.push(object),
+        };
+        let inline =
+            std::mem::replace(self, SmallVector::Heap(Vector::default()));
+        let SmallVector::Inline(array) = inline else {
+            unreachable!("The objects are stored inline");
+        };
+        let mut vector = array.into_vector();
+        let id = vector.push(object);
+        *self = SmallVector::Heap(vector);
+        id
+    }
+
+    /// Removes the object referenced by the handle
+    ///
+    /// @return The object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        match self {
+            SmallVector::Inline(array) => array.remove(handle),
+            SmallVector::Heap(vector) => vector.remove(handle),
+        }
+    }
+
+    /// Creates a handle pointing to the provided ID
+    #[must_use]
+    pub fn create_handle(&self, id: ID) -> Option<Handle<T>> {
+        match self {
+            SmallVector::Inline(array) => array.create_handle(id),
+            SmallVector::Heap(vector) => vector.create_handle(id),
+        }
+    }
+
+    /// Returns the object referenced by the handle
+    #[must_use]
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        match self {
+            SmallVector::Inline(array) => array.get(handle),
+            SmallVector::Heap(vector) => vector.get(handle),
+        }
+    }
+
+    /// Returns the object referenced by the handle
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        match self {
+            SmallVector::Inline(array) => array.get_mut(handle),
+            SmallVector::Heap(vector) => vector.get_mut(handle),
+        }
+    }
+
+    /// Tells if the handle references a live object
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        match self {
+            SmallVector::Inline(array) => array.contains(handle),
+            SmallVector::Heap(vector) => vector.contains(handle),
+        }
+    }
+
+    /// Returns the number of objects
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            SmallVector::Inline(array) => array.len(),
+            SmallVector::Heap(vector) => vector.len(),
+        }
+    }
+
+    /// Tells if there is no object
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tells if the objects are still stored inline
+    #[must_use]
+    pub fn is_inline(&self) -> bool {
+        matches!(self, SmallVector::Inline(_))
+    }
+
+    /// Returns an iterator over the objects, in data order
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let (inline, heap) = match self {
+            SmallVector::Inline(array) => (Some(array.iter()), None),
+            SmallVector::Heap(vector) => (None, Some(vector.iter())),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(heap.into_iter().flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_keeps_handles() {
+        let mut vec: SmallVector<u32, 2> = SmallVector::default();
+        let a = vec.push(1);
+        let b = vec.push(2);
+        let handle_a = vec.create_handle(a).unwrap();
+        let handle_b = vec.create_handle(b).unwrap();
+        vec.remove(&handle_a);
+        let a = vec.push(3);
+        let reused = vec.create_handle(a).unwrap();
+        assert!(vec.is_inline());
+
+        let c = vec.push(4);
+        assert!(!vec.is_inline());
+        assert_eq!(c, 2);
+        assert_eq!(vec.get(&handle_b), Some(&2));
+        assert_eq!(vec.get(&reused), Some(&3));
+        assert!(!vec.contains(&handle_a));
+        *vec.get_mut(&reused).unwrap() = 5;
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![2, 5, 4]);
+        assert_eq!(vec.len(), 3);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::ID;
+use crate::extras::SlotExtras;
+use crate::metadata::Metadata;
+use crate::vector::Vector;
+use std::collections::VecDeque;
+
+/// The logical state of a Vector captured by `Vector::snapshot`: the
+/// objects, the IDs and the generations of every slot, so `Vector::restore`
+/// can roll the vector back to it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot<T> {
+    /// The live objects, in data order.
+    pub(crate) data: Vec<T>,
+    /// The metadata of every slot.
+    pub(crate) metadata: Vec<Metadata>,
+    /// The metadata index for each ID.
+    pub(crate) indices: Vec<ID>,
+    /// The optional state of the slots.
+    pub(crate) extras: SlotExtras,
+    /// The number of retired slots.
+    pub(crate) retired: usize,
+    /// The number of taken slots.
+    pub(crate) taken: usize,
+    /// The generation of newly created slots.
+    pub(crate) generation_floor: ID,
+    /// The insertion order of the objects in bounded mode.
+    pub(crate) insertion_queue: VecDeque<(ID, ID)>,
+    /// The heap bytes owned by the objects.
+    pub(crate) heap_size: usize,
+}
+
+impl<T> Snapshot<T> {
+    /// Returns the number of objects captured
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Tells if no object was captured
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// A bounded history of snapshots of a Vector, to undo and redo changes.
+/// Record the state before each change, then undo to roll it back.
+#[derive(Clone, Debug)]
+pub struct UndoStack<T> {
+    /// The states to go back to, oldest first.
+    undo: VecDeque<Snapshot<T>>,
+    /// The states undone, most recently undone last.
+    redo: Vec<Snapshot<T>>,
+    /// The maximum number of undo steps kept, or None for no limit.
+    limit: Option<usize>,
+}
+
+impl<T: Clone> UndoStack<T> {
+    /// Creates an empty history
+    ///
+    /// @param limit The maximum number of undo steps kept, the oldest ones
+    /// being dropped first, or None for no limit
+    /// @return The empty history
+    #[must_use]
+    pub fn new(limit: Option<usize>) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), limit }
+    }
+
+    /// Records the current state of the vector as an undo step and forgets
+    /// the undone steps
+    ///
+    /// @param vector The vector about to be changed
+    pub fn record(&mut self, vector: &Vector<T>) {
+        if self.limit == Some(0) {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(vector.snapshot());
+        if let Some(limit) = self.limit
+            && self.undo.len() > limit
+        {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Rolls the vector back to the last recorded state
+    ///
+    /// @param vector The vector to roll back
+    /// @return False if there is nothing to undo
+    pub fn undo(&mut self, vector: &mut Vector<T>) -> bool {
+        let Some(snapshot) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(vector.snapshot());
+        vector.restore(&snapshot);
+        true
+    }
+
+    /// Applies again the last undone change
+    ///
+    /// @param vector The vector to roll forward
+    /// @return False if there is nothing to redo
+    pub fn redo(&mut self, vector: &mut Vector<T>) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(vector.snapshot());
+        vector.restore(&snapshot);
+        true
+    }
+
+    /// Tells if there is a step to undo
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Tells if there is a step to redo
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all the steps
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<T: Clone> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut vec = Vector::default();
+        let mut history = UndoStack::new(None);
+        let a = vec.push(1);
+        let h_a = vec.create_handle(a).unwrap();
+
+        history.record(&vec);
+        vec.erase_by_handle(&h_a);
+        assert!(history.undo(&mut vec));
+        assert_eq!(vec.get(&h_a), Some(&1));
+        assert!(!history.undo(&mut vec));
+
+        assert!(history.redo(&mut vec));
+        assert_eq!(vec.get(&h_a), None);
+        assert!(!history.can_redo());
+
+        // The handles of a reused slot do not survive the undo
+        history.record(&vec);
+        assert_eq!(vec.push(2), a);
+        let h_b = vec.create_handle(a).unwrap();
+        assert!(history.undo(&mut vec));
+        assert!(vec.is_empty());
+        assert_eq!(vec.push(3), a);
+        assert_eq!(vec.get(&h_a), None);
+        assert_eq!(vec.get(&h_b), None);
+    }
+
+    #[test]
+    fn test_undo_limit() {
+        let mut vec = Vector::default();
+        let mut history = UndoStack::new(Some(2));
+        for i in 0..4 {
+            history.record(&vec);
+            vec.push(i);
+        }
+
+        while history.undo(&mut vec) {}
+        assert_eq!(vec.data, [0, 1]);
+
+        history.redo(&mut vec);
+        history.record(&vec);
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::handle::Handle;
+use crate::vector::Vector;
+use proptest::collection::SizeRange;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use std::fmt::Debug;
+
+/// A generated vector with a realistic history of insertions and removals,
+/// together with handles into it.
+#[derive(Clone, Debug)]
+pub struct Fragmented<T> {
+    /// The generated vector.
+    pub vector: Vector<T>,
+    /// Handles to every live object.
+    pub live: Vec<Handle<T>>,
+    /// Handles to removed objects, which must not resolve anymore.
+    pub stale: Vec<Handle<T>>,
+}
+
+/// One step of the generated history
+#[derive(Clone, Debug)]
+enum Operation<T> {
+    /// Push a new object
+    Push(T),
+    /// Erase one of the live objects
+    Erase(Index),
+}
+
+/// Generates a vector by replaying a random history of pushes and removals,
+/// roughly one removal for every two pushes, so it has free slots and
+/// bumped generations like a long-lived vector
+///
+/// @param element The strategy generating the objects
+/// @param operations The number of pushes and removals to replay
+/// @return A strategy producing the vector and its live and stale handles
+pub fn fragmented_vector<S>(
+    element: S,
+    operations: impl Into<SizeRange>,
+) -> impl Strategy<Value = Fragmented<S::Value>>
+where
+    S: Strategy,
+    S::Value: Clone + Debug,
+{
+    let operation = prop_oneof![
+        2 => element.prop_map(Operation::Push),
+        1 => any::<Index>().prop_map(Operation::Erase),
+    ];
+    proptest::collection::vec(operation, operations).prop_map(replay)
+}
+
+/// Generates a fragmented vector of arbitrary objects
+pub fn any_fragmented_vector<T>() -> impl Strategy<Value = Fragmented<T>>
+where
+    T: Arbitrary + Clone,
+{
+    fragmented_vector(any::<T>(), 0..64)
+}
+
+/// Applies the generated history to an empty vector
+fn replay<T>(operations: Vec<Operation<T>>) -> Fragmented<T> {
+    let mut vector = Vector::default();
+    let mut live: Vec<Handle<T>> = Vec::new();
+    let mut stale = Vec::new();
+    for operation in operations {
+        match operation {
+            Operation::Push(object) => {
+                let id = vector.push(object);
+                live.push(Handle::new(id, vector.get_validity_id(id)));
+            }
+            Operation::Erase(index) if !live.is_empty() => {
+                let handle = live.swap_remove(index.index(live.len()));
+                vector.erase_by_handle(&handle);
+                stale.push(handle);
+            }
+            Operation::Erase(_) => {}
+        }
+    }
+    Fragmented { vector, live, stale }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_generated_handles(generated in any_fragmented_vector::<u8>()) {
+            let Fragmented { vector, live, stale } = generated;
+
+            prop_assert_eq!(vector.len(), live.len());
+            for handle in &live {
+                prop_assert!(vector.get(handle).is_some());
+            }
+            for handle in &stale {
+                prop_assert!(vector.get(handle).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// The counters maintained by a vector about its activity. They are updated
+/// with relaxed atomics so even read-only accesses can be counted.
+#[derive(Default)]
+pub(crate) struct Telemetry {
+    /// Number of objects ever inserted.
+    inserts: AtomicU64,
+    /// Number of objects ever removed.
+    erases: AtomicU64,
+    /// Number of accesses attempted with a stale or invalid handle.
+    stale_accesses: AtomicU64,
+    /// Largest number of objects live at the same time.
+    peak_len: AtomicUsize,
+    /// Whether stale accesses are left uncounted, for vectors read by many
+    /// threads at once.
+    ignore_stale_accesses: bool,
+}
+
+impl Telemetry {
+    /// Records inserted objects
+    pub(crate) fn record_inserts(&self, count: usize) {
+        self.inserts.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Records removed objects
+    pub(crate) fn record_erases(&self, count: usize) {
+        self.erases.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Records an access with a stale or invalid handle
+    pub(crate) fn record_stale_access(&self) {
+        if !self.ignore_stale_accesses {
+            self.stale_accesses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Stops counting the stale accesses, so reads never write the counters
+    #[cfg(feature = "epoch")]
+    pub(crate) fn ignore_stale_accesses(&mut self) {
+        self.ignore_stale_accesses = true;
+    }
+
+    /// Records the current number of live objects, after it grew
+    pub(crate) fn record_len(&self, len: usize) {
+        self.peak_len.fetch_max(len, Ordering::Relaxed);
+    }
+
+    /// Returns the largest number of objects live at the same time
+    pub(crate) fn peak_len(&self) -> usize {
+        self.peak_len.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current value of the counters
+    pub(crate) fn snapshot(
+        &self,
+        live: usize,
+        capacity: usize,
+    ) -> TelemetrySnapshot {
+        TelemetrySnapshot {
+            live,
+            capacity,
+            inserts: self.inserts.load(Ordering::Relaxed),
+            erases: self.erases.load(Ordering::Relaxed),
+            stale_accesses: self.stale_accesses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Clone for Telemetry {
+    fn clone(&self) -> Self {
+        let load = |counter: &AtomicU64| {
+            AtomicU64::new(counter.load(Ordering::Relaxed))
+        };
+        Self {
+            inserts: load(&self.inserts),
+            erases: load(&self.erases),
+            stale_accesses: load(&self.stale_accesses),
+            peak_len: AtomicUsize::new(self.peak_len()),
+            // A clone is not shared with the readers of the original
+            ignore_stale_accesses: false,
+        }
+    }
+}
+
+impl fmt::Debug for Telemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("inserts", &self.inserts)
+            .field("erases", &self.erases)
+            .field("stale_accesses", &self.stale_accesses)
+            .field("peak_len", &self.peak_len)
+            .finish()
+    }
+}
+
+/// The activity counters of a vector at a given time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TelemetrySnapshot {
+    /// The number of live objects.
+    pub live: usize,
+    /// The capacity of the data vector.
+    pub capacity: usize,
+    /// The number of objects ever inserted.
+    pub inserts: u64,
+    /// The number of objects ever removed.
+    pub erases: u64,
+    /// The number of accesses attempted with a stale or invalid handle.
+    pub stale_accesses: u64,
+}
+
+/// The occupancy of the ID space of a vector, as returned by
+/// `Vector::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VectorStats {
+    /// The number of live objects.
+    pub live: usize,
+    /// The number of slots ever allocated, which is the number of IDs.
+    pub slots: usize,
+    /// The number of free slots, ready to be reused.
+    pub free: usize,
+    /// The number of taken or reserved slots.
+    pub taken: usize,
+    /// The number of retired slots, never reused.
+    pub retired: usize,
+    /// The largest number of objects live at the same time.
+    pub high_water: usize,
+    /// The average generation of the slots, 0 without slots.
+    pub average_generation: f64,
+}
+
+#[cfg(feature = "metrics")]
+impl TelemetrySnapshot {
+    /// Publishes the counters to the `metrics` facade, so any installed
+    /// recorder (e.g. a Prometheus exporter) can expose them. Call this
+    /// periodically, typically once per frame or scrape interval.
+    ///
+    /// @param prefix The prefix of the metric names, e.g. "entities"
+    pub fn publish(&self, prefix: &str) {
+        metrics::gauge!(format!("{prefix}_live_objects")).set(self.live as f64);
+        metrics::gauge!(format!("{prefix}_capacity")).set(self.capacity as f64);
+        metrics::counter!(format!("{prefix}_inserts_total"))
+            .absolute(self.inserts);
+        metrics::counter!(format!("{prefix}_erases_total"))
+            .absolute(self.erases);
+        metrics::counter!(format!("{prefix}_stale_accesses_total"))
+            .absolute(self.stale_accesses);
+    }
+}
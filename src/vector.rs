@@ -1,53 +1,407 @@
-use crate::{ID, handle::Handle, metadata::Metadata};
+use crate::{ID, INVALID_ID, MAX_GENERATION};
+use crate::alloc::{Allocator, Global, TryReserveError, buffer};
+use crate::alloc::{clone_from_slice, convert_error, from_vec, into_vec};
+use crate::alloc::new_buffer;
+use crate::{growth::GrowthStrategy, handle::Handle};
+use crate::index::IndexType;
+use crate::metadata::Metadata;
+use crate::handle::{IdReservations, IdReserver, PackedHandle, ReservedHandle};
+use crate::cursor::CursorMut;
+use crate::entry::Entry;
+use crate::events::{EventOp, VectorEvent};
+use crate::extract::ExtractIf;
+use crate::extras::SlotExtras;
+use crate::group::{Group, GroupViewMut};
+use crate::heap_size::{HeapSize, MemoryUsage, SizeHook};
+use crate::hooks::{EraseHook, RelocateHook};
+use crate::identity::{StampedHandle, VectorId, generate_vector_id};
+use crate::projection::{Projection, ProjectionMut};
+use crate::raw::{RawParts, RawPartsError};
+use crate::remap::IdRemap;
+use crate::reuse::ReusePolicy;
+use crate::snapshot::Snapshot;
+use crate::telemetry::{Telemetry, TelemetrySnapshot, VectorStats};
+use crate::view::{SharedView, VectorView};
+use std::alloc::Layout;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Index, IndexMut, Range};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
 use std::marker::PhantomData;
-use std::ops::{Index, IndexMut};
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Vector<T> {
+pub struct Vector<T, Idx = ID, A: Allocator = Global> {
     /// The vector holding the actual objects.
-    pub data: Vec<T>,
+    pub data: buffer!(T, A),
     /// The vector holding the associated metadata. It is accessed using the
     /// same index as for the data vector.
-    pub metadata: Vec<Metadata>,
+    pub metadata: buffer!(Metadata<Idx>, A),
     /// The vector that stores the data index for each ID.
-    pub indices: Vec<ID>,
+    pub indices: buffer!(Idx, A),
+    /// The allocator of the three vectors above.
+    allocator: A,
+    /// When set, every mutating operation panics until the vector is
+    /// unfrozen.
+    frozen: bool,
+    /// The policy used to grow the internal vectors.
+    growth: GrowthStrategy,
+    /// The policy choosing the free slot reused by insertions.
+    reuse: ReusePolicy,
+    /// The tick recorded in the metadata of newly inserted objects.
+    tick: u64,
+    /// Whether mutable accesses bump the per-object modification counter.
+    track_modifications: bool,
+    /// Whether the reuses of each slot are counted.
+    track_reuses: bool,
+    /// The optional state of the slots, only allocated when used.
+    extras: SlotExtras,
+    /// The number of retired slots, kept at the end of the metadata vector
+    /// and never reused.
+    retired: usize,
+    /// The number of taken slots, kept right before the retired slots and
+    /// not reused until their object is put back.
+    taken: usize,
+    /// The maximum number of live objects in bounded mode.
+    max_len: Option<usize>,
+    /// The highest generation a slot may reach. A slot freed at this
+    /// generation is retired instead of being reused.
+    generation_limit: ID,
+    /// Whether erased IDs are retired instead of being reused.
+    monotonic: bool,
+    /// The generation of newly created slots, raised by `reset` so the IDs
+    /// it frees never match an old handle.
+    generation_floor: ID,
+    /// The IDs and validity IDs of the objects in insertion order, only
+    /// maintained in bounded mode. Entries may be stale.
+    insertion_queue: VecDeque<(ID, ID)>,
+    /// The identity of the vector, stamped in serialized handles.
+    identity: Option<VectorId>,
+    /// The heap size function of the objects, when deep memory usage is
+    /// tracked.
+    size_hook: Option<SizeHook<T>>,
+    /// The running total of the heap bytes owned by the objects.
+    heap_size: usize,
+    /// The function called with each object right before it is removed.
+    erase_hook: Option<EraseHook<T>>,
+    /// The function called with each object moved in the data vector.
+    relocate_hook: Option<RelocateHook<T>>,
+    /// Whether inserts and erases are recorded in the event log.
+    log_events: bool,
+    /// The inserts and erases recorded since the last drain.
+    events: Vec<VectorEvent<T>>,
+    /// The IDs reserved from shared references, whose slots are created by
+    /// the next mutation needing them.
+    reserved_ids: IdReservations,
+    /// The activity counters of the vector.
+    telemetry: Telemetry,
 }
 
 /// A vector that provides stable IDs when adding objects.
 /// These ID will still allow to access their associated objects even after
 /// inserting or removing other objects.
 /// This comes at the cost of a small overhead because of an addition indirection.
-impl<T> Vector<T> {
+impl<T, Idx: IndexType, A: Allocator + Clone> Vector<T, Idx, A> {
+    /// Creates an empty vector storing its IDs, generations and data
+    /// indices as @p Idx, its internal vectors allocating from @p alloc.
+    /// Generations are capped at the highest value of @p Idx.
+    ///
+    /// @note Entries, cursors, views, snapshots, raw parts, serialization,
+    /// the parallel iterators and the other modules need the default index
+    /// type and allocator.
+    /// @param alloc The allocator of the internal vectors
+    /// @return The empty vector
+    #[must_use]
+    pub fn with_index_type_in(alloc: A) -> Self {
+        Self {
+            data: new_buffer(&alloc),
+            metadata: new_buffer(&alloc),
+            indices: new_buffer(&alloc),
+            allocator: alloc,
+            frozen: false,
+            growth: GrowthStrategy::default(),
+            reuse: ReusePolicy::default(),
+            tick: 0,
+            track_modifications: false,
+            track_reuses: false,
+            extras: SlotExtras::default(),
+            retired: 0,
+            taken: 0,
+            max_len: None,
+            generation_limit: MAX_GENERATION.min(Idx::MAX),
+            monotonic: false,
+            generation_floor: 0,
+            insertion_queue: VecDeque::new(),
+            identity: None,
+            size_hook: None,
+            heap_size: 0,
+            erase_hook: None,
+            relocate_hook: None,
+            log_events: false,
+            events: Vec::new(),
+            reserved_ids: IdReservations::default(),
+            telemetry: Telemetry::default(),
+        }
+    }
+
     /// Copies the provided object at the end of the vector
     ///
     /// @param object The object to copy
     /// @return The ID to retrieve the object
     pub fn push(&mut self, object: T) -> ID {
-        let id = self.get_free_slot();
-        self.data.push(object);
-        id
+        self.push_evicting(object).0
+    }
+
+    /// Inserts the object, reporting allocation failures instead of aborting
+    ///
+    /// @param object The object to insert
+    /// @return The ID to retrieve the object, or the object and the error if
+    /// the internal vectors could not grow
+    pub fn try_push(&mut self, object: T) -> Result<ID, (T, TryReserveError)> {
+        if let Err(error) = self.try_reserve(1) {
+            return Err((object, error));
+        }
+        Ok(self.push(object))
+    }
+
+    /// Inserts @p count objects with a contiguous range of new IDs, so the
+    /// batch can be addressed by range. Free slots are not reused.
+    ///
+    /// @note In bounded mode the objects making room for the batch are all
+    /// evicted before it is created, so the batch never evicts its own
+    /// objects. A batch larger than the maximum length panics.
+    /// @param count The number of objects to create
+    /// @param make Creates an object from its ID
+    /// @return The IDs of the new objects
+    pub fn push_batch<F>(&mut self, count: usize, mut make: F) -> Range<ID>
+    where
+        F: FnMut(ID) -> T,
+    {
+        self.assert_not_frozen();
+        if let Some(max_len) = self.max_len {
+            assert!(count <= max_len, "The batch exceeds the maximum length");
+            while self.data.len() + count > max_len
+                && self.evict_oldest().is_some()
+            {}
+        }
+        self.create_reserved_slots();
+        let start = self.indices.len();
+        self.growth.reserve(&mut self.data, count);
+        self.growth.reserve(&mut self.metadata, count);
+        self.growth.reserve(&mut self.indices, count);
+        for _ in 0..count {
+            let id = self.new_slot();
+            self.store(make(id));
+            if self.max_len.is_some() {
+                self.record_insertion(id);
+            }
+        }
+        start..self.indices.len()
+    }
+
+    /// Inserts the objects of an iterator, reserving the room for all of
+    /// them at once
+    ///
+    /// @param objects The objects to insert
+    /// @return The ID of each object, in iteration order
+    pub fn push_many<I>(&mut self, objects: I) -> Vec<ID>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.assert_not_frozen();
+        let objects = objects.into_iter();
+        self.reserve_insertions(objects.size_hint().0);
+        objects.map(|object| self.push(object)).collect()
+    }
+
+    /// Copies the provided object at the end of the vector. In bounded mode,
+    /// if the vector is full, the oldest object is removed first.
+    ///
+    /// @param object The object to copy
+    /// @return The ID to retrieve the object and the evicted object, if any
+    pub fn push_evicting(&mut self, object: T) -> (ID, Option<T>) {
+        self.push_with_evicting(|_| object)
+    }
+
+    /// Inserts the object created by the closure, which receives the ID
+    /// the object will be stored at
+    ///
+    /// @param make Creates the object from its ID
+    /// @return The ID to retrieve the object
+    pub fn push_with<F>(&mut self, make: F) -> ID
+    where
+        F: FnOnce(ID) -> T,
+    {
+        self.push_with_evicting(make).0
+    }
+
+    /// Enables or disables the bounded mode. In bounded mode, pushing
+    /// beyond the limit evicts the oldest live object.
+    ///
+    /// @note Objects already in the vector are considered inserted in data
+    /// order when the mode is enabled.
+    /// @param max_len The maximum number of live objects, or None to disable
+    /// @return The objects evicted to honor the new limit, oldest first
+    pub fn set_max_len(&mut self, max_len: Option<usize>) -> Vec<T> {
+        self.assert_not_frozen();
+        assert!(max_len != Some(0), "The maximum length must not be zero");
+        self.insertion_queue.clear();
+        self.max_len = max_len;
+        let Some(max_len) = max_len else {
+            return Vec::new();
+        };
+        let live = &self.metadata[..self.data.len()];
+        self.insertion_queue
+            .extend(live.iter().map(|md| (md.id(), md.generation())));
+        let mut evicted = Vec::new();
+        while self.data.len() > max_len {
+            evicted.extend(self.evict_oldest());
+        }
+        evicted
+    }
+
+    /// Returns the maximum number of live objects in bounded mode
+    #[must_use]
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Sets the highest generation a slot may reach, so handles can be
+    /// stored in a narrower type, e.g. `u32::MAX as ID` for PackedHandle.
+    /// Generations never wrap: a slot freed at the limit is retired, so an
+    /// old handle can never alias a reused slot.
+    ///
+    /// @note Free slots already at the limit are retired immediately, live
+    /// ones when they are erased.
+    /// @param limit The highest generation, clamped to MAX_GENERATION and to
+    /// the highest value of the index type
+    pub fn set_generation_limit(&mut self, limit: ID) {
+        self.assert_not_frozen();
+        self.generation_limit = limit.min(MAX_GENERATION).min(Idx::MAX);
+        self.retire_exhausted(self.data.len());
+    }
+
+    /// Returns the highest generation a slot may reach
+    #[must_use]
+    pub fn generation_limit(&self) -> ID {
+        self.generation_limit
+    }
+
+    /// Enables or disables the monotonic mode. In monotonic mode erased IDs
+    /// are retired instead of being reused, so IDs only grow and each one
+    /// names a single object for the lifetime of the vector, at the cost of
+    /// keeping the bookkeeping of every erased object.
+    ///
+    /// @note The free slots are retired when the mode is enabled. `compact`
+    /// and `reset` still restart the ID space.
+    /// @param enabled Whether IDs may never be reused
+    pub fn set_monotonic(&mut self, enabled: bool) {
+        self.assert_not_frozen();
+        self.monotonic = enabled;
+        self.retire_exhausted(self.data.len());
+    }
+
+    /// Tells if erased IDs are retired instead of being reused
+    #[must_use]
+    pub fn is_monotonic(&self) -> bool {
+        self.monotonic
+    }
+
+    /// Sets the tick that will be recorded for the objects inserted from
+    /// now on. Typically called once per frame or simulation step.
+    ///
+    /// @param tick The current tick
+    pub fn set_tick(&mut self, tick: u64) {
+        self.tick = tick;
+    }
+
+    /// Returns the tick recorded for newly inserted objects
+    #[must_use]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the tick at which the object referenced by the handle was
+    /// inserted
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The insertion tick, or None if the handle is stale
+    #[must_use]
+    pub fn insertion_tick(&self, handle: &Handle<T>) -> Option<u64> {
+        self.resolve(handle)?;
+        Some(self.extras.insertion_tick(handle.id))
+    }
+
+    /// Returns the tick at which the object was inserted or, when
+    /// modification tracking is enabled, last accessed mutably
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The tick, or None if the handle is stale
+    #[must_use]
+    pub fn modified_tick(&self, handle: &Handle<T>) -> Option<u64> {
+        self.resolve(handle)?;
+        Some(self.extras.modified_tick(handle.id))
+    }
+
+    /// Returns an iterator over the objects inserted or, when modification
+    /// tracking is enabled, accessed mutably at @p tick or later, with their
+    /// handles, in data order
+    pub fn iter_changed_since(
+        &self,
+        tick: u64,
+    ) -> impl Iterator<Item = (Handle<T>, &T)> + '_ {
+        self.metadata
+            .iter()
+            .zip(self.data.iter())
+            .filter(move |(md, _)| {
+                self.extras.modified_tick(md.id()) >= tick
+            })
+            .map(|(md, object)| {
+                (Handle::new(md.id(), md.generation()), object)
+            })
+    }
+
+    /// Returns a handle to the object with the lowest insertion tick
+    #[must_use]
+    pub fn oldest(&self) -> Option<Handle<T>> {
+        let md = self.metadata[..self.data.len()]
+            .iter()
+            .min_by_key(|md| self.extras.insertion_tick(md.id()))?;
+        Some(Handle::new(md.id(), md.generation()))
     }
 
     /// Removes the object from the vector
     ///
     /// @param id The ID of the object to remove
     pub fn erase_by_id(&mut self, id: ID) {
-        let data_id = self.indices[id];
-        let last_data_id = self.data.len() - 1;
-        let last_id = self.metadata[last_data_id].reverse_id;
+        self.assert_not_frozen();
+        self.remove_slot(id);
+    }
 
-        self.metadata[data_id].validity_id += 1;
-        self.data.swap(data_id, last_data_id);
-        self.metadata.swap(data_id, last_data_id);
-        self.indices.swap(id, last_id);
-        self.data.pop();
+    /// Invalidates all the existing handles to an object while keeping the
+    /// object in place
+    ///
+    /// @param handle A handle referencing the object
+    /// @return A fresh handle to the object, or None if @p handle is stale
+    /// or the slot already reached the generation limit
+    pub fn invalidate(&mut self, handle: &Handle<T>) -> Option<Handle<T>> {
+        self.assert_not_frozen();
+        let data_index = self.resolve(handle)?;
+        if self.metadata[data_index].generation() >= self.generation_limit {
+            return None;
+        }
+        self.bump_generation(data_index);
+        self.create_handle(handle.id)
     }
 
     /// Removes the object from the vector
     ///
     /// @param index The index in the data vector of the object to remove
     pub fn erase_by_data(&mut self, index: usize) {
-        self.erase_by_id(self.metadata[index].reverse_id);
+        self.erase_by_id(self.metadata[index].id());
     }
 
     /// Removes the object referenced by the handle from the vector
@@ -57,6 +411,427 @@ impl<T> Vector<T> {
         self.erase_by_id(handle.get_id());
     }
 
+    /// Removes the object referenced by the handle and returns it
+    ///
+    /// @param handle The handle referencing the object to remove
+    /// @return The removed object, or None if the handle is stale
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.assert_not_frozen();
+        self.resolve(handle)?;
+        Some(self.remove_slot(handle.id))
+    }
+
+    /// Removes the object with the provided ID and returns it
+    ///
+    /// @param id The ID of the object to remove
+    /// @return The removed object, or None if no object has this ID
+    pub fn remove_by_id(&mut self, id: ID) -> Option<T> {
+        self.assert_not_frozen();
+        if id >= self.indices.len()
+            || self.indices[id].index() >= self.data.len()
+        {
+            return None;
+        }
+        Some(self.remove_slot(id))
+    }
+
+    /// Removes the object referenced by the handle, shifting the following
+    /// objects down instead of moving the last object in its place, so the
+    /// data order is preserved. Costs a shift of the following objects.
+    ///
+    /// @param handle The handle referencing the object to remove
+    /// @return The removed object, or None if the handle is stale
+    pub fn remove_ordered(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.assert_not_frozen();
+        self.resolve(handle)?;
+        Some(self.remove_slot_ordered(handle.id))
+    }
+
+    /// Removes the object with the provided ID, preserving the data order
+    /// like `remove_ordered`
+    ///
+    /// @param id The ID of the object to remove
+    /// @return The removed object, or None if no object has this ID
+    pub fn remove_ordered_by_id(&mut self, id: ID) -> Option<T> {
+        self.assert_not_frozen();
+        if id >= self.indices.len()
+            || self.indices[id].index() >= self.data.len()
+        {
+            return None;
+        }
+        Some(self.remove_slot_ordered(id))
+    }
+
+    /// Removes the object referenced by the handle if it is still alive
+    ///
+    /// @param handle The handle referencing the object to remove
+    /// @return True if an object was removed
+    pub fn try_erase(&mut self, handle: &Handle<T>) -> bool {
+        self.try_erase_by_id(handle.id, handle.generation())
+    }
+
+    /// Removes the object with the provided ID if its validity ID matches
+    ///
+    /// @param id The ID of the object to remove
+    /// @param validity_id The expected validity ID of the object
+    /// @return True if an object was removed
+    pub fn try_erase_by_id(&mut self, id: ID, validity_id: ID) -> bool {
+        self.assert_not_frozen();
+        if !self.is_live(id, validity_id) {
+            return false;
+        }
+        self.remove_slot(id);
+        true
+    }
+
+    /// Removes all the objects referenced by the handles. The objects are
+    /// removed from the highest data index down, so each removal only moves
+    /// an object that is kept.
+    ///
+    /// @param handles The handles referencing the objects to remove, stale
+    /// and duplicate handles are ignored
+    /// @return The number of removed objects
+    pub fn erase_many(&mut self, handles: &[Handle<T>]) -> usize {
+        self.assert_not_frozen();
+        let mut targets: Vec<usize> =
+            handles.iter().filter_map(|h| self.resolve(h)).collect();
+        targets.sort_unstable_by(|a, b| b.cmp(a));
+        targets.dedup();
+        for &data_index in &targets {
+            self.remove_slot(self.metadata[data_index].id());
+        }
+        targets.len()
+    }
+
+    /// Removes all the objects for which the predicate returns false. The
+    /// handles to the kept objects remain valid.
+    ///
+    /// @param keep Tells if an object should be kept
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.assert_not_frozen();
+        let mut data_index = 0;
+        while data_index < self.data.len() {
+            if keep(&self.data[data_index]) {
+                data_index += 1;
+            } else {
+                // The last object is moved here and checked next
+                self.remove_slot(self.metadata[data_index].id());
+            }
+        }
+    }
+
+    /// Removes all the objects for which the predicate returns false, the
+    /// predicate being allowed to update the objects. The handles to the
+    /// kept objects remain valid.
+    ///
+    /// @param keep Updates an object and tells if it should be kept
+    pub fn retain_mut<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.assert_not_frozen();
+        let mut data_index = 0;
+        while data_index < self.data.len() {
+            self.touch(data_index);
+            if keep(&mut self.data[data_index]) {
+                data_index += 1;
+            } else {
+                // The last object is moved here and checked next
+                self.remove_slot(self.metadata[data_index].id());
+            }
+        }
+    }
+
+    /// Removes the last object of the data vector, which moves no other
+    /// object
+    ///
+    /// @return The handle the object had and the object, or None if the
+    /// vector is empty
+    pub fn pop(&mut self) -> Option<(Handle<T>, T)> {
+        self.assert_not_frozen();
+        let md = self.metadata[..self.data.len()].last()?;
+        let handle = Handle::new(md.id(), md.generation());
+        Some((handle, self.remove_slot(handle.id)))
+    }
+
+    /// Removes the objects beyond the provided data index, invalidating
+    /// their handles. Objects are dropped from the end so no object is
+    /// moved.
+    ///
+    /// @param len The number of objects to keep
+    pub fn truncate(&mut self, len: usize) {
+        self.assert_not_frozen();
+        while self.data.len() > len {
+            let id = self.metadata[self.data.len() - 1].id();
+            self.remove_slot(id);
+        }
+    }
+
+    /// Moves the objects from data index @p at to the end into a new vector,
+    /// keeping their relative order. The moved objects get new IDs in the
+    /// new vector.
+    ///
+    /// @param at The data index of the first object to move
+    /// @return The new vector and the mapping from the old handles of the
+    /// moved objects to their handles in the new vector
+    pub fn split_off(&mut self, at: usize) -> (Self, IdRemap<T>) {
+        self.assert_not_frozen();
+        assert!(at <= self.data.len(), "Split index out of bounds");
+        let mut moved = Vec::with_capacity(self.data.len() - at);
+        // The objects are moved, not erased
+        for position in (at..self.data.len()).rev() {
+            self.record_event(EventOp::Move, position);
+        }
+        let hook = self.erase_hook.take();
+        let log_events = std::mem::replace(&mut self.log_events, false);
+        while self.data.len() > at {
+            moved.extend(self.pop());
+        }
+        self.erase_hook = hook;
+        self.log_events = log_events;
+
+        let mut other = Self::with_index_type_in(self.allocator.clone());
+        other.reserve(moved.len());
+        other.growth = self.growth;
+        other.reuse = self.reuse;
+        other.tick = self.tick;
+        other.generation_limit = self.generation_limit;
+        other.monotonic = self.monotonic;
+        let mut remap = IdRemap::default();
+        for (handle, object) in moved.into_iter().rev() {
+            let id = other.push(object);
+            remap.insert(handle, Handle::new(id, other.get_validity_id(id)));
+        }
+        (other, remap)
+    }
+
+    /// Moves all the objects of @p other into this vector, where they get
+    /// new IDs
+    ///
+    /// @param other The vector to empty into this one
+    /// @return The mapping from the handles of the objects in @p other to
+    /// their handles in this vector
+    pub fn append(&mut self, other: Self) -> IdRemap<T> {
+        self.assert_not_frozen();
+        let Self { data, metadata, .. } = other;
+        self.reserve_insertions(data.len());
+        let mut remap = IdRemap::default();
+        for (md, object) in metadata.iter().zip(data) {
+            let id = self.push(object);
+            remap.insert(
+                Handle::new(md.id(), md.generation()),
+                Handle::new(id, self.get_validity_id(id)),
+            );
+        }
+        remap
+    }
+
+    /// Rebuilds the ID space densely so the live object at data index @p i
+    /// gets the ID @p i, dropping all the free, taken and retired slots.
+    /// Every existing handle is invalidated.
+    ///
+    /// @note Once a slot reached the generation limit, the new generation
+    /// is the limit itself and old handles at the limit are not detected.
+    /// @return The mapping from the old handles of the live objects to
+    /// their new handles
+    pub fn compact(&mut self) -> IdRemap<T> {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        // A generation no old handle can have
+        let generation = self
+            .metadata
+            .iter()
+            .map(|md| md.generation().saturating_add(1))
+            .max()
+            .unwrap_or(0)
+            .min(self.generation_limit);
+        let len = self.data.len();
+        let old_ids: Vec<ID> =
+            self.metadata[..len].iter().map(Metadata::id).collect();
+        self.extras = self.extras.select(&old_ids);
+        let mut remap = IdRemap::default();
+        for (position, md) in self.metadata[..len].iter_mut().enumerate() {
+            remap.insert(
+                Handle::new(md.id(), md.generation()),
+                Handle::new(position, generation),
+            );
+            md.reverse_id = Idx::from_index(position);
+            md.validity_id = Idx::from_index(generation);
+        }
+        self.metadata.truncate(len);
+        self.indices.clear();
+        self.indices.extend((0..len).map(Idx::from_index));
+        self.retired = 0;
+        self.taken = 0;
+        // The IDs freed by the truncation are created again at this
+        // generation, so their old handles and reservations never match
+        self.generation_floor = self.generation_floor.max(generation);
+        for entry in &mut self.insertion_queue {
+            let new = remap.remap(&Handle::new(entry.0, entry.1));
+            *entry = new.map_or((ID::MAX, 0), |h| (h.id, h.generation()));
+        }
+        remap
+    }
+
+    /// Removes all the objects and returns them by value. The slots are kept
+    /// for reuse and all handles to the objects are invalidated, even if
+    /// the iterator is not consumed.
+    ///
+    /// @return The iterator over the removed objects, in data order
+    pub fn drain(&mut self) -> buffer!(Drain<'_, T, A>) {
+        self.assert_not_frozen();
+        self.notify_erase_all();
+        let len = self.data.len();
+        for position in 0..len {
+            self.bump_generation(position);
+        }
+        self.telemetry.record_erases(len);
+        self.heap_size = 0;
+        // Every slot is free once the data is drained, their order among
+        // themselves does not matter
+        self.retire_exhausted(0);
+        self.data.drain(..)
+    }
+
+    /// Moves the object referenced by the handle out of the vector while
+    /// keeping its ID reserved. The handles to the object stay valid but do
+    /// not resolve until the object is given back with `put_back`.
+    ///
+    /// @note Taken slots are persisted as free slots by `into_raw_parts` and
+    /// the serialization formats, and freed by `clear`, `compact` and
+    /// `reset`.
+    /// @param handle The handle referencing the object to take
+    /// @return The object, or None if the handle is stale
+    pub fn take(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.assert_not_frozen();
+        self.resolve(handle)?;
+        let object = self.detach_slot(handle.id);
+        self.swap_slots(self.data.len(), self.free_end() - 1);
+        self.taken += 1;
+        Some(object)
+    }
+
+    /// Gives back an object to the slot it was taken from, making the
+    /// handles to the slot resolve again. In bounded mode, if the vector is
+    /// full, the oldest object is removed first.
+    ///
+    /// @param id The ID of the taken slot
+    /// @param object The object to store
+    /// @return Err with @p object if the ID is not taken
+    pub fn put_back(&mut self, id: ID, object: T) -> Result<(), T> {
+        self.assert_not_frozen();
+        if !self.is_taken(id) {
+            return Err(object);
+        }
+        if let Some(max_len) = self.max_len
+            && self.data.len() >= max_len
+        {
+            self.evict_oldest();
+        }
+        self.untake(id);
+        self.swap_slots(self.indices[id].index(), self.data.len());
+        self.extras.set_modified_tick(id, self.tick);
+        self.add_heap_size(&object);
+        self.growth.reserve(&mut self.data, 1);
+        self.data.push(object);
+        self.telemetry.record_len(self.data.len());
+        if self.max_len.is_some() {
+            self.record_insertion(id);
+        }
+        Ok(())
+    }
+
+    /// Reserves a slot before its object exists. The slot is handled like
+    /// a taken slot until the reservation is fulfilled or cancelled.
+    ///
+    /// @return The reservation, holding the handle the object will have
+    pub fn reserve_handle(&mut self) -> ReservedHandle<T> {
+        self.assert_not_frozen();
+        let id = self.get_free_id();
+        self.extras.set_insertion_tick(id, self.tick);
+        self.extras.set_modification_count(id, 0);
+        self.extras.set_modified_tick(id, self.tick);
+        self.extras.set_group(id, 0);
+        let generation = self.metadata[self.data.len()].generation();
+        let handle = Handle::new(id, generation);
+        self.swap_slots(self.data.len(), self.free_end() - 1);
+        self.taken += 1;
+        ReservedHandle::new(handle)
+    }
+
+    /// Stores the object in a reserved slot. In bounded mode, if the vector
+    /// is full, the oldest object is removed first.
+    ///
+    /// @note `clear`, `compact` and `reset` cancel all the outstanding
+    /// reservations.
+    /// @param reserved The reservation to fulfill
+    /// @param object The object to store
+    /// @return The handle to the object, or Err with @p object if the
+    /// reservation was cancelled
+    pub fn fulfill(
+        &mut self,
+        reserved: ReservedHandle<T>,
+        object: T,
+    ) -> Result<Handle<T>, T> {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        let handle = reserved.handle();
+        if !self.is_reserved(&handle) {
+            return Err(object);
+        }
+        self.put_back(handle.id, object)?;
+        self.telemetry.record_inserts(1);
+        self.record_event(EventOp::Insert, self.indices[handle.id].index());
+        Ok(handle)
+    }
+
+    /// Releases a reserved slot without storing an object, the slot
+    /// becoming free
+    ///
+    /// @param reserved The reservation to cancel, ignored if it was already
+    /// cancelled
+    pub fn cancel_reservation(&mut self, reserved: ReservedHandle<T>) {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        let id = reserved.get_id();
+        if self.is_reserved(&reserved.handle()) {
+            self.bump_generation(self.indices[id].index());
+            self.untake(id);
+            self.retire_if_exhausted(id);
+        }
+    }
+
+    /// Reserves @p count new IDs through a shared reference, for
+    /// `IdReserver::reserve`
+    pub(crate) fn reserve_ids_atomic(
+        &self,
+        count: usize,
+    ) -> Vec<ReservedHandle<T>> {
+        let start = self.indices.len() + self.reserved_ids.reserve(count);
+        (start..start + count)
+            .map(|id| {
+                ReservedHandle::new(Handle::new(id, self.generation_floor))
+            })
+            .collect()
+    }
+
+    /// Tells if the slot of the handle is still taken at the generation of
+    /// the handle, so a reservation made for it was not cancelled
+    fn is_reserved(&self, handle: &Handle<T>) -> bool {
+        self.is_taken(handle.id)
+            && self.get_validity_id(handle.id) == handle.generation()
+    }
+
+    /// Tells if the object with the provided ID is currently taken
+    #[must_use]
+    pub fn is_taken(&self, id: ID) -> bool {
+        let taken = self.free_end()..self.retired_start();
+        id < self.indices.len() && taken.contains(&self.indices[id].index())
+    }
+
     /// Return the index in the data vector of the object referenced by the
     /// provided ID
     ///
@@ -64,7 +839,7 @@ impl<T> Vector<T> {
     /// @return The index in the data vector assoicated with the ID
     #[must_use]
     pub fn get_data_index(&self, id: ID) -> usize {
-        self.indices[id]
+        self.indices[id].index()
     }
 
     /// Return the number of objects in the vector
@@ -98,11 +873,16 @@ impl<T> Vector<T> {
         if data_index >= self.data.len() {
             return None;
         }
-        Some(Handle {
-            id,
-            validity_id: self.metadata[data_index].validity_id,
-            _marker: PhantomData,
-        })
+        Some(Handle::new(id, self.metadata[data_index].generation()))
+    }
+
+    /// Creates a packed handle pointing to the provided ID
+    ///
+    /// @param id The ID of the object
+    /// @return A packed handle to the object, or None if there is no object
+    /// or its ID or generation does not fit in 32 bits
+    pub fn create_packed_handle(&self, id: ID) -> Option<PackedHandle<T>> {
+        PackedHandle::pack(self.create_handle(id)?)
     }
 
     /// Creates a handle to an object using its position in the data vector
@@ -115,11 +895,10 @@ impl<T> Vector<T> {
         if index >= self.data.len() {
             return None;
         }
-        Some(Handle {
-            id: self.metadata[index].reverse_id,
-            validity_id: self.metadata[index].validity_id,
-            _marker: PhantomData,
-        })
+        Some(Handle::new(
+            self.metadata[index].id(),
+            self.metadata[index].generation(),
+        ))
     }
 
     /// Checks if the provided object is still valid considering its last
@@ -127,10 +906,23 @@ impl<T> Vector<T> {
     ///
     /// @param id The ID of the object
     /// @param validity_id The last known validity ID
-    /// @return True if the last knownvlidity ID is equal to the current one
+    /// @return True if the last knownvlidity ID is equal to the current one,
+    /// always false for INVALID_ID
     #[must_use]
     pub fn is_valid(&self, id: ID, validity_id: ID) -> bool {
-        validity_id == self.metadata[self.indices[id]].validity_id
+        if id == INVALID_ID {
+            return false;
+        }
+        validity_id == self.metadata[self.indices[id].index()].generation()
+    }
+
+    /// Removes all the stale handles from the provided list, keeping only
+    /// the ones still referencing a live object, i.e. the ones `get`
+    /// resolves. The relative order of the remaining handles is preserved.
+    ///
+    /// @param handles The list of handles to clean
+    pub fn revalidate(&self, handles: &mut Vec<Handle<T>>) {
+        handles.retain(|handle| self.resolve(handle).is_some());
     }
 
     /// Returns an iterator over immutable references to the elements.
@@ -140,20 +932,205 @@ impl<T> Vector<T> {
 
     /// Returns an iterator over mutable references to the elements.
     pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.assert_not_frozen();
+        self.touch_all();
         self.data.iter_mut()
     }
 
+    /// Consumes the vector into an iterator over the objects and the
+    /// handles referencing them, in data order
+    pub fn into_iter_with_handles(
+        self,
+    ) -> impl Iterator<Item = (Handle<T>, T)> {
+        self.metadata
+            .into_iter()
+            .map(|md| Handle::new(md.id(), md.generation()))
+            .zip(self.data)
+    }
+
+    /// Returns an iterator over the live objects and their IDs, in ascending
+    /// ID order. Unlike the data order, it does not depend on the history of
+    /// removals.
+    pub fn iter_in_id_order(&self) -> impl Iterator<Item = (ID, &T)> + '_ {
+        self.indices.iter().enumerate().filter_map(|(id, data_index)| {
+            self.data.get(data_index.index()).map(|object| (id, object))
+        })
+    }
+
+    /// Returns an iterator over the IDs of the live objects, in data order
+    pub fn ids(&self) -> impl Iterator<Item = ID> + '_ {
+        self.metadata[..self.data.len()].iter().map(|md| md.id())
+    }
+
+    /// Returns an iterator over handles to the live objects, in data order
+    pub fn handles(&self) -> impl Iterator<Item = Handle<T>> + '_ {
+        self.metadata[..self.data.len()]
+            .iter()
+            .map(|md| Handle::new(md.id(), md.generation()))
+    }
+
+    /// Returns an iterator over the objects and their handles, in data
+    /// order
+    pub fn iter_with_handles(
+        &self,
+    ) -> impl Iterator<Item = (Handle<T>, &T)> + '_ {
+        self.metadata.iter().zip(self.data.iter()).map(|(md, object)| {
+            (Handle::new(md.id(), md.generation()), object)
+        })
+    }
+
+    /// Returns an iterator over mutable references to the elements, each
+    /// paired with a handle referencing it.
+    pub fn iter_mut_with_handles(
+        &mut self,
+    ) -> impl Iterator<Item = (Handle<T>, &mut T)> + '_ {
+        self.assert_not_frozen();
+        self.touch_all();
+        self.metadata.iter().zip(self.data.iter_mut()).map(|(md, object)| {
+            (Handle::new(md.id(), md.generation()), object)
+        })
+    }
+
+    /// Moves the object referenced by the handle to another group
+    ///
+    /// @param handle The handle referencing the object
+    /// @param group The new group of the object
+    /// @return False if the handle is stale
+    pub fn set_group(&mut self, handle: &Handle<T>, group: Group) -> bool {
+        self.assert_not_frozen();
+        if self.resolve(handle).is_none() {
+            return false;
+        }
+        self.extras.set_group(handle.id, group);
+        true
+    }
+
+    /// Returns the group of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The group, or None if the handle is stale
+    #[must_use]
+    pub fn group_of(&self, handle: &Handle<T>) -> Option<Group> {
+        self.resolve(handle)?;
+        Some(self.extras.group(handle.id))
+    }
+
+    /// Splits the vector into disjoint mutable views, one per requested
+    /// group, so different groups can be mutated at the same time
+    ///
+    /// @param groups The groups to create views for
+    /// @return The views in the same order as @p groups, or None if a group
+    /// is requested more than once
+    pub fn split_groups_mut<const N: usize>(
+        &mut self,
+        groups: [Group; N],
+    ) -> Option<[GroupViewMut<'_, T>; N]> {
+        self.assert_not_frozen();
+        for (i, group) in groups.iter().enumerate() {
+            if groups[..i].contains(group) {
+                return None;
+            }
+        }
+        self.touch_all();
+        let mut views = groups.map(GroupViewMut::new);
+        for (md, object) in self.metadata.iter().zip(self.data.iter_mut()) {
+            let group = self.extras.group(md.id());
+            if let Some(slot) = groups.iter().position(|g| *g == group) {
+                let handle = Handle::new(md.id(), md.generation());
+                views[slot].push(handle, object);
+            }
+        }
+        views.iter_mut().for_each(GroupViewMut::sort_by_id);
+        Some(views)
+    }
+
     /// Pre allocates @p size slots in the vector
     /// @param size The number of slots to allocate in the vector
     pub fn reserve(&mut self, size: usize) {
+        self.assert_not_frozen();
         self.data.reserve(size);
         self.metadata.reserve(size);
         self.indices.reserve(size);
     }
 
+    /// Releases the excess capacity of the internal vectors
+    pub fn shrink_to_fit(&mut self) {
+        self.assert_not_frozen();
+        self.data.shrink_to_fit();
+        self.metadata.shrink_to_fit();
+        self.indices.shrink_to_fit();
+        self.extras.shrink_to(0);
+        self.insertion_queue.shrink_to_fit();
+    }
+
+    /// Releases the capacity of the internal vectors beyond @p capacity
+    /// slots. The slots in use are always kept.
+    ///
+    /// @param capacity The number of slots to keep room for
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.assert_not_frozen();
+        self.data.shrink_to(capacity);
+        self.metadata.shrink_to(capacity);
+        self.indices.shrink_to(capacity);
+        self.extras.shrink_to(capacity);
+        self.insertion_queue.shrink_to(capacity);
+    }
+
+    /// Makes sure @p additional objects can be inserted without allocating,
+    /// reporting allocation failures instead of aborting
+    ///
+    /// @param additional The number of objects to make room for
+    /// @return The error if one of the internal vectors could not grow, in
+    /// which case the objects are left untouched
+    pub fn try_reserve(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        self.assert_not_frozen();
+        let free = self.free_end() - self.data.len();
+        let new_slots = additional.saturating_sub(free);
+        self.growth.try_reserve(&mut self.data, additional)?;
+        self.growth.try_reserve(&mut self.metadata, new_slots)?;
+        self.growth.try_reserve(&mut self.indices, new_slots)?;
+        if self.max_len.is_some() {
+            let queued = self.insertion_queue.len();
+            self.insertion_queue.try_reserve(additional).map_err(|error| {
+                let required = queued.saturating_add(additional);
+                convert_error(error, Layout::array::<(ID, ID)>(required))
+            })?;
+        }
+        Ok(())
+    }
+
     /// Return the validity ID associated with the provided ID
     pub fn get_validity_id(&self, id: ID) -> ID {
-        self.metadata[self.indices[id]].validity_id
+        self.metadata[self.indices[id].index()].generation()
+    }
+
+    /// Returns the current generation of the slot associated with the ID
+    ///
+    /// @param id The ID of the slot
+    /// @return The generation, or None if the ID was never allocated
+    #[must_use]
+    pub fn slot_generation(&self, id: ID) -> Option<ID> {
+        if id >= self.indices.len() {
+            return None;
+        }
+        Some(self.get_validity_id(id))
+    }
+
+    /// Returns how many times the slot associated with the ID has been
+    /// recycled for a new object
+    ///
+    /// @note The reuses are only counted while reuse tracking is enabled.
+    /// @param id The ID of the slot
+    /// @return The reuse count, or None if the ID was never allocated
+    #[must_use]
+    pub fn slot_reuse_count(&self, id: ID) -> Option<usize> {
+        if id >= self.indices.len() {
+            return None;
+        }
+        Some(self.extras.reuse_count(id))
     }
 
     /// Returns an optional to the first element of the data vector
@@ -161,37 +1138,193 @@ impl<T> Vector<T> {
         self.data.first()
     }
 
+    /// Returns the first object of the data vector and its handle
+    #[must_use]
+    pub fn first_handle(&self) -> Option<(Handle<T>, &T)> {
+        Some((self.create_handle_from_data(0)?, self.data.first()?))
+    }
+
+    /// Returns the last object of the data vector and its handle
+    #[must_use]
+    pub fn last_handle(&self) -> Option<(Handle<T>, &T)> {
+        let index = self.data.len().checked_sub(1)?;
+        Some((self.create_handle_from_data(index)?, &self.data[index]))
+    }
+
     /// Consumes the Vector to return the underlying data
-    pub fn data(self) -> Vec<T> {
+    pub fn data(self) -> buffer!(T, A) {
         self.data
     }
 
     /// Returns a constant reference to the data vector
-    pub fn get_data(&self) -> &Vec<T> {
+    pub fn get_data(&self) -> &buffer!(T, A) {
         &self.data
     }
 
+    /// Returns the objects as a slice, in data order
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the objects as a mutable slice, in data order. Reordering
+    /// the objects does not move their handles along.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.assert_not_frozen();
+        self.touch_all();
+        &mut self.data
+    }
+
     /// Return a reference to the data vector
-    pub fn get_data_mut(&mut self) -> &mut Vec<T> {
+    pub fn get_data_mut(&mut self) -> &mut buffer!(T, A) {
+        self.assert_not_frozen();
+        self.touch_all();
         &mut self.data
     }
 
+    /// Replaces the object referenced by the handle, keeping its ID and
+    /// validity ID
+    ///
+    /// @param handle The handle referencing the object
+    /// @param object The new object
+    /// @return The previous object, or @p object back if the handle is stale
+    pub fn replace(&mut self, handle: &Handle<T>, object: T) -> Result<T, T> {
+        self.assert_not_frozen();
+        let Some(data_index) = self.resolve(handle) else {
+            return Err(object);
+        };
+        self.touch(data_index);
+        self.add_heap_size(&object);
+        let previous = std::mem::replace(&mut self.data[data_index], object);
+        self.sub_heap_size(&previous);
+        Ok(previous)
+    }
+
+    /// Applies the closure to the object referenced by the handle, if the
+    /// handle is still valid
+    ///
+    /// @param handle The handle referencing the object
+    /// @param update Modifies the object
+    /// @return True if the closure ran
+    pub fn update<F>(&mut self, handle: &Handle<T>, update: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        self.assert_not_frozen();
+        let Some(data_index) = self.resolve(handle) else {
+            return false;
+        };
+        self.touch(data_index);
+        let object = &mut self.data[data_index];
+        if let Some(SizeHook(size_of)) = self.size_hook {
+            self.heap_size = self.heap_size.saturating_sub(size_of(object));
+            update(object);
+            self.heap_size += size_of(object);
+        } else {
+            update(object);
+        }
+        true
+    }
+
     /// Returns the ID that would be used if an object was added
     #[must_use]
     pub fn get_next_id(&self) -> ID {
-        if self.metadata.len() > self.data.len() {
-            return self.metadata[self.data.len()].reverse_id;
+        match self.next_free_position() {
+            Some(position) => self.metadata[position].id(),
+            None => self.indices.len() + self.reserved_ids.pending(),
         }
-        self.data.len()
     }
 
-    /// Erase all objects and invalidates all slots
-    pub fn clear(&mut self) {
+    /// Permanently bans the ID from being reused. If the ID references a
+    /// live object, the object is erased first.
+    ///
+    /// @param id The ID to retire
+    /// @return False if the ID was never allocated or is already retired
+    pub fn retire_id(&mut self, id: ID) -> bool {
+        self.assert_not_frozen();
+        if id >= self.indices.len() || self.is_retired(id) {
+            return false;
+        }
+        if self.indices[id].index() < self.data.len() {
+            self.erase_by_id(id);
+            // The slot may have reached the generation limit
+            if self.is_retired(id) {
+                return true;
+            }
+        } else if self.is_taken(id) {
+            self.bump_generation(self.indices[id].index());
+            self.untake(id);
+        }
+        self.retire_free_position(self.indices[id].index());
+        true
+    }
+
+    /// Tells if the ID has been retired
+    #[must_use]
+    pub fn is_retired(&self, id: ID) -> bool {
+        id < self.indices.len()
+            && self.indices[id].index() >= self.retired_start()
+    }
+
+    /// Returns the number of retired IDs
+    #[must_use]
+    pub fn retired_count(&self) -> usize {
+        self.retired
+    }
+
+    /// Returns the generation of the slots created from now on
+    pub(crate) fn generation_floor(&self) -> ID {
+        self.generation_floor
+    }
+
+    /// Erase all objects and invalidates all slots. The taken slots are
+    /// freed too: their objects can not be put back and the outstanding
+    /// reservations are cancelled.
+    pub fn clear(&mut self) {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        self.notify_erase_all();
+        self.telemetry.record_erases(self.data.len());
         self.data.clear();
+        self.heap_size = 0;
 
-        for md in &mut self.metadata {
-            md.validity_id += 1;
+        for position in 0..self.metadata.len() {
+            self.bump_generation(position);
         }
+        // The taken slots sit right after the free ones
+        self.taken = 0;
+        self.retire_exhausted(0);
+    }
+
+    /// Erases all objects and releases the memory of all slots, including
+    /// free and retired ones, so IDs are handed out from 0 again. Every
+    /// handle is invalidated: the new slots start at a generation above
+    /// all the previous ones.
+    ///
+    /// @note The generations are only read, once, to compute the new
+    /// starting generation, which `into_raw_parts` and the serialization
+    /// formats persist.
+    pub fn reset(&mut self) {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        self.notify_erase_all();
+        let floor = self
+            .metadata
+            .iter()
+            .map(|md| md.generation().saturating_add(1))
+            .max()
+            .unwrap_or(0)
+            .max(self.generation_floor);
+        self.telemetry.record_erases(self.data.len());
+        self.data = new_buffer(&self.allocator);
+        self.metadata = new_buffer(&self.allocator);
+        self.indices = new_buffer(&self.allocator);
+        self.extras = SlotExtras::default();
+        self.insertion_queue = VecDeque::new();
+        self.retired = 0;
+        self.taken = 0;
+        self.heap_size = 0;
+        self.generation_floor = floor.min(self.generation_limit);
     }
 
     #[must_use]
@@ -199,28 +1332,797 @@ impl<T> Vector<T> {
         id < self.indices.len()
     }
 
+    /// Tells if the handle references a live object
+    #[must_use]
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    /// Tells if the ID references a live object, whatever its generation
+    #[must_use]
+    pub fn contains_id(&self, id: ID) -> bool {
+        id < self.indices.len() && self.indices[id].index() < self.data.len()
+    }
+
     pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
-        if handle.id >= self.indices.len() {
+        let Some(data_index) = self.resolve(handle) else {
+            self.telemetry.record_stale_access();
             return None;
-        }
-        let data_index = self.indices[handle.id];
-        let current_validity = self.metadata[data_index].validity_id;
-        if handle.validity_id != current_validity {
-            return None;
-        }
+        };
         Some(&self.data[data_index])
     }
     
     pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
-        if handle.id >= self.indices.len() {
+        self.assert_not_frozen();
+        let Some(data_index) = self.resolve(handle) else {
+            self.telemetry.record_stale_access();
+            return None;
+        };
+        self.touch(data_index);
+        Some(&mut self.data[data_index])
+    }
+
+    /// Returns a reference to the object referenced by the handle without
+    /// checking the bounds nor the generation
+    ///
+    /// # Safety
+    ///
+    /// The handle must reference a live object of this vector, for example
+    /// a handle for which `contains` returned true with no removal since.
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object
+    #[must_use]
+    pub unsafe fn get_unchecked(&self, handle: &Handle<T>) -> &T {
+        // SAFETY: the caller guarantees the ID and its data index are valid
+        unsafe {
+            let data_index = self.indices.get_unchecked(handle.id).index();
+            self.data.get_unchecked(data_index)
+        }
+    }
+
+    /// Returns a mutable reference to the object referenced by the handle
+    /// without checking the bounds nor the generation
+    ///
+    /// # Safety
+    ///
+    /// The handle must reference a live object of this vector, for example
+    /// a handle for which `contains` returned true with no removal since.
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The object
+    pub unsafe fn get_unchecked_mut(&mut self, handle: &Handle<T>) -> &mut T {
+        self.assert_not_frozen();
+        // SAFETY: the caller guarantees the ID and its data index are valid
+        unsafe {
+            let data_index = self.indices.get_unchecked(handle.id).index();
+            self.touch(data_index);
+            self.data.get_unchecked_mut(data_index)
+        }
+    }
+
+    /// Returns mutable references to several distinct objects at once
+    ///
+    /// @param handles The handles referencing the objects
+    /// @return The objects in the order of the handles, or None if a handle
+    /// is stale or two handles reference the same object
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        handles: [&Handle<T>; N],
+    ) -> Option<[&mut T; N]> {
+        self.assert_not_frozen();
+        let mut indices = [0; N];
+        for (data_index, handle) in indices.iter_mut().zip(handles) {
+            *data_index = self.resolve(handle)?;
+        }
+        for (i, data_index) in indices.iter().enumerate() {
+            if indices[..i].contains(data_index) {
+                return None;
+            }
+        }
+        for &data_index in &indices {
+            self.touch(data_index);
+        }
+        self.data.get_disjoint_mut(indices).ok()
+    }
+
+    /// Returns mutable references to any number of distinct objects at once
+    ///
+    /// @param handles The handles referencing the objects
+    /// @return The objects in the order of the handles, or None if a handle
+    /// is stale or two handles reference the same object
+    pub fn get_disjoint_mut_slice(
+        &mut self,
+        handles: &[Handle<T>],
+    ) -> Option<Vec<&mut T>> {
+        self.assert_not_frozen();
+        let mut targets = handles
+            .iter()
+            .enumerate()
+            .map(|(position, handle)| {
+                Some((self.resolve(handle)?, position))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        targets.sort_unstable();
+        if targets.windows(2).any(|pair| pair[0].0 == pair[1].0) {
             return None;
         }
-        let data_index = self.indices[handle.id];
-        let current_validity = self.metadata[data_index].validity_id;
-        if handle.validity_id != current_validity {
+        for &(data_index, _) in &targets {
+            self.touch(data_index);
+        }
+
+        let mut objects: Vec<Option<&mut T>> =
+            std::iter::repeat_with(|| None).take(handles.len()).collect();
+        let mut targets = targets.into_iter().peekable();
+        for (data_index, object) in self.data.iter_mut().enumerate() {
+            if let Some(&(target, position)) = targets.peek()
+                && target == data_index
+            {
+                objects[position] = Some(object);
+                targets.next();
+            }
+        }
+        objects.into_iter().collect()
+    }
+
+    /// Returns the data index of the object referenced by the handle
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The data index, or None if the handle is stale
+    pub(crate) fn resolve(&self, handle: &Handle<T>) -> Option<usize> {
+        let data_index = self.indices.get(handle.id)?.index();
+        if data_index >= self.data.len()
+            || self.metadata[data_index].generation() != handle.generation()
+        {
             return None;
         }
-        Some(&mut self.data[data_index])
+        Some(data_index)
+    }
+
+    /// Returns the occupancy of the ID space, to monitor its growth and
+    /// decide when to compact
+    #[must_use]
+    pub fn stats(&self) -> VectorStats {
+        // Summed wide so high generations can not overflow
+        let generations: u128 =
+            self.metadata.iter().map(|md| md.generation() as u128).sum();
+        let slots = self.metadata.len();
+        VectorStats {
+            live: self.data.len(),
+            slots,
+            free: self.free_end() - self.data.len(),
+            taken: self.taken,
+            retired: self.retired,
+            high_water: self.telemetry.peak_len(),
+            average_generation: if slots == 0 {
+                0.0
+            } else {
+                generations as f64 / slots as f64
+            },
+        }
+    }
+
+    /// Returns the activity counters of the vector
+    #[must_use]
+    pub fn telemetry(&self) -> TelemetrySnapshot {
+        self.telemetry.snapshot(self.data.len(), self.data.capacity())
+    }
+
+    /// Stops counting the accesses with stale handles, for vectors read by
+    /// many threads at once
+    #[cfg(feature = "epoch")]
+    pub(crate) fn ignore_stale_accesses(&mut self) {
+        self.telemetry.ignore_stale_accesses();
+    }
+
+    /// Publishes the activity counters to the `metrics` facade as gauges
+    /// and counters named after @p prefix
+    ///
+    /// @param prefix The prefix of the metric names
+    #[cfg(feature = "metrics")]
+    pub fn publish_metrics(&self, prefix: &str) {
+        self.telemetry().publish(prefix);
+    }
+
+    /// Hashes the live objects with their IDs in ascending ID order, so the
+    /// result only depends on the logical content and not on the order of
+    /// the data vector nor on the generations of the slots.
+    ///
+    /// @note This is the hash fed to @p H by the Hash implementation.
+    /// @return The hash computed by a default @p H hasher
+    #[must_use]
+    pub fn content_hash<H: Hasher + Default>(&self) -> u64
+    where
+        T: Hash,
+    {
+        let mut state = H::default();
+        self.hash(&mut state);
+        state.finish()
+    }
+
+    /// Assigns a new random identity to the vector, if it does not have one
+    /// yet, so the handles it stamps can not be used with other vectors
+    ///
+    /// @note Clones of the vector get a new identity of their own.
+    /// @return The identity of the vector
+    pub fn assign_identity(&mut self) -> VectorId {
+        *self.identity.get_or_insert_with(generate_vector_id)
+    }
+
+    /// Returns the identity of the vector, if it was assigned one
+    #[must_use]
+    pub fn identity(&self) -> Option<VectorId> {
+        self.identity
+    }
+
+    /// Stamps the handle with the identity of the vector
+    ///
+    /// @param handle The handle to stamp
+    /// @return The stamped handle, ready to be serialized
+    #[must_use]
+    pub fn stamp(&self, handle: Handle<T>) -> StampedHandle<T> {
+        StampedHandle::new(handle, self.identity)
+    }
+
+    /// Checks that the stamped handle was issued by this vector and is still
+    /// valid
+    ///
+    /// @param stamped The stamped handle, typically deserialized
+    /// @return The handle, or None if it belongs to another vector or is
+    /// stale
+    #[must_use]
+    pub fn resolve_stamped(
+        &self,
+        stamped: &StampedHandle<T>,
+    ) -> Option<Handle<T>> {
+        if stamped.vector_id != self.identity {
+            return None;
+        }
+        self.resolve(&stamped.handle)?;
+        Some(stamped.handle)
+    }
+
+    /// Sets the policy used to grow the internal vectors when they are full
+    ///
+    /// @param growth The growth policy to apply to all internal vectors
+    pub fn set_growth_strategy(&mut self, growth: GrowthStrategy) {
+        self.growth = growth;
+    }
+
+    /// Returns the policy used to grow the internal vectors
+    #[must_use]
+    pub fn growth_strategy(&self) -> GrowthStrategy {
+        self.growth
+    }
+
+    /// Sets the policy choosing which free slot is reused by insertions
+    pub fn set_reuse_policy(&mut self, reuse: ReusePolicy) {
+        self.reuse = reuse;
+    }
+
+    /// Returns the policy choosing which free slot is reused by insertions
+    #[must_use]
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.reuse
+    }
+
+    /// Enables or disables the per-object modification counters. When
+    /// enabled, every mutable access bumps the counter of the accessed
+    /// objects, allowing caches to detect that the content changed.
+    ///
+    /// @param enabled True to maintain the counters
+    pub fn set_track_modifications(&mut self, enabled: bool) {
+        self.track_modifications = enabled;
+    }
+
+    /// Tells if the per-object modification counters are maintained
+    #[must_use]
+    pub fn tracks_modifications(&self) -> bool {
+        self.track_modifications
+    }
+
+    /// Enables or disables the counting of the reuses of each slot,
+    /// returned by `slot_reuse_count`. The counts are only allocated once a
+    /// slot is reused while enabled.
+    ///
+    /// @param enabled True to count the reuses
+    pub fn set_track_reuses(&mut self, enabled: bool) {
+        self.track_reuses = enabled;
+    }
+
+    /// Tells if the reuses of each slot are counted
+    #[must_use]
+    pub fn tracks_reuses(&self) -> bool {
+        self.track_reuses
+    }
+
+    /// Returns the number of mutable accesses to the object referenced by
+    /// the handle since its insertion
+    ///
+    /// @param handle The handle referencing the object
+    /// @return The modification count, or None if the handle is stale
+    #[must_use]
+    pub fn modification_count(&self, handle: &Handle<T>) -> Option<usize> {
+        self.resolve(handle)?;
+        Some(self.extras.modification_count(handle.id))
+    }
+
+    /// Starts maintaining a running total of the heap memory owned by the
+    /// objects, updated on insertion, removal and replacement.
+    ///
+    /// @note Mutations through get_mut, indexing or the data vector are not
+    /// tracked: call this again to recompute the total after them.
+    pub fn track_heap_size(&mut self)
+    where
+        T: HeapSize,
+    {
+        self.size_hook = Some(SizeHook::of());
+        self.heap_size = self.data.iter().map(HeapSize::heap_size).sum();
+    }
+
+    /// Stops maintaining the heap memory total
+    pub fn untrack_heap_size(&mut self) {
+        self.size_hook = None;
+        self.heap_size = 0;
+    }
+
+    /// Sets the function called with each object and its handle right
+    /// before the object is removed, whether it is erased, returned, evicted
+    /// or cleared, e.g. to release the resources it refers to.
+    ///
+    /// @note The hook is not called when the vector is dropped, nor when
+    /// objects are moved to another vector by `split_off`.
+    /// @param hook The function to call, or None to remove it
+    pub fn set_erase_hook(&mut self, hook: Option<fn(Handle<T>, &mut T)>) {
+        self.erase_hook = hook.map(EraseHook);
+    }
+
+    /// Sets the function called when a removal moves an object to another
+    /// data index, with the handle of the object, its old index and its new
+    /// index, so caches indexed by data index can be patched incrementally
+    ///
+    /// @param hook The function to call, or None to remove it
+    pub fn set_relocate_hook(
+        &mut self,
+        hook: Option<fn(Handle<T>, usize, usize)>,
+    ) {
+        self.relocate_hook = hook.map(RelocateHook);
+    }
+
+    /// Enables or disables the event log. When enabled, every insert and
+    /// erase is recorded with the handle of the object and the current tick
+    /// until the events are drained.
+    ///
+    /// @note Fulfilling a reservation is recorded as an insert, and the
+    /// objects moved out by `split_off` as moves. Taking and putting back an
+    /// object is not recorded, nor are the handle changes made by `compact`.
+    /// Disabling the log discards the pending events.
+    /// @param enabled True to record the events
+    pub fn set_event_log(&mut self, enabled: bool) {
+        self.log_events = enabled;
+        if !enabled {
+            self.events = Vec::new();
+        }
+    }
+
+    /// Tells if inserts and erases are recorded in the event log
+    #[must_use]
+    pub fn logs_events(&self) -> bool {
+        self.log_events
+    }
+
+    /// Returns the events recorded since the last drain, oldest first
+    #[must_use]
+    pub fn events(&self) -> &[VectorEvent<T>] {
+        &self.events
+    }
+
+    /// Removes the events recorded since the last drain and returns them
+    ///
+    /// @return The iterator over the events, oldest first
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, VectorEvent<T>> {
+        self.events.drain(..)
+    }
+
+    /// Returns the deep memory used by the objects: their inline size plus
+    /// the heap memory they own
+    ///
+    /// @return The number of bytes, or None if the heap memory is not
+    /// tracked
+    #[must_use]
+    pub fn deep_size(&self) -> Option<usize> {
+        self.size_hook?;
+        Some(self.data.len() * std::mem::size_of::<T>() + self.heap_size)
+    }
+
+    /// Returns the bytes allocated by the internal vectors, plus the heap
+    /// memory owned by the objects when it is tracked
+    #[must_use]
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            data: self.data.capacity() * size_of::<T>(),
+            metadata: self.metadata.capacity() * size_of::<Metadata<Idx>>(),
+            indices: self.indices.capacity() * size_of::<Idx>(),
+            extras: self.extras.allocated_bytes(),
+            insertion_queue: self.insertion_queue.capacity()
+                * size_of::<(ID, ID)>(),
+            heap: self.size_hook.map(|_| self.heap_size),
+        }
+    }
+
+    /// Puts the vector in read-only mode. Any mutating operation will panic
+    /// until `unfreeze` is called.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Leaves the read-only mode entered with `freeze`
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    /// Tells if the vector is currently in read-only mode
+    #[must_use]
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Panics if the vector is frozen, called by all mutating operations
+    pub(crate) fn assert_not_frozen(&self) {
+        assert!(!self.frozen, "Attempted to mutate a frozen Vector");
+    }
+
+    /// Bumps the modification counter of the object at @p data_index
+    pub(crate) fn touch(&mut self, data_index: usize) {
+        if self.track_modifications {
+            let id = self.metadata[data_index].id();
+            self.extras.add_modification(id);
+            self.extras.set_modified_tick(id, self.tick);
+        }
+    }
+
+    /// Bumps the modification counter of all the objects, used when the
+    /// whole data vector is lent mutably
+    fn touch_all(&mut self) {
+        if self.track_modifications {
+            let len = self.data.len();
+            let tick = self.tick;
+            for md in &self.metadata[..len] {
+                self.extras.add_modification(md.id());
+                self.extras.set_modified_tick(md.id(), tick);
+            }
+        }
+    }
+
+    /// Adds the heap size of the object to the running total
+    fn add_heap_size(&mut self, object: &T) {
+        if let Some(SizeHook(size_of)) = self.size_hook {
+            self.heap_size += size_of(object);
+        }
+    }
+
+    /// Removes the heap size of the object from the running total
+    fn sub_heap_size(&mut self, object: &T) {
+        if let Some(SizeHook(size_of)) = self.size_hook {
+            self.heap_size = self.heap_size.saturating_sub(size_of(object));
+        }
+    }
+
+    /// Reserves the room for @p count insertions, the free slots being
+    /// reused before new slots are created
+    fn reserve_insertions(&mut self, count: usize) {
+        let new_slots = count.saturating_sub(self.free_end() - self.data.len());
+        self.growth.reserve(&mut self.data, count);
+        self.growth.reserve(&mut self.metadata, new_slots);
+        self.growth.reserve(&mut self.indices, new_slots);
+    }
+
+    /// Stores the object in the free or taken slot of the provided ID
+    pub(crate) fn fill_slot(&mut self, id: ID, object: T) {
+        if self.is_taken(id) {
+            let _ = self.put_back(id, object);
+            // Observed like the other inserts, as in fulfill
+            self.telemetry.record_inserts(1);
+            self.record_event(EventOp::Insert, self.indices[id].index());
+            return;
+        }
+        if let Some(max_len) = self.max_len
+            && self.data.len() >= max_len
+        {
+            self.evict_oldest();
+        }
+        // Make the slot the first free one so it is the one reused
+        self.swap_slots(self.indices[id].index(), self.data.len());
+        self.reuse_first_free();
+        self.store(object);
+        if self.max_len.is_some() {
+            self.record_insertion(id);
+        }
+    }
+
+    /// Stores the object in the free slot of the handle, which takes the
+    /// generation of the handle, creating the slots up to its ID if needed.
+    /// In bounded mode, if the vector is full, the oldest object is removed
+    /// first.
+    ///
+    /// @return Err with @p object if the slot is live, taken or retired, or
+    /// if its generation is above the one of the handle
+    pub(crate) fn insert_at(
+        &mut self,
+        handle: &Handle<T>,
+        object: T,
+    ) -> Result<(), T> {
+        self.assert_not_frozen();
+        let id = handle.id;
+        if id == INVALID_ID || handle.generation() > self.generation_limit {
+            return Err(object);
+        }
+        let fresh = id >= self.indices.len();
+        while self.indices.len() <= id {
+            self.new_slot();
+        }
+        let position = self.indices[id].index();
+        if !(self.data.len()..self.free_end()).contains(&position)
+            || self.metadata[position].generation() > handle.generation()
+        {
+            return Err(object);
+        }
+        if let Some(max_len) = self.max_len
+            && self.data.len() >= max_len
+        {
+            self.evict_oldest();
+        }
+        self.swap_slots(self.indices[id].index(), self.data.len());
+        let md = &mut self.metadata[self.data.len()];
+        md.validity_id = Idx::from_index(handle.generation());
+        if !fresh && self.track_reuses {
+            self.extras.add_reuse(id);
+        }
+        self.store(object);
+        if self.max_len.is_some() {
+            self.record_insertion(id);
+        }
+        Ok(())
+    }
+
+    /// Creates the object from the ID it will be stored at and inserts it,
+    /// evicting the oldest object first in bounded mode
+    fn push_with_evicting<F>(&mut self, make: F) -> (ID, Option<T>)
+    where
+        F: FnOnce(ID) -> T,
+    {
+        self.assert_not_frozen();
+        let evicted = match self.max_len {
+            Some(max_len) if self.data.len() >= max_len => self.evict_oldest(),
+            _ => None,
+        };
+        let object = make(self.get_next_id());
+        let id = self.insert(object);
+        if self.max_len.is_some() {
+            self.record_insertion(id);
+        }
+        (id, evicted)
+    }
+
+    /// Inserts the object in a free slot
+    fn insert(&mut self, object: T) -> ID {
+        let id = self.get_free_slot();
+        self.store(object);
+        id
+    }
+
+    /// Stores the object in the first free slot, which must be prepared
+    fn store(&mut self, object: T) {
+        self.add_heap_size(&object);
+        self.telemetry.record_inserts(1);
+        self.growth.reserve(&mut self.data, 1);
+        let id = self.metadata[self.data.len()].id();
+        self.extras.set_insertion_tick(id, self.tick);
+        self.extras.set_modification_count(id, 0);
+        self.extras.set_modified_tick(id, self.tick);
+        self.extras.set_group(id, 0);
+        self.record_event(EventOp::Insert, self.data.len());
+        self.data.push(object);
+        self.telemetry.record_len(self.data.len());
+    }
+
+    /// Removes the object with the provided ID, moving the last object in
+    /// its place, and returns it
+    pub(crate) fn remove_slot(&mut self, id: ID) -> T {
+        self.notify_erase(id);
+        self.bump_generation(self.indices[id].index());
+        let object = self.detach_slot(id);
+        self.retire_if_exhausted(id);
+        self.telemetry.record_erases(1);
+        object
+    }
+
+    /// Removes the object with the provided ID, shifting the following
+    /// objects down, and returns it
+    fn remove_slot_ordered(&mut self, id: ID) -> T {
+        self.notify_erase(id);
+        self.bump_generation(self.indices[id].index());
+        let data_id = self.indices[id].index();
+        let len = self.data.len();
+        // The slot ends up as the first free slot
+        self.metadata[data_id..len].rotate_left(1);
+        for position in data_id..len {
+            let id = self.metadata[position].id();
+            self.indices[id] = Idx::from_index(position);
+        }
+        for position in data_id..len - 1 {
+            self.notify_relocate(position + 1, position);
+        }
+        let object = self.data.remove(data_id);
+        self.sub_heap_size(&object);
+        self.retire_if_exhausted(id);
+        self.telemetry.record_erases(1);
+        object
+    }
+
+    /// Calls the erase hook with the live object of the provided ID
+    fn notify_erase(&mut self, id: ID) {
+        self.record_event(EventOp::Erase, self.indices[id].index());
+        if let Some(EraseHook(hook)) = self.erase_hook {
+            let md = &self.metadata[self.indices[id].index()];
+            let handle = Handle::new(id, md.generation());
+            hook(handle, &mut self.data[self.indices[id].index()]);
+        }
+    }
+
+    /// Calls the relocate hook for the object now at data index @p to
+    fn notify_relocate(&self, from: usize, to: usize) {
+        if let Some(RelocateHook(hook)) = self.relocate_hook {
+            let md = &self.metadata[to];
+            hook(Handle::new(md.id(), md.generation()), from, to);
+        }
+    }
+
+    /// Calls the erase hook with every live object, in data order
+    fn notify_erase_all(&mut self) {
+        for position in 0..self.data.len() {
+            self.record_event(EventOp::Erase, position);
+        }
+        if let Some(EraseHook(hook)) = self.erase_hook {
+            let live = self.metadata.iter().zip(self.data.iter_mut());
+            for (md, object) in live {
+                hook(Handle::new(md.id(), md.generation()), object);
+            }
+        }
+    }
+
+    /// Records an event for the slot at the metadata position if the event
+    /// log is enabled
+    fn record_event(&mut self, op: EventOp, position: usize) {
+        if self.log_events {
+            let md = &self.metadata[position];
+            let handle = Handle::new(md.id(), md.generation());
+            self.events.push(VectorEvent::new(op, handle, self.tick));
+        }
+    }
+
+    /// Increments the generation of the slot at the metadata position,
+    /// saturating at the generation limit
+    fn bump_generation(&mut self, position: usize) {
+        let md = &mut self.metadata[position];
+        md.validity_id = Idx::from_index(
+            md.generation().saturating_add(1).min(self.generation_limit),
+        );
+    }
+
+    /// Tells if the slot at the metadata position can not be reused, because
+    /// it reached the generation limit or the vector is monotonic
+    fn is_exhausted(&self, position: usize) -> bool {
+        self.monotonic
+            || self.metadata[position].generation() >= self.generation_limit
+    }
+
+    /// Retires the free slot with the provided ID if it can not be reused
+    fn retire_if_exhausted(&mut self, id: ID) {
+        let position = self.indices[id].index();
+        if position >= self.data.len()
+            && position < self.free_end()
+            && self.is_exhausted(position)
+        {
+            self.retire_free_position(position);
+        }
+    }
+
+    /// Retires every slot from the metadata position @p start to the end of
+    /// the free region that can not be reused
+    fn retire_exhausted(&mut self, start: usize) {
+        let mut position = start;
+        while position < self.free_end() {
+            if self.is_exhausted(position) {
+                self.retire_free_position(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+
+    /// Moves the free slot at the metadata position to the retired region,
+    /// past the taken slots
+    fn retire_free_position(&mut self, position: usize) {
+        let last_free = self.free_end() - 1;
+        self.swap_slots(position, last_free);
+        if self.taken > 0 {
+            self.swap_slots(last_free, self.retired_start() - 1);
+        }
+        self.retired += 1;
+    }
+
+    /// Moves the object with the provided ID out of the data vector, moving
+    /// the last object in its place. The slot becomes the first free slot
+    /// and keeps its validity ID.
+    fn detach_slot(&mut self, id: ID) -> T {
+        let data_id = self.indices[id].index();
+        let last_data_id = self.data.len() - 1;
+        let last_id = self.metadata[last_data_id].id();
+
+        self.metadata.swap(data_id, last_data_id);
+        self.indices.swap(id, last_id);
+        let object = self.data.swap_remove(data_id);
+        self.sub_heap_size(&object);
+        if data_id != last_data_id {
+            self.notify_relocate(last_data_id, data_id);
+        }
+        object
+    }
+
+    /// Records a new insertion in bounded mode, dropping the stale entries
+    /// when they start to dominate the queue
+    fn record_insertion(&mut self, id: ID) {
+        let validity_id = self.get_validity_id(id);
+        self.insertion_queue.push_back((id, validity_id));
+        if self.insertion_queue.len() > 2 * self.data.len() {
+            let mut queue = std::mem::take(&mut self.insertion_queue);
+            queue.retain(|&(id, validity_id)| self.is_live(id, validity_id));
+            self.insertion_queue = queue;
+        }
+    }
+
+    /// Removes the oldest live object in bounded mode
+    fn evict_oldest(&mut self) -> Option<T> {
+        while let Some((id, validity_id)) = self.insertion_queue.pop_front() {
+            if self.is_live(id, validity_id) {
+                return Some(self.remove_slot(id));
+            }
+        }
+        None
+    }
+
+    /// Tells if the ID references a live object with the provided validity
+    fn is_live(&self, id: ID, validity_id: ID) -> bool {
+        id < self.indices.len()
+            && self.indices[id].index() < self.data.len()
+            && self.metadata[self.indices[id].index()].generation()
+                == validity_id
+    }
+
+    /// Returns the end of the free slots region of the metadata vector,
+    /// which is also the start of the retired slots region
+    fn free_end(&self) -> usize {
+        self.retired_start() - self.taken
+    }
+
+    /// Returns the start of the retired slots region of the metadata vector
+    fn retired_start(&self) -> usize {
+        self.metadata.len() - self.retired
+    }
+
+    /// Moves a taken slot back to the end of the free slots region
+    fn untake(&mut self, id: ID) {
+        self.swap_slots(self.indices[id].index(), self.free_end());
+        self.taken -= 1;
+    }
+
+    /// Swaps two slots that do not hold live objects, keeping the indices
+    /// consistent
+    fn swap_slots(&mut self, a: usize, b: usize) {
+        self.metadata.swap(a, b);
+        self.indices[self.metadata[a].id()] = Idx::from_index(a);
+        self.indices[self.metadata[b].id()] = Idx::from_index(b);
     }
 
     /// Creates a new slot in the vector
@@ -230,281 +2132,3254 @@ impl<T> Vector<T> {
     /// @return The ID of the newly created slot.
     fn get_free_slot(&mut self) -> ID {
         let id = self.get_free_id();
-        self.indices[id] = self.data.len();
+        self.indices[id] = Idx::from_index(self.data.len());
         id
     }
 
-    /// Gets a ID to a free slot.
-    ///
-    /// @note If an ID is available it will be reused, if not a new one will be
-    /// created.
-    /// @return An ID of a free slot.
-    fn get_free_id(&mut self) -> ID {
-        // This means that we have available slots
-        if self.metadata.len() > self.data.len() {
-            // Update the validity ID
-            self.metadata[self.data.len()].validity_id += 1;
-            return self.metadata[self.data.len()].reverse_id;
+    /// Returns the metadata position of the free slot the reuse policy
+    /// picks, or None if there is no free slot. Freed slots become the
+    /// first free slot, so the free region goes from newest to oldest.
+    fn next_free_position(&self) -> Option<usize> {
+        let free = self.data.len()..self.free_end();
+        if free.is_empty() {
+            return None;
+        }
+        match self.reuse {
+            ReusePolicy::Lifo => Some(free.start),
+            ReusePolicy::Fifo => Some(free.end - 1),
+            ReusePolicy::LowestId => {
+                free.min_by_key(|&position| self.metadata[position].id())
+            }
+        }
+    }
+
+    /// Gets a ID to a free slot.
+    ///
+    /// @note If an ID is available it will be reused, if not a new one will be
+    /// created.
+    /// @return An ID of a free slot.
+    fn get_free_id(&mut self) -> ID {
+        // This means that we have available slots
+        if let Some(position) = self.next_free_position() {
+            let first_free = self.data.len();
+            if self.reuse == ReusePolicy::Fifo {
+                // Keep the other free slots from the newest to the oldest
+                self.rotate_slots_right(first_free, position);
+            } else {
+                self.swap_slots(position, first_free);
+            }
+            return self.reuse_first_free();
+        }
+        // A new slot has to be created
+        self.new_slot()
+    }
+
+    /// Prepares the first free slot for a new object
+    ///
+    /// @return The ID of the slot
+    fn reuse_first_free(&mut self) -> ID {
+        // Update the validity ID
+        self.bump_generation(self.data.len());
+        let id = self.metadata[self.data.len()].id();
+        if self.track_reuses {
+            self.extras.add_reuse(id);
+        }
+        id
+    }
+
+    /// Creates the slots of the IDs reserved by an IdReserver, as taken
+    /// slots
+    fn create_reserved_slots(&mut self) {
+        for _ in 0..self.reserved_ids.take() {
+            self.new_slot();
+            self.swap_slots(self.data.len(), self.free_end() - 1);
+            self.taken += 1;
+        }
+    }
+
+    /// Creates a new slot with the next unused ID and makes it the first
+    /// free slot
+    ///
+    /// @return The ID of the new slot
+    fn new_slot(&mut self) -> ID {
+        self.create_reserved_slots();
+        let new_id = self.indices.len();
+        self.growth.reserve(&mut self.metadata, 1);
+        self.growth.reserve(&mut self.indices, 1);
+        self.metadata.push(Metadata::with_ids(new_id, self.generation_floor));
+        self.indices.push(Idx::from_index(self.metadata.len() - 1));
+        // Move the new slot before the retired and taken slots, whose order
+        // does not matter
+        let mut position = self.metadata.len() - 1;
+        for count in [self.retired, self.taken] {
+            if count > 0 {
+                self.swap_slots(position - count, position);
+                position -= count;
+            }
+        }
+        // Shift the free slots instead, so the order in which they are
+        // reused does not change
+        self.rotate_slots_right(self.data.len(), position);
+        new_id
+    }
+
+    /// Moves the slot at @p last to @p first, shifting the slots in between
+    /// up by one
+    fn rotate_slots_right(&mut self, first: usize, last: usize) {
+        if first >= last {
+            return;
+        }
+        self.metadata[first..=last].rotate_right(1);
+        for position in first..=last {
+            let id = self.metadata[position].id();
+            self.indices[id] = Idx::from_index(position);
+        }
+    }
+}
+
+impl<T, Idx: IndexType> Vector<T, Idx> {
+    /// Creates an empty vector storing its IDs, generations and data
+    /// indices as @p Idx. Generations are capped at the highest value of
+    /// @p Idx.
+    ///
+    /// @note Entries, cursors, views, snapshots, raw parts, serialization,
+    /// the parallel iterators and the other modules need the default index
+    /// type.
+    /// @return The empty vector
+    #[must_use]
+    pub fn with_index_type() -> Self {
+        Self::with_index_type_in(Global)
+    }
+}
+
+impl<T, A: Allocator + Clone> Vector<T, ID, A> {
+    /// Creates an empty vector whose internal vectors allocate from
+    /// @p alloc, e.g. a frame arena or a tracking allocator
+    ///
+    /// @note Custom allocators need the `allocator-api2` feature, with which
+    /// the internal vectors are allocator-api2 vectors.
+    /// @param alloc The allocator of the internal vectors
+    /// @return The empty vector
+    #[must_use]
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_index_type_in(alloc)
+    }
+
+    /// Returns the allocator of the internal vectors
+    #[must_use]
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
+}
+
+// Methods relying on the types of the other modules, which use the
+// default index type
+impl<T> Vector<T> {
+    /// Creates an empty vector able to hold @p capacity objects without
+    /// reallocating any of its internal vectors
+    ///
+    /// @param capacity The number of objects to allocate for
+    /// @return The empty vector
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut vector = Self::default();
+        vector.reserve(capacity);
+        vector
+    }
+
+    /// Builds a vector from the objects of an iterator, along with the IDs
+    /// they were assigned
+    ///
+    /// @param iter The objects to insert
+    /// @return The vector and the ID of each object, in iteration order
+    pub fn from_iter_with_ids<I>(iter: I) -> (Self, Vec<ID>)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vector = Self::default();
+        let ids = vector.push_many(iter);
+        (vector, ids)
+    }
+
+    /// Returns the entry of an ID, to update its object or fill its slot
+    /// with a single lookup
+    ///
+    /// @param id The ID of the entry
+    /// @return The entry, or None if the ID does not exist or is retired
+    pub fn entry(&mut self, id: ID) -> Option<Entry<'_, T>> {
+        self.assert_not_frozen();
+        if id >= self.indices.len() || self.is_retired(id) {
+            return None;
+        }
+        Some(Entry::new(self, id))
+    }
+
+    /// Returns a cursor walking the objects in data order, able to remove
+    /// the object it points at
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        self.assert_not_frozen();
+        CursorMut::new(self)
+    }
+
+    /// Returns an iterator that removes the objects matching the predicate
+    /// and yields them with the handle they had
+    ///
+    /// @param predicate Tells if an object should be removed, and can update
+    /// the objects that are kept
+    /// @return The lazy iterator, objects not visited before it is dropped
+    /// are kept
+    pub fn extract_if<F>(&mut self, predicate: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.assert_not_frozen();
+        ExtractIf::new(self, predicate)
+    }
+
+    /// Returns a reserver that several threads can share to reserve IDs at
+    /// the same time
+    ///
+    /// @return The reserver, borrowing the vector until it is dropped
+    pub fn id_reserver(&mut self) -> IdReserver<'_, T> {
+        self.assert_not_frozen();
+        IdReserver::new(self)
+    }
+
+    /// Returns a read-only view over the vector
+    pub fn view(&self) -> VectorView<'_, T> {
+        VectorView::new(self)
+    }
+
+    /// Returns a view over the objects matching the projection, typically
+    /// a single variant when the element type is an enum
+    ///
+    /// @param project Returns the projected object, or None to skip it
+    pub fn project<'a, V: ?Sized + 'a, F>(
+        &'a self,
+        project: F,
+    ) -> Projection<'a, T, F>
+    where
+        F: Fn(&T) -> Option<&V>,
+    {
+        Projection::new(self, project)
+    }
+
+    /// Returns a mutable view over the objects matching the projection
+    ///
+    /// @param project Returns the projected object, or None to skip it
+    pub fn project_mut<'a, V: ?Sized + 'a, F>(
+        &'a mut self,
+        project: F,
+    ) -> ProjectionMut<'a, T, F>
+    where
+        F: Fn(&mut T) -> Option<&mut V>,
+    {
+        ProjectionMut::new(self, project)
+    }
+
+    /// Consumes the Vector to return a reference counted read-only view
+    pub fn into_shared(self) -> SharedView<T> {
+        SharedView::new(self)
+    }
+
+    /// Returns the metadata and the indices the vector has once the slots
+    /// of the IDs reserved by an IdReserver are created, so encoding the
+    /// vector does not drop them
+    ///
+    /// @return The slots, borrowed if no ID is pending
+    pub(crate) fn encoded_slots(
+        &self,
+    ) -> (Cow<'_, [Metadata]>, Cow<'_, [ID]>) {
+        let pending = self.reserved_ids.pending();
+        if pending == 0 {
+            return (
+                Cow::Borrowed(&self.metadata),
+                Cow::Borrowed(&self.indices),
+            );
+        }
+        // The new slots go before the retired ones, like in
+        // create_reserved_slots
+        let start = self.retired_start();
+        let first = self.indices.len();
+        let mut metadata = self.metadata.to_vec();
+        metadata.splice(
+            start..start,
+            (first..first + pending)
+                .map(|id| Metadata::new(id, self.generation_floor)),
+        );
+        let mut indices = self.indices.to_vec();
+        indices.resize(first + pending, 0);
+        for (position, md) in metadata.iter().enumerate().skip(start) {
+            indices[md.reverse_id] = position;
+        }
+        (Cow::Owned(metadata), Cow::Owned(indices))
+    }
+
+    /// Returns the optional state of the slots, the IDs reserved by an
+    /// IdReserver having none
+    pub(crate) fn slot_extras(&self) -> &SlotExtras {
+        &self.extras
+    }
+
+    /// Captures the logical state of the vector: the objects, the IDs and
+    /// the generations of every slot
+    ///
+    /// @return The state, to give back to `restore`
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot<T>
+    where
+        T: Clone,
+    {
+        Snapshot {
+            data: self.data.to_vec(),
+            metadata: self.metadata.to_vec(),
+            indices: self.indices.to_vec(),
+            extras: self.extras.clone(),
+            retired: self.retired,
+            taken: self.taken,
+            generation_floor: self.generation_floor,
+            insertion_queue: self.insertion_queue.clone(),
+            heap_size: self.heap_size,
+        }
+    }
+
+    /// Rolls the vector back to a captured state. The configuration and the
+    /// tick are kept.
+    ///
+    /// @note A slot whose handles were issued after the snapshot gets a
+    /// generation above all of them, so they can never alias the restored
+    /// object or a later one. The handles valid when the snapshot was taken
+    /// are only valid again if their slot was left alone or only had its
+    /// object erased. Slots reaching the generation limit this way are
+    /// retired, with their restored object. The IDs created after the
+    /// snapshot are kept as free slots. The hooks are not called and the
+    /// event log is not updated.
+    /// @param snapshot The state to restore
+    pub fn restore(&mut self, snapshot: &Snapshot<T>)
+    where
+        T: Clone,
+    {
+        self.assert_not_frozen();
+        self.create_reserved_slots();
+        // The generation of every ID before the roll back, and whether it
+        // was live
+        let current: Vec<(ID, bool)> = (0..self.indices.len())
+            .map(|id| (self.get_validity_id(id), self.contains_id(id)))
+            .collect();
+        clone_from_slice(&mut self.data, &snapshot.data);
+        clone_from_slice(&mut self.metadata, &snapshot.metadata);
+        clone_from_slice(&mut self.indices, &snapshot.indices);
+        self.extras.clone_from(&snapshot.extras);
+        self.retired = snapshot.retired;
+        self.taken = snapshot.taken;
+        self.generation_floor = snapshot.generation_floor;
+        self.insertion_queue.clone_from(&snapshot.insertion_queue);
+        self.heap_size = if self.size_hook.is_some() {
+            snapshot.heap_size
+        } else {
+            0
+        };
+        while self.indices.len() < current.len() {
+            self.new_slot();
+        }
+        let mut changed = Vec::new();
+        for (id, &(generation, live)) in current.iter().enumerate() {
+            let position = self.indices[id];
+            let restored = self.metadata[position].validity_id;
+            // Erasing bumps the generation once and issues no handle
+            let only_erased = position < self.data.len()
+                && !live
+                && generation == restored + 1;
+            if generation == restored || only_erased {
+                continue;
+            }
+            changed.push(id);
+            let bumped = restored.max(generation).saturating_add(1);
+            if bumped <= self.generation_limit {
+                self.metadata[position].validity_id = bumped;
+                for entry in &mut self.insertion_queue {
+                    if *entry == (id, restored) {
+                        entry.1 = bumped;
+                    }
+                }
+                continue;
+            }
+            if self.contains_id(id) {
+                self.detach_slot(id);
+            } else if self.is_taken(id) {
+                self.untake(id);
+            }
+            let position = self.indices[id];
+            self.metadata[position].validity_id = self.generation_limit;
+        }
+        for id in changed {
+            self.retire_if_exhausted(id);
+        }
+        self.telemetry.record_len(self.data.len());
+    }
+
+    /// Decomposes the vector into its raw state, which can be persisted and
+    /// given back to `from_raw_parts`
+    pub fn into_raw_parts(mut self) -> RawParts<T> {
+        self.create_reserved_slots();
+        RawParts {
+            data: into_vec(self.data),
+            metadata: into_vec(self.metadata),
+            indices: into_vec(self.indices),
+            retired: self.retired,
+            identity: self.identity,
+            generation_floor: self.generation_floor,
+            extras: self.extras,
+        }
+    }
+
+    /// Rebuilds a vector from its raw state, keeping all IDs and validity
+    /// IDs so previously created handles remain valid
+    ///
+    /// @param parts The raw state, typically loaded from a save file
+    /// @return The vector, or the first inconsistency found
+    pub fn from_raw_parts(parts: RawParts<T>) -> Result<Self, RawPartsError> {
+        let RawParts {
+            data,
+            metadata,
+            indices,
+            retired,
+            identity,
+            generation_floor,
+            extras,
+        } = parts;
+        if metadata.len() != indices.len() {
+            return Err(RawPartsError::LengthMismatch {
+                metadata: metadata.len(),
+                indices: indices.len(),
+            });
+        }
+        if data.len().saturating_add(retired) > metadata.len() {
+            return Err(RawPartsError::TooManyObjects {
+                objects: data.len() + retired,
+                slots: metadata.len(),
+            });
+        }
+        for (position, md) in metadata.iter().enumerate() {
+            if indices.get(md.reverse_id) != Some(&position) {
+                return Err(RawPartsError::InconsistentSlot { position });
+            }
+            if md.validity_id > MAX_GENERATION {
+                return Err(RawPartsError::GenerationOutOfRange { position });
+            }
+        }
+        if extras.id_count() > indices.len() {
+            return Err(RawPartsError::TooManyExtras {
+                extras: extras.id_count(),
+                ids: indices.len(),
+            });
+        }
+        let vector = Self {
+            data: from_vec(data),
+            metadata: from_vec(metadata),
+            indices: from_vec(indices),
+            extras,
+            retired,
+            identity,
+            generation_floor: generation_floor.min(MAX_GENERATION),
+            ..Self::default()
+        };
+        vector.telemetry.record_len(vector.data.len());
+        Ok(vector)
+    }
+
+    /// Rebuilds a vector from a possibly corrupted raw state, salvaging
+    /// every consistent slot. The IDs that could not be recovered are
+    /// retired so they never resolve nor get reused.
+    ///
+    /// @param parts The raw state, typically loaded from a save file
+    /// @return The vector and the list of broken IDs
+    pub fn from_raw_parts_lenient(parts: RawParts<T>) -> (Self, Vec<ID>) {
+        let RawParts {
+            data,
+            metadata,
+            indices,
+            retired,
+            identity,
+            generation_floor,
+            mut extras,
+        } = parts;
+        let id_count = indices.len().max(metadata.len());
+        let retired_start =
+            metadata.len().saturating_sub(retired).max(data.len());
+        let mut claimed = vec![false; id_count];
+        let mut live = Vec::new();
+        let mut live_data = Vec::new();
+        let mut free = Vec::new();
+        let mut retired_slots = Vec::new();
+
+        let mut objects = data.into_iter();
+        for (position, md) in metadata.iter().enumerate() {
+            let object = objects.next();
+            let id = md.reverse_id;
+            if indices.get(id) != Some(&position)
+                || md.validity_id > MAX_GENERATION
+            {
+                continue;
+            }
+            claimed[id] = true;
+            match object {
+                Some(object) => {
+                    live.push(*md);
+                    live_data.push(object);
+                }
+                None if position < retired_start => free.push(*md),
+                None => retired_slots.push(*md),
+            }
+        }
+        let broken: Vec<ID> =
+            (0..id_count).filter(|&id| !claimed[id]).collect();
+        retired_slots.extend(broken.iter().map(|&id| Metadata::new(id, 0)));
+
+        let retired = retired_slots.len();
+        let mut metadata = live;
+        metadata.extend(free);
+        metadata.extend(retired_slots);
+        let mut indices = vec![0; id_count];
+        for (position, md) in metadata.iter().enumerate() {
+            indices[md.reverse_id] = position;
+        }
+        extras.truncate(id_count);
+        let vector = Self {
+            data: from_vec(live_data),
+            metadata: from_vec(metadata),
+            indices: from_vec(indices),
+            extras,
+            retired,
+            identity,
+            generation_floor: generation_floor.min(MAX_GENERATION),
+            ..Self::default()
+        };
+        vector.telemetry.record_len(vector.data.len());
+        (vector, broken)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync> Vector<T> {
+    /// Returns a parallel iterator over the objects, in data order
+    pub fn par_iter(&self) -> rayon::slice::Iter<'_, T> {
+        self.data.par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send + Sync> Vector<T> {
+    /// Returns a parallel iterator over the objects, each paired with a
+    /// handle referencing it, in data order
+    pub fn par_iter_with_handles(
+        &self,
+    ) -> impl IndexedParallelIterator<Item = (Handle<T>, &T)> + '_ {
+        self.metadata[..self.data.len()]
+            .par_iter()
+            .zip(self.data.par_iter())
+            .map(|(md, object)| {
+                (Handle::new(md.reverse_id, md.validity_id), object)
+            })
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> Vector<T> {
+    /// Returns a parallel iterator over mutable references to the objects,
+    /// in data order
+    pub fn par_iter_mut(&mut self) -> rayon::slice::IterMut<'_, T> {
+        self.assert_not_frozen();
+        self.touch_all();
+        self.data.par_iter_mut()
+    }
+
+    /// Returns a parallel iterator over disjoint mutable chunks of the
+    /// objects, in data order, each paired with the handles of its objects
+    ///
+    /// @param chunk_size The number of objects per chunk, the last chunk
+    /// may be shorter
+    pub fn par_chunks_mut(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl IndexedParallelIterator<Item = (ChunkHandles<'_, T>, &mut [T])>
+    {
+        self.assert_not_frozen();
+        assert!(chunk_size != 0, "The chunk size must not be zero");
+        self.touch_all();
+        let len = self.data.len();
+        self.metadata[..len]
+            .par_chunks(chunk_size)
+            .map(ChunkHandles::new)
+            .zip(self.data.par_chunks_mut(chunk_size))
+    }
+
+    /// Builds a vector from a parallel iterator. Object @p i of the iterator
+    /// gets the ID @p i.
+    ///
+    /// @param iter The objects to insert
+    /// @return The populated vector
+    pub fn par_from_iter<I>(iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let mut vector = Self::default();
+        vector.par_extend(iter);
+        vector
+    }
+
+    /// Inserts the objects of a parallel iterator. The free slots are reused
+    /// first, then the remaining objects get a contiguous range of new IDs
+    /// whose bookkeeping is built in parallel and appended at the end.
+    ///
+    /// @note In bounded mode the objects are pushed one by one so the
+    /// eviction order is preserved.
+    /// @param iter The objects to insert
+    pub fn par_extend<I>(&mut self, iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        self.assert_not_frozen();
+        let mut objects: Vec<T> = iter.into_par_iter().collect();
+        if self.max_len.is_some() {
+            for object in objects {
+                self.push(object);
+            }
+            return;
+        }
+
+        let reused = (self.free_end() - self.data.len()).min(objects.len());
+        let fresh = objects.split_off(reused);
+        for object in objects {
+            self.insert(object);
+        }
+        if fresh.is_empty() {
+            return;
+        }
+
+        // All the free slots are used, the new slots go right after the live
+        // ones and before the retired ones
+        self.create_reserved_slots();
+        let count = fresh.len();
+        let first_id = self.indices.len();
+        let first_slot = self.data.len();
+        let tick = self.tick;
+        let floor = self.generation_floor;
+        let retired = self.metadata.split_off(first_slot);
+        self.growth.reserve(&mut self.data, count);
+        self.growth.reserve(&mut self.metadata, count + retired.len());
+        self.growth.reserve(&mut self.indices, count);
+        // Filled in place, so the internal vectors need not be the ones of
+        // the standard library
+        self.metadata.resize(first_slot + count, Metadata::default());
+        self.metadata[first_slot..].par_iter_mut().enumerate().for_each(
+            |(offset, md)| *md = Metadata::new(first_id + offset, floor),
+        );
+        self.indices.resize(first_id + count, 0);
+        self.indices[first_id..].par_iter_mut().enumerate().for_each(
+            |(offset, index)| *index = first_slot + offset,
+        );
+        self.metadata.extend(retired);
+        for slot in self.free_end()..self.metadata.len() {
+            self.indices[self.metadata[slot].reverse_id] = slot;
+        }
+        for id in first_id..first_id + count {
+            self.extras.set_insertion_tick(id, tick);
+            self.extras.set_modified_tick(id, tick);
+        }
+
+        for object in &fresh {
+            self.add_heap_size(object);
+        }
+        for position in first_slot..first_slot + count {
+            self.record_event(EventOp::Insert, position);
+        }
+        self.telemetry.record_inserts(count);
+        self.data.extend(fresh);
+        self.telemetry.record_len(self.data.len());
+    }
+}
+
+impl<T, Idx: IndexType, A: Allocator + Clone> Index<usize>
+    for Vector<T, Idx, A>
+{
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let data_index = self.indices[index].index();
+        &self.data[data_index]
+    }
+}
+
+impl<T, Idx: IndexType, A: Allocator + Clone> IndexMut<usize>
+    for Vector<T, Idx, A>
+{
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.assert_not_frozen();
+        let data_index = self.indices[index].index();
+        self.touch(data_index);
+        &mut self.data[data_index]
+    }
+}
+
+impl<T, Idx: IndexType, A: Allocator + Clone> Index<Handle<T>>
+    for Vector<T, Idx, A>
+{
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &Self::Output {
+        self.get(&handle).expect("Attempted to access a stale handle")
+    }
+}
+
+impl<T, Idx: IndexType, A: Allocator + Clone> IndexMut<Handle<T>>
+    for Vector<T, Idx, A>
+{
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut Self::Output {
+        self.get_mut(&handle).expect("Attempted to access a stale handle")
+    }
+}
+
+impl<'a, T, Idx, A: Allocator> IntoIterator for &'a Vector<T, Idx, A> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<'a, T, Idx: IndexType, A: Allocator + Clone> IntoIterator
+    for &'a mut Vector<T, Idx, A>
+{
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, Idx, A: Allocator> IntoIterator for Vector<T, Idx, A> {
+    type Item = T;
+    type IntoIter = buffer!(IntoIter<T, A>);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<T, Idx: IndexType> FromIterator<T> for Vector<T, Idx> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vector = Self::with_index_type();
+        vector.extend(iter);
+        vector
+    }
+}
+
+impl<T, Idx: IndexType, A: Allocator + Clone> Extend<T>
+    for Vector<T, Idx, A>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.assert_not_frozen();
+        let iter = iter.into_iter();
+        self.reserve_insertions(iter.size_hint().0);
+        for object in iter {
+            self.push(object);
+        }
+    }
+}
+
+/// The handles of the objects of a chunk lent by `Vector::par_chunks_mut`.
+#[cfg(feature = "rayon")]
+pub struct ChunkHandles<'a, T> {
+    /// The metadata of the objects of the chunk.
+    metadata: &'a [Metadata],
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T> ChunkHandles<'a, T> {
+    fn new(metadata: &'a [Metadata]) -> Self {
+        Self { metadata, _marker: PhantomData }
+    }
+
+    /// Returns the handle of the object at index @p i of the chunk
+    ///
+    /// @param i The index in the chunk
+    /// @return The handle, or None if @p i is out of the chunk
+    #[must_use]
+    pub fn get(&self, i: usize) -> Option<Handle<T>> {
+        let md = self.metadata.get(i)?;
+        Some(Handle::new(md.reverse_id, md.validity_id))
+    }
+
+    /// Returns the number of objects in the chunk
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.metadata.len()
+    }
+
+    /// Tells if the chunk is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+    }
+
+    /// Returns an iterator over the handles, in chunk order
+    pub fn iter(&self) -> impl Iterator<Item = Handle<T>> + 'a {
+        self.metadata
+            .iter()
+            .map(|md| Handle::new(md.reverse_id, md.validity_id))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> FromParallelIterator<T> for Vector<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(iter: I) -> Self {
+        Self::par_from_iter(iter)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> ParallelExtend<T> for Vector<T> {
+    fn par_extend<I: IntoParallelIterator<Item = T>>(&mut self, iter: I) {
+        Vector::par_extend(self, iter);
+    }
+}
+
+/// Takes ownership of the buffer, object @p i getting the ID @p i
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(data: Vec<T>) -> Self {
+        let vector = Self {
+            metadata: (0..data.len()).map(|id| Metadata::new(id, 0)).collect(),
+            indices: (0..data.len()).collect(),
+            data: from_vec(data),
+            ..Self::default()
+        };
+        vector.telemetry.record_inserts(vector.data.len());
+        vector.telemetry.record_len(vector.data.len());
+        vector
+    }
+}
+
+// Only for the default index type, so the type of `Vector::default()` can
+// be inferred
+impl<T> Default for Vector<T> {
+    fn default() -> Self {
+        Self::with_index_type()
+    }
+}
+
+// Written by hand as derive does not support the type macro of the
+// internal vectors
+impl<T: fmt::Debug, Idx: fmt::Debug, A: Allocator + fmt::Debug> fmt::Debug
+    for Vector<T, Idx, A>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vector")
+            .field("data", &self.data)
+            .field("metadata", &self.metadata)
+            .field("indices", &self.indices)
+            .field("allocator", &self.allocator)
+            .field("frozen", &self.frozen)
+            .field("growth", &self.growth)
+            .field("reuse", &self.reuse)
+            .field("tick", &self.tick)
+            .field("track_modifications", &self.track_modifications)
+            .field("track_reuses", &self.track_reuses)
+            .field("extras", &self.extras)
+            .field("retired", &self.retired)
+            .field("taken", &self.taken)
+            .field("max_len", &self.max_len)
+            .field("generation_limit", &self.generation_limit)
+            .field("monotonic", &self.monotonic)
+            .field("generation_floor", &self.generation_floor)
+            .field("insertion_queue", &self.insertion_queue)
+            .field("identity", &self.identity)
+            .field("size_hook", &self.size_hook)
+            .field("heap_size", &self.heap_size)
+            .field("erase_hook", &self.erase_hook)
+            .field("relocate_hook", &self.relocate_hook)
+            .field("log_events", &self.log_events)
+            .field("events", &self.events)
+            .field("reserved_ids", &self.reserved_ids)
+            .field("telemetry", &self.telemetry)
+            .finish()
+    }
+}
+
+// Written by hand so clone_from reuses the allocations of the target, which
+// matters for vectors copied every frame
+impl<T: Clone, Idx: Clone, A: Allocator + Clone> Clone
+    for Vector<T, Idx, A>
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            metadata: self.metadata.clone(),
+            indices: self.indices.clone(),
+            allocator: self.allocator.clone(),
+            frozen: self.frozen,
+            growth: self.growth,
+            reuse: self.reuse,
+            tick: self.tick,
+            track_modifications: self.track_modifications,
+            track_reuses: self.track_reuses,
+            extras: self.extras.clone(),
+            retired: self.retired,
+            taken: self.taken,
+            max_len: self.max_len,
+            generation_limit: self.generation_limit,
+            monotonic: self.monotonic,
+            generation_floor: self.generation_floor,
+            insertion_queue: self.insertion_queue.clone(),
+            // A clone is another vector, so it gets its own identity
+            identity: self.identity.map(|_| generate_vector_id()),
+            size_hook: self.size_hook,
+            heap_size: self.heap_size,
+            erase_hook: self.erase_hook,
+            relocate_hook: self.relocate_hook,
+            log_events: self.log_events,
+            events: self.events.clone(),
+            reserved_ids: self.reserved_ids.clone(),
+            telemetry: self.telemetry.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.data.clone_from(&source.data);
+        self.metadata.clone_from(&source.metadata);
+        self.indices.clone_from(&source.indices);
+        self.frozen = source.frozen;
+        self.growth = source.growth;
+        self.reuse = source.reuse;
+        self.tick = source.tick;
+        self.track_modifications = source.track_modifications;
+        self.track_reuses = source.track_reuses;
+        self.extras.clone_from(&source.extras);
+        self.retired = source.retired;
+        self.taken = source.taken;
+        self.max_len = source.max_len;
+        self.generation_limit = source.generation_limit;
+        self.monotonic = source.monotonic;
+        self.generation_floor = source.generation_floor;
+        self.insertion_queue.clone_from(&source.insertion_queue);
+        self.identity = source.identity.map(|_| generate_vector_id());
+        self.size_hook = source.size_hook;
+        self.heap_size = source.heap_size;
+        self.erase_hook = source.erase_hook;
+        self.relocate_hook = source.relocate_hook;
+        self.log_events = source.log_events;
+        self.events.clone_from(&source.events);
+        self.reserved_ids.clone_from(&source.reserved_ids);
+        self.telemetry.clone_from(&source.telemetry);
+    }
+}
+
+// Two vectors are equal when they hold equal objects under the same IDs,
+// whatever their data order, free slots, generations and configuration.
+impl<T: PartialEq, Idx: IndexType, A: Allocator + Clone> PartialEq
+    for Vector<T, Idx, A>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.data.len() == other.data.len()
+            && self.iter_in_id_order().eq(other.iter_in_id_order())
+    }
+}
+
+impl<T: Eq, Idx: IndexType, A: Allocator + Clone> Eq for Vector<T, Idx, A> {}
+
+// Like the equality, the hash only covers the live objects and their IDs,
+// in ascending ID order
+impl<T: Hash, Idx: IndexType, A: Allocator + Clone> Hash
+    for Vector<T, Idx, A>
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.len().hash(state);
+        for (id, object) in self.iter_in_id_order() {
+            id.hash(state);
+            object.hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_push_and_get() {
+        let mut vec = Vector::default();
+        
+        let id1 = vec.push(10);
+        let id2 = vec.push(20);
+
+        let h1 = vec.create_handle(id1).unwrap();
+        let h2 = vec.create_handle(id2).unwrap();
+
+        assert_eq!(vec.get(&h1), Some(&10));
+        assert_eq!(vec.get(&h2), Some(&20));
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut vec = Vector::with_capacity(16);
+
+        assert!(vec.is_empty());
+        assert!(vec.capacity() >= 16);
+        assert!(vec.metadata.capacity() >= 16);
+        assert!(vec.indices.capacity() >= 16);
+
+        let data = vec.data.as_ptr();
+        for i in 0..16 {
+            vec.push(i);
+        }
+        assert_eq!(vec.data.as_ptr(), data);
+    }
+
+    #[test]
+    fn test_narrow_index_type() {
+        let mut vec = Vector::<char, u32>::with_index_type();
+        let a = vec.push('a');
+        let b = vec.push('b');
+        let h_a = vec.create_handle(a).unwrap();
+        vec.erase_by_handle(&h_a);
+        let c = vec.push('c');
+        let h_c = vec.create_handle(c).unwrap();
+        assert_eq!(h_c.id, a);
+        assert_eq!(h_c.generation(), h_a.generation() + 2);
+        assert_eq!(vec.get(&h_a), None);
+        assert_eq!(vec.get(&h_c), Some(&'c'));
+        assert_eq!(vec[b], 'b');
+        assert_eq!(size_of::<Metadata<u32>>() * 2, size_of::<Metadata>());
+
+        let vec = Vector::<char, u16>::with_index_type();
+        assert_eq!(vec.generation_limit(), u16::MAX as ID);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_narrow_index_type_exhausted() {
+        let mut vec = Vector::<(), u16>::with_index_type();
+        vec.extend(std::iter::repeat_n((), (1 << 16) + 1));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let vec: Vector<char> = "abc".chars().collect();
+        assert_eq!(vec.iter().collect::<String>(), "abc");
+
+        let (vec, ids) = Vector::from_iter_with_ids(["x", "y"]);
+        assert_eq!(ids, vec![0, 1]);
+        let handle = vec.create_handle(ids[1]).unwrap();
+        assert_eq!(vec.get(&handle), Some(&"y"));
+    }
+
+    #[test]
+    fn test_extend_reuses_slots() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        vec.push(2);
+        vec.erase_by_id(id);
+
+        vec.extend([3, 4]);
+
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec[id], 3);
+        assert_eq!(vec.get_next_id(), 3);
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let data = vec![10, 20, 30];
+        let buffer = data.as_ptr();
+        let mut vec = Vector::from(data);
+
+        assert_eq!(vec.data.as_ptr(), buffer);
+        let handle = vec.create_handle(2).unwrap();
+        assert_eq!(vec.get(&handle), Some(&30));
+        assert_eq!(vec.push(40), 3);
+    }
+
+    #[test]
+    fn test_push_many() {
+        let mut vec = Vector::default();
+        let first = vec.push(0);
+        vec.erase_by_id(first);
+
+        let ids = vec.push_many(1..=1000);
+
+        assert_eq!(ids.len(), 1000);
+        assert_eq!(ids[0], first);
+        assert!(vec.capacity() >= 1000);
+        assert_eq!(vec.metadata.len(), 1000);
+        for (&id, value) in ids.iter().zip(1..=1000) {
+            assert_eq!(vec[id], value);
+        }
+    }
+
+    #[test]
+    fn test_push_with() {
+        let mut vec = Vector::default();
+        let a = vec.push_with(|id| (id, 'a'));
+        let b = vec.push_with(|id| (id, 'b'));
+        vec.erase_by_id(a);
+        let c = vec.push_with(|id| (id, 'c'));
+
+        assert_eq!(vec[b], (b, 'b'));
+        assert_eq!(vec[c], (c, 'c'));
+        assert_eq!(c, a);
+
+        vec.set_max_len(Some(2));
+        let d = vec.push_with(|id| (id, 'd'));
+        assert_eq!(vec[d], (d, 'd'));
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_returns_object() {
+        let mut vec = Vector::default();
+        let a = vec.push("a".to_string());
+        let b = vec.push("b".to_string());
+        let handle = vec.create_handle(a).unwrap();
+
+        assert_eq!(vec.remove(&handle).as_deref(), Some("a"));
+        assert_eq!(vec.remove(&handle), None);
+        assert_eq!(vec.remove_by_id(b).as_deref(), Some("b"));
+        assert_eq!(vec.remove_by_id(b), None);
+        assert_eq!(vec.remove_by_id(42), None);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn test_take_and_put_back() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        vec.retire_id(ids[3]);
+        let handle = vec.create_handle(ids[1]).unwrap();
+
+        assert_eq!(vec.take(&handle), Some(1));
+        assert!(vec.is_taken(ids[1]));
+        assert_eq!(vec.get(&handle), None);
+        assert_eq!(vec.take(&handle), None);
+
+        // Neither the taken ID nor the retired one is reused
+        let other = vec.push(10);
+        assert_eq!(other, 4);
+        assert!(vec.is_retired(ids[3]));
+        assert!(!vec.is_retired(ids[1]));
+
+        assert_eq!(vec.put_back(ids[1], 11), Ok(()));
+        assert_eq!(vec.get(&handle), Some(&11));
+        assert_eq!(vec.put_back(ids[1], 12), Err(12));
+        assert_eq!(vec.put_back(ids[3], 13), Err(13));
+        assert_eq!(vec.len(), 4);
+    }
+
+    #[test]
+    fn test_retire_taken_id() {
+        let mut vec = Vector::default();
+        let a = vec.push('a');
+        let b = vec.push('b');
+        let handle = vec.create_handle(a).unwrap();
+        vec.take(&handle);
+        vec.take(&vec.create_handle(b).unwrap());
+
+        assert!(vec.retire_id(a));
+        assert!(vec.is_retired(a));
+        assert!(vec.is_taken(b));
+        assert_eq!(vec.put_back(a, 'x'), Err('x'));
+        assert_eq!(vec.put_back(b, 'b'), Ok(()));
+        assert_eq!(vec.push('c'), 2);
+        assert_eq!(vec.get(&handle), None);
+    }
+
+    #[test]
+    fn test_try_erase() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        assert!(!vec.try_erase_by_id(id, handle.generation() + 1));
+        assert!(!vec.try_erase_by_id(7, 0));
+        assert!(vec.try_erase(&handle));
+        assert!(!vec.try_erase(&handle));
+        vec.push(2);
+        assert!(!vec.try_erase(&handle));
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn test_erase_many() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..10).map(|i| vec.push(i)).collect();
+        let stale = vec.create_handle(ids[0]).unwrap();
+        vec.erase_by_id(ids[0]);
+        let mut handles: Vec<_> = [1, 9, 4, 8, 4]
+            .iter()
+            .map(|&i| vec.create_handle(ids[i]).unwrap())
+            .collect();
+        handles.push(stale);
+
+        assert_eq!(vec.erase_many(&handles), 4);
+
+        let mut left: Vec<_> = vec.iter().copied().collect();
+        left.sort_unstable();
+        assert_eq!(left, vec![2, 3, 5, 6, 7]);
+        for &i in &[2, 3, 5, 6, 7] {
+            let handle = vec.create_handle(ids[i]).unwrap();
+            assert_eq!(vec.get(&handle), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..10).map(|i| vec.push(i)).collect();
+        let handles: Vec<_> =
+            ids.iter().map(|&id| vec.create_handle(id).unwrap()).collect();
+
+        vec.retain(|&value| value % 3 == 0);
+
+        assert_eq!(vec.len(), 4);
+        for (value, handle) in handles.iter().enumerate() {
+            let expected = (value % 3 == 0).then_some(&value);
+            assert_eq!(vec.get(handle), expected);
+        }
+    }
+
+    #[test]
+    fn test_retain_mut() {
+        let mut vec = Vector::default();
+        let timers: Vec<_> = [1, 3, 2, 1]
+            .into_iter()
+            .map(|timer| {
+                let id = vec.push(timer);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        vec.retain_mut(|timer| {
+            *timer -= 1;
+            *timer > 0
+        });
+
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(&timers[0]), None);
+        assert_eq!(vec.get(&timers[1]), Some(&2));
+        assert_eq!(vec.get(&timers[2]), Some(&1));
+        assert_eq!(vec.get(&timers[3]), None);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        vec.push(2);
+        let handle = vec.create_handle(id).unwrap();
+
+        let drained: Vec<_> = vec.drain().collect();
+
+        assert_eq!(drained, vec![1, 2]);
+        assert!(vec.is_empty());
+        assert_eq!(vec.get(&handle), None);
+        assert_eq!(vec.push(3), id);
+        assert_eq!(vec.get(&handle), None);
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut vec = Vector::default();
+        let a = vec.push('a');
+        let b = vec.push('b');
+
+        let (handle, object) = vec.pop().unwrap();
+        assert_eq!((handle.id, object), (b, 'b'));
+        assert_eq!(vec.get(&handle), None);
+        assert_eq!(vec.pop().map(|(h, o)| (h.id, o)), Some((a, 'a')));
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = (0..5)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        vec.truncate(2);
+        vec.truncate(3);
+
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.get(&handles[1]), Some(&1));
+        for handle in &handles[2..] {
+            assert_eq!(vec.get(handle), None);
+        }
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = (0..5)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        let (other, remap) = vec.split_off(3);
+
+        assert_eq!(vec.len(), 3);
+        assert_eq!(other.iter().copied().collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(remap.len(), 2);
+        for (value, handle) in handles.iter().enumerate() {
+            match remap.remap(handle) {
+                Some(new) => assert_eq!(other.get(&new), Some(&value)),
+                None => assert_eq!(vec.get(handle), Some(&value)),
+            }
+        }
+        assert_eq!(vec.get(&handles[4]), None);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut main = Vector::default();
+        let kept = main.push("main");
+        let kept = main.create_handle(kept).unwrap();
+        let mut staging = Vector::default();
+        let a = staging.push("a");
+        let b = staging.push("b");
+        let a = staging.create_handle(a).unwrap();
+        let b = staging.create_handle(b).unwrap();
+        staging.erase_by_handle(&a);
+
+        let remap = main.append(staging);
+
+        assert_eq!(main.len(), 2);
+        assert_eq!(remap.len(), 1);
+        assert_eq!(remap.remap(&a), None);
+        assert_eq!(main.get(&remap.remap(&b).unwrap()), Some(&"b"));
+        assert_eq!(main.get(&kept), Some(&"main"));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = (0..6)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+        vec.erase_by_handle(&handles[0]);
+        vec.erase_by_handle(&handles[3]);
+        vec.retire_id(handles[3].id);
+
+        let remap = vec.compact();
+
+        assert_eq!(vec.metadata.len(), 4);
+        assert_eq!(vec.indices.len(), 4);
+        assert_eq!(vec.retired_count(), 0);
+        assert_eq!(remap.len(), 4);
+        for (value, handle) in handles.iter().enumerate() {
+            assert_eq!(vec.get(handle), None);
+            if let Some(new) = remap.remap(handle) {
+                assert_eq!(vec.get(&new), Some(&value));
+            }
+        }
+        assert_eq!(vec.push(6), 4);
+    }
+
+    #[test]
+    fn test_compact_keeps_eviction_order() {
+        let mut vec = Vector::default();
+        vec.set_max_len(Some(3));
+        let first = vec.push(0);
+        vec.push(1);
+        vec.push(2);
+        vec.erase_by_id(first);
+        vec.compact();
+
+        vec.push(3);
+        let evicted = vec.push_evicting(4).1;
+
+        assert_eq!(evicted, Some(1));
+    }
+
+    #[test]
+    fn test_iter_with_handles() {
+        let mut vec = Vector::default();
+        let a = vec.push('a');
+        let b = vec.push('b');
+        vec.push('c');
+        vec.erase_by_id(a);
+
+        let entries: Vec<_> = vec.iter_with_handles().collect();
+
+        assert_eq!(entries.len(), 2);
+        for (handle, object) in entries {
+            assert_eq!(vec.get(&handle), Some(object));
+        }
+        let handle = vec.create_handle(b).unwrap();
+        assert!(vec.iter_with_handles().any(|(h, _)| h == handle));
+    }
+
+    #[test]
+    fn test_ids_and_handles() {
+        let mut vec = Vector::default();
+        let a = vec.push(1);
+        let b = vec.push(2);
+        let c = vec.push(3);
+        vec.erase_by_id(a);
+
+        assert_eq!(vec.ids().collect::<Vec<_>>(), vec![c, b]);
+        let handles: Vec<_> = vec.handles().collect();
+        assert_eq!(handles.len(), 2);
+        assert_eq!(vec.get(&handles[0]), Some(&3));
+        assert_eq!(vec.get(&handles[1]), Some(&2));
+    }
+
+    #[test]
+    fn test_into_iter_with_handles() {
+        let mut vec = Vector::default();
+        let a = vec.push("a".to_string());
+        let b = vec.push("b".to_string());
+        let a = vec.create_handle(a).unwrap();
+        let b = vec.create_handle(b).unwrap();
+
+        let mut migrated = std::collections::HashMap::new();
+        migrated.extend(vec.into_iter_with_handles());
+
+        assert_eq!(migrated[&a], "a");
+        assert_eq!(migrated[&b], "b");
+    }
+
+    #[test]
+    fn test_iter_in_id_order() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..5).map(|i| vec.push(i * 10)).collect();
+        vec.erase_by_id(ids[0]);
+        vec.erase_by_id(ids[2]);
+
+        let ordered: Vec<_> = vec.iter_in_id_order().collect();
+
+        assert_eq!(ordered, vec![(1, &10), (3, &30), (4, &40)]);
+    }
+
+    #[test]
+    fn test_get_disjoint_mut() {
+        let mut vec = Vector::default();
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+
+        let [a, b] =
+            vec.get_disjoint_mut([&handles[3], &handles[1]]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(vec.get(&handles[3]), Some(&1));
+        assert_eq!(vec.get(&handles[1]), Some(&3));
+        assert!(vec.get_disjoint_mut([&handles[0], &handles[0]]).is_none());
+
+        let objects = vec.get_disjoint_mut_slice(&handles[1..]).unwrap();
+        assert_eq!(objects.iter().map(|o| **o).collect::<Vec<_>>(), [3, 2, 1]);
+        let twice = [handles[2], handles[2]];
+        assert!(vec.get_disjoint_mut_slice(&twice).is_none());
+
+        vec.erase_by_handle(&handles[2]);
+        assert!(vec.get_disjoint_mut([&handles[0], &handles[2]]).is_none());
+        assert!(vec.get_disjoint_mut_slice(&handles[..3]).is_none());
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        assert!(vec.contains(&handle));
+        assert!(vec.contains_id(id));
+        assert!(!vec.contains_id(id + 1));
+
+        vec.erase_by_id(id);
+        vec.push(2);
+        assert!(!vec.contains(&handle));
+        assert!(vec.contains_id(id));
+        assert_eq!(vec.telemetry().stale_accesses, 0);
+    }
+
+    #[test]
+    fn test_index_by_handle() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        vec[handle] += 1;
+
+        assert_eq!(vec[handle], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to access a stale handle")]
+    fn test_index_by_stale_handle() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+        vec.erase_by_handle(&handle);
+        vec.push(2);
+
+        let _ = vec[handle];
+    }
+
+    #[test]
+    fn test_get_unchecked() {
+        let mut vec = Vector::default();
+        vec.push(0);
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+        assert!(vec.contains(&handle));
+
+        // SAFETY: the handle was just checked and nothing was removed since
+        unsafe {
+            *vec.get_unchecked_mut(&handle) += 1;
+            assert_eq!(*vec.get_unchecked(&handle), 2);
+        }
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let mut vec = Vector::default();
+        vec.push(3);
+        vec.push(1);
+
+        vec.as_mut_slice().iter_mut().for_each(|value| *value *= 2);
+
+        assert_eq!(vec.as_slice(), &[6, 2]);
+    }
+
+    #[test]
+    fn test_first_and_last_handle() {
+        let mut vec = Vector::default();
+        assert!(vec.first_handle().is_none());
+        assert!(vec.last_handle().is_none());
+
+        vec.push('a');
+        vec.push('b');
+        let (first, _) = vec.first_handle().unwrap();
+        let (last, &object) = vec.last_handle().unwrap();
+        assert_eq!(object, 'b');
+
+        vec.push('c');
+        assert_eq!(vec.get(&first), Some(&'a'));
+        assert_eq!(vec.get(&last), Some(&'b'));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut vec = Vector::default();
+        vec.track_heap_size();
+        let id = vec.push(String::new());
+        let handle = vec.create_handle(id).unwrap();
+
+        assert!(vec.update(&handle, |name| name.push_str("player")));
+        assert_eq!(vec.get(&handle).map(String::as_str), Some("player"));
+        let shallow = std::mem::size_of::<String>();
+        assert_eq!(vec.deep_size(), Some(shallow + vec[id].capacity()));
+
+        vec.erase_by_handle(&handle);
+        assert!(!vec.update(&handle, |_| unreachable!()));
+    }
+
+    #[test]
+    fn test_reserve_handle() {
+        let mut vec = Vector::default();
+        let first = vec.push("first");
+        vec.erase_by_id(first);
+
+        let reused = vec.reserve_handle();
+        let fresh = vec.reserve_handle();
+        let other = vec.push("other");
+        let reused_handle = reused.handle();
+
+        assert_eq!(reused.get_id(), first);
+        assert_eq!(vec.get(&reused_handle), None);
+        assert_ne!(other, fresh.get_id());
+        let handle = vec.fulfill(reused, "payload").unwrap();
+        assert_eq!(handle, reused_handle);
+        assert_eq!(vec.get(&handle), Some(&"payload"));
+
+        let fresh_handle = fresh.handle();
+        vec.cancel_reservation(fresh);
+        assert!(!vec.is_taken(fresh_handle.id));
+        assert_eq!(vec.push("next"), fresh_handle.id);
+        assert_eq!(vec.get(&fresh_handle), None);
+    }
+
+    #[test]
+    fn test_clear_cancels_reservations() {
+        let mut vec = Vector::default();
+        vec.push(0);
+        let reserved = vec.reserve_handle();
+        let stale = reserved.handle();
+
+        vec.clear();
+        assert!(!vec.is_taken(stale.id));
+        assert_eq!(vec.fulfill(reserved, 1), Err(1));
+        assert!(vec.is_empty());
+
+        // The next reservation of the ID gets a new generation
+        let again = [vec.reserve_handle(), vec.reserve_handle()]
+            .into_iter()
+            .find(|reserved| reserved.get_id() == stale.id)
+            .unwrap();
+        let handle = vec.fulfill(again, 2).unwrap();
+        assert_eq!(vec.get(&handle), Some(&2));
+        assert_eq!(vec.get(&stale), None);
+    }
+
+    #[test]
+    fn test_compact_cancels_reservations() {
+        let mut vec = Vector::default();
+        let a = vec.push('a');
+        let reserved = vec.reserve_handle();
+        let stale = reserved.handle();
+        vec.erase_by_id(a);
+
+        vec.compact();
+        assert_eq!(vec.fulfill(reserved, 'r'), Err('r'));
+        assert!(vec.is_empty());
+
+        // Recreating the freed IDs does not revive the reservation
+        vec.reserve_handle();
+        let again = vec.reserve_handle();
+        assert_eq!(again.get_id(), stale.id);
+        assert_ne!(again.handle(), stale);
+    }
+
+    #[test]
+    fn test_reset_cancels_reservations() {
+        let mut vec = Vector::default();
+        vec.push(0);
+        let reserved = vec.reserve_handle();
+        let atomic = vec.id_reserver().reserve(1).pop().unwrap();
+
+        vec.reset();
+        assert_eq!(vec.fulfill(reserved, 1), Err(1));
+        assert_eq!(vec.fulfill(atomic, 2), Err(2));
+        assert!(vec.is_empty());
+
+        let fresh = vec.id_reserver().reserve(1).pop().unwrap();
+        let handle = vec.fulfill(fresh, 3).unwrap();
+        assert_eq!(vec.get(&handle), Some(&3));
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn test_push_batch() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        for &id in &ids[..3] {
+            vec.erase_by_id(id);
+        }
+        vec.retire_id(ids[1]);
+        let reserved = vec.reserve_handle();
+        let free = vec.get_next_id();
+
+        let batch = vec.push_batch(3, |id| id * 100);
+
+        assert_eq!(batch, 4..7);
+        for id in batch {
+            assert_eq!(vec[id], id * 100);
+        }
+        assert!(vec.is_retired(ids[1]));
+        assert!(vec.is_taken(reserved.get_id()));
+        assert_eq!(vec.push(9), free);
+        assert_eq!(vec.push(10), 7);
+        assert!(vec.fulfill(reserved, 11).is_ok());
+        assert_eq!(vec.len(), 7);
+    }
+
+    #[test]
+    fn test_push_batch_in_bounded_mode() {
+        let mut vec = Vector::default();
+        vec.set_max_len(Some(3));
+        let old: Vec<_> = (0..3).map(|i| vec.push(i)).collect();
+
+        let batch = vec.push_batch(2, |id| id * 10);
+
+        assert_eq!(batch, 3..5);
+        for id in batch {
+            assert_eq!(vec[id], id * 10);
+        }
+        assert!(!vec.contains_id(old[0]));
+        assert!(!vec.contains_id(old[1]));
+        assert!(vec.contains_id(old[2]));
+        assert_eq!(vec.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "The batch exceeds the maximum length")]
+    fn test_push_batch_larger_than_max_len() {
+        let mut vec = Vector::default();
+        vec.set_max_len(Some(2));
+        vec.push_batch(3, |id| id);
+    }
+
+    #[test]
+    fn test_packed_handle_access() {
+        let mut vec = Vector::default();
+        let id = vec.push("first");
+        let packed = vec.create_packed_handle(id).unwrap();
+
+        assert_eq!(vec.get(&packed.unpack()), Some(&"first"));
+        vec.erase_by_id(id);
+        assert_eq!(vec.create_packed_handle(id), None);
+        assert!(!vec.contains(&packed.into()));
+    }
+
+    #[test]
+    fn test_invalid_handle_resolves_to_nothing() {
+        let mut vec = Vector::default();
+        vec.push(1);
+        let invalid = Handle::invalid();
+
+        assert_eq!(vec.get(&invalid), None);
+        assert_eq!(vec.get_mut(&invalid), None);
+        assert!(!vec.contains(&invalid));
+        assert!(!vec.is_valid(INVALID_ID, 0));
+        assert_eq!(vec.create_handle(INVALID_ID), None);
+        assert_eq!(vec.telemetry().stale_accesses, 2);
+    }
+
+    #[test]
+    fn test_generation_limit_retires_slots() {
+        let mut vec = Vector::default();
+        vec.set_generation_limit(2);
+        let id = vec.push("a");
+        let first = vec.create_handle(id).unwrap();
+
+        // The slot is freed at generation 1, then saturates at 2
+        vec.erase_by_id(id);
+        assert_eq!(vec.push("b"), id);
+        let second = vec.create_handle(id).unwrap();
+        assert_eq!(second.generation(), 2);
+        assert_eq!(vec.invalidate(&second), None);
+
+        vec.erase_by_id(id);
+        assert!(vec.is_retired(id));
+        assert_eq!(vec.get_validity_id(id), 2);
+        assert_ne!(vec.push("c"), id);
+        assert!(!vec.contains(&first));
+        assert!(!vec.contains(&second));
+    }
+
+    #[test]
+    fn test_generation_limit_on_bulk_erase() {
+        let mut vec: Vector<i32> = (0..4).collect();
+        vec.set_generation_limit(1);
+
+        vec.clear();
+        assert_eq!(vec.retired_count(), 4);
+        assert_eq!(vec.get_next_id(), 4);
+
+        let mut vec: Vector<i32> = (0..4).collect();
+        vec.set_generation_limit(1);
+        vec.drain().for_each(drop);
+        assert_eq!(vec.retired_count(), 4);
+        assert_eq!(vec.push(9), 4);
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn test_generation_limit_on_free_slots() {
+        let mut vec: Vector<i32> = (0..3).collect();
+        vec.erase_by_id(1);
+        vec.retire_id(0);
+
+        vec.set_generation_limit(1);
+        assert!(vec.is_retired(1));
+        assert_eq!(vec.retired_count(), 2);
+        assert_eq!(vec.push(3), 3);
+        assert_eq!(vec[2], 2);
+        assert_eq!(vec[3], 3);
+    }
+
+    #[test]
+    fn test_try_reserve_and_try_push() {
+        let mut vec = Vector::default();
+        let id = vec.push(0u64);
+        vec.erase_by_id(id);
+
+        assert!(vec.try_reserve(8).is_ok());
+        assert!(vec.capacity() >= 8);
+        assert!(vec.indices.capacity() >= 7);
+        assert_eq!(vec.try_push(1).ok(), Some(0));
+
+        let error = vec.try_reserve(usize::MAX);
+        assert!(error.is_err());
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec[0], 1);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut vec: Vector<u8> = (0..100).collect();
+        vec.truncate(10);
+
+        vec.shrink_to(40);
+        assert!(vec.capacity() >= 40 && vec.capacity() < 100);
+        assert!(vec.metadata.capacity() >= 100);
+        vec.shrink_to_fit();
+        assert_eq!(vec.capacity(), 10);
+        assert_eq!(vec.indices.capacity(), 100);
+        assert_eq!(vec.len(), 10);
+
+        let handle = vec.create_handle(1).unwrap();
+        vec.set_group(&handle, 1);
+        vec.extras.groups.reserve(100);
+        vec.shrink_to_fit();
+        assert_eq!(vec.extras.groups.capacity(), 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut vec: Vector<String> = Vector::with_capacity(64);
+        let ids = vec.push_many((0..64).map(|i| i.to_string()));
+        let old = vec.create_handle(ids[0]).unwrap();
+        vec.erase_by_id(ids[1]);
+        let again = vec.push("again".into());
+        vec.erase_by_id(again);
+        vec.retire_id(ids[2]);
+
+        vec.reset();
+        assert!(vec.is_empty());
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(vec.indices.capacity(), 0);
+        assert_eq!(vec.retired_count(), 0);
+
+        let id = vec.push("new".into());
+        assert_eq!(id, 0);
+        assert_eq!(vec.get_validity_id(id), 4);
+        assert!(!vec.contains(&old));
+        vec.reset();
+        assert_eq!(vec.push("newer".into()), 0);
+        assert_eq!(vec.get_validity_id(0), 5);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut vec: Vector<u8> = (0..4).collect();
+        assert_eq!(vec.stats().average_generation, 0.0);
+        vec.erase_by_id(0);
+        vec.erase_by_id(1);
+        vec.retire_id(2);
+        let taken = vec.take(&vec.create_handle(3).unwrap());
+        assert_eq!(taken, Some(3));
+
+        let stats = vec.stats();
+        assert_eq!(stats.live, 0);
+        assert_eq!(stats.slots, 4);
+        assert_eq!(stats.free, 2);
+        assert_eq!(stats.taken, 1);
+        assert_eq!(stats.retired, 1);
+        assert_eq!(stats.high_water, 4);
+        assert_eq!(stats.average_generation, 0.75);
+        assert_eq!(Vector::<u8>::default().stats(), VectorStats::default());
+    }
+
+    #[test]
+    fn test_stats_with_high_generations() {
+        let mut vec = Vector::default();
+        let ids: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        for &id in &ids {
+            vec.metadata[vec.indices[id]].validity_id = ID::MAX - 1;
+        }
+
+        let stats = vec.stats();
+        assert_eq!(stats.average_generation, (ID::MAX - 1) as f64);
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut vec: Vector<String> = Vector::with_capacity(4);
+        vec.push("text".to_string());
+
+        let usage = vec.memory_usage();
+        assert_eq!(usage.data, 4 * size_of::<String>());
+        assert_eq!(usage.metadata, 4 * size_of::<Metadata>());
+        assert_eq!(usage.indices, 4 * size_of::<ID>());
+        assert_eq!(usage.heap, None);
+
+        vec.track_heap_size();
+        let usage = vec.memory_usage();
+        assert_eq!(usage.heap, Some(4));
+        assert_eq!(
+            usage.total(),
+            4 * (size_of::<String>() + size_of::<Metadata>() + size_of::<ID>())
+                + 4
+        );
+
+        let mut narrow = Vector::<String, u32>::with_index_type();
+        narrow.reserve(4);
+        narrow.push("text".to_string());
+        let usage = narrow.memory_usage();
+        assert_eq!(usage.metadata, 4 * size_of::<Metadata<u32>>());
+        assert_eq!(usage.indices, 4 * size_of::<u32>());
+        assert_eq!(usage.metadata * 2, vec.memory_usage().metadata);
+    }
+
+    #[test]
+    fn test_reuse_policies() {
+        let erased = |reuse| {
+            let mut vec: Vector<u8> = (0..5).collect();
+            vec.set_reuse_policy(reuse);
+            for id in [3, 0, 4] {
+                vec.erase_by_id(id);
+            }
+            vec
+        };
+
+        let mut vec = erased(ReusePolicy::Lifo);
+        let reused: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        assert_eq!(reused, vec![4, 0, 3, 5]);
+
+        let mut vec = erased(ReusePolicy::Fifo);
+        assert_eq!(vec.get_next_id(), 3);
+        let reused: Vec<_> = (0..2).map(|i| vec.push(i)).collect();
+        vec.erase_by_id(3);
+        let reused_later: Vec<_> = (0..3).map(|i| vec.push(i)).collect();
+        assert_eq!(reused, vec![3, 0]);
+        assert_eq!(reused_later, vec![4, 3, 5]);
+
+        let mut vec = erased(ReusePolicy::LowestId);
+        assert_eq!(vec.get_next_id(), 0);
+        let reused: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        assert_eq!(reused, vec![0, 3, 4, 5]);
+        for id in 0..6 {
+            assert_eq!(vec.get_data_index(id), vec.indices[id]);
+            assert_eq!(vec.metadata[vec.indices[id]].reverse_id, id);
+        }
+    }
+
+    #[test]
+    fn test_new_slots_keep_reuse_order() {
+        let mut vec: Vector<u8> = (0..5).collect();
+        for id in [3, 0, 4] {
+            vec.erase_by_id(id);
+        }
+        assert_eq!(vec.push_batch(2, |_| 9), 5..7);
+        vec.insert_at(&Handle::new(7, 0), 9).unwrap();
+        vec.id_reserver().reserve(1);
+
+        let reused: Vec<_> = (0..3).map(|i| vec.push(i)).collect();
+        assert_eq!(reused, vec![4, 0, 3]);
+    }
+
+    #[test]
+    fn test_vacant_entry_ignores_reuse_policy() {
+        let mut vec: Vector<u8> = (0..3).collect();
+        vec.set_reuse_policy(ReusePolicy::LowestId);
+        vec.erase_by_id(0);
+        vec.erase_by_id(2);
+
+        let Some(Entry::Vacant(entry)) = vec.entry(2) else {
+            panic!("The slot should be vacant");
+        };
+        entry.insert(7);
+        assert_eq!(vec[2], 7);
+        assert!(!vec.contains_id(0));
+    }
+
+    #[test]
+    fn test_monotonic_ids() {
+        let mut vec: Vector<u8> = (0..3).collect();
+        vec.erase_by_id(1);
+        vec.set_monotonic(true);
+        assert!(vec.is_retired(1));
+
+        let handle = vec.create_handle(0).unwrap();
+        assert_eq!(vec.remove(&handle), Some(0));
+        assert_eq!(vec.pop().map(|(handle, _)| handle.id), Some(2));
+        let ids: Vec<_> = (0..3).map(|i| vec.push(i)).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+
+        vec.clear();
+        assert_eq!(vec.push(9), 6);
+        assert_eq!(vec.iter().collect::<Vec<_>>(), vec![&9]);
+        assert_eq!(vec.retired_count(), 6);
+
+        vec.set_monotonic(false);
+        vec.erase_by_id(6);
+        assert_eq!(vec.push(1), 6);
+    }
+
+    #[test]
+    fn test_remove_ordered() {
+        let mut vec: Vector<char> = "abcde".chars().collect();
+        let handles: Vec<_> = vec.handles().collect();
+
+        assert_eq!(vec.remove_ordered(&handles[1]), Some('b'));
+        assert_eq!(vec.remove_ordered_by_id(3), Some('d'));
+        assert_eq!(vec.remove_ordered(&handles[1]), None);
+        assert_eq!(vec.iter().collect::<String>(), "ace");
+        for (id, object) in [(0, 'a'), (2, 'c'), (4, 'e')] {
+            assert_eq!(vec.get(&handles[id]), Some(&object));
+        }
+        assert_eq!(vec.push('f'), 3);
+        assert_eq!(vec.push('g'), 1);
+    }
+
+    #[test]
+    fn test_erase_hook() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut vec: Vector<Vec<usize>> = Vector::default();
+        vec.set_erase_hook(Some(|handle, released| {
+            released.push(handle.id);
+            RELEASED.fetch_add(1, Ordering::Relaxed);
+        }));
+        for _ in 0..5 {
+            vec.push(Vec::new());
+        }
+
+        assert_eq!(vec.remove_by_id(1), Some(vec![1]));
+        assert_eq!(vec.remove_ordered_by_id(2), Some(vec![2]));
+        vec.erase_by_id(0);
+        let (moved, _) = vec.split_off(1);
+        assert_eq!(RELEASED.load(Ordering::Relaxed), 3);
+        assert_eq!(moved.iter().next(), Some(&vec![]));
+        assert_eq!(vec.drain().next(), Some(vec![3]));
+        vec.push(Vec::new());
+        vec.clear();
+        assert_eq!(RELEASED.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_relocate_hook() {
+        use std::sync::Mutex;
+        static MOVES: Mutex<Vec<(ID, usize, usize)>> = Mutex::new(Vec::new());
+
+        let mut vec: Vector<u8> = (0..5).collect();
+        vec.set_relocate_hook(Some(|handle, from, to| {
+            MOVES.lock().unwrap().push((handle.id, from, to));
+        }));
+
+        vec.erase_by_id(1);
+        vec.erase_by_id(3);
+        vec.remove_ordered_by_id(0);
+        vec.pop();
+        let moves = MOVES.lock().unwrap().clone();
+        assert_eq!(moves, vec![(4, 4, 1), (4, 1, 0), (2, 2, 1)]);
+    }
+
+    #[test]
+    fn test_iter_changed_since() {
+        let mut vec = Vector::default();
+        vec.set_track_modifications(true);
+        let a = vec.push('a');
+        let b = vec.push('b');
+        let h_a = vec.create_handle(a).unwrap();
+        let h_b = vec.create_handle(b).unwrap();
+
+        vec.set_tick(1);
+        let c = vec.push('c');
+        let h_c = vec.create_handle(c).unwrap();
+        vec.set_tick(2);
+        vec.update(&h_a, |object| *object = 'A');
+        let _ = vec.get(&h_b);
+
+        let changed: Vec<_> = vec.iter_changed_since(1).collect();
+        assert_eq!(changed, vec![(h_a, &'A'), (h_c, &'c')]);
+        assert_eq!(vec.iter_changed_since(2).count(), 1);
+        assert_eq!(vec.modified_tick(&h_b), Some(0));
+
+        vec.set_track_modifications(false);
+        vec.set_tick(3);
+        vec.get_mut(&h_b);
+        assert_eq!(vec.iter_changed_since(3).count(), 0);
+    }
+
+    #[test]
+    fn test_event_log() {
+        let mut vec = Vector::default();
+        vec.push('a');
+        vec.set_event_log(true);
+        let b = vec.push('b');
+        let h_b = vec.create_handle(b).unwrap();
+        vec.set_tick(1);
+        vec.erase_by_handle(&h_b);
+        let c = vec.push('c');
+        let h_c = vec.create_handle(c).unwrap();
+
+        let events: Vec<_> = vec.drain_events().collect();
+        assert_eq!(
+            events,
+            vec![
+                VectorEvent::new(EventOp::Insert, h_b, 0),
+                VectorEvent::new(EventOp::Erase, h_b, 1),
+                VectorEvent::new(EventOp::Insert, h_c, 1),
+            ]
+        );
+        assert!(vec.events().is_empty());
+
+        vec.clear();
+        assert_eq!(vec.events().len(), 2);
+        vec.set_event_log(false);
+        assert!(vec.events().is_empty());
+        vec.push('d');
+        assert!(vec.events().is_empty());
+    }
+
+    #[test]
+    fn test_event_log_fulfill_and_split_off() {
+        let mut vec = Vector::default();
+        vec.push('a');
+        vec.push('b');
+        vec.set_event_log(true);
+        let reserved = vec.reserve_handle();
+        let h_r = vec.fulfill(reserved, 'r').unwrap();
+        let h_b = vec.create_handle_from_data(1).unwrap();
+
+        vec.split_off(1);
+
+        let events: Vec<_> = vec.drain_events().collect();
+        assert_eq!(
+            events,
+            vec![
+                VectorEvent::new(EventOp::Insert, h_r, 0),
+                VectorEvent::new(EventOp::Move, h_r, 0),
+                VectorEvent::new(EventOp::Move, h_b, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut vec = Vector::default();
+        let a = vec.push('a');
+        let b = vec.push('b');
+        let x = vec.push('x');
+        let h_a = vec.create_handle(a).unwrap();
+        let h_b = vec.create_handle(b).unwrap();
+        let h_x = vec.create_handle(x).unwrap();
+        vec.erase_by_handle(&h_b);
+        let snapshot = vec.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        vec.erase_by_handle(&h_x);
+        assert_eq!(vec.push('c'), x);
+        let h_c = vec.create_handle(x).unwrap();
+        vec.erase_by_handle(&h_a);
+        let d = vec.push_batch(1, |_| 'd').start;
+        vec.retire_id(d);
+        vec.restore(&snapshot);
+
+        assert_eq!(vec.get(&h_a), Some(&'a'));
+        assert!(!vec.contains(&h_b));
+        assert_eq!(vec[x], 'x');
+        assert_eq!(vec.get(&h_x), None);
+        assert_eq!(vec.get(&h_c), None);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.retired_count(), 0);
+        assert_eq!(vec.push('e'), d);
+    }
+
+    #[test]
+    fn test_restore_never_aliases_newer_handles() {
+        let mut vec = Vector::default();
+        let id = vec.push(0);
+        let snapshot = vec.snapshot();
+        vec.erase_by_id(id);
+        assert_eq!(vec.push(1), id);
+        let newer = vec.create_handle(id).unwrap();
+
+        vec.restore(&snapshot);
+        assert_eq!(vec.get(&newer), None);
+        for object in 2..6 {
+            vec.erase_by_id(id);
+            assert_eq!(vec.push(object), id);
+            assert_eq!(vec.get(&newer), None);
+        }
+    }
+
+    #[test]
+    fn test_restore_at_generation_limit() {
+        let mut vec = Vector::default();
+        vec.set_generation_limit(2);
+        let id = vec.push(0);
+        let snapshot = vec.snapshot();
+        vec.erase_by_id(id);
+        assert_eq!(vec.push(1), id);
+        let newer = vec.create_handle(id).unwrap();
+
+        vec.restore(&snapshot);
+        assert!(vec.is_empty());
+        assert!(vec.is_retired(id));
+        assert_eq!(vec.get(&newer), None);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut a = Vector::default();
+        a.push(1);
+        a.push(2);
+        a.push(3);
+        let mut b = a.clone();
+        b.erase_by_id(0);
+        b.erase_by_id(1);
+        let first = b.push(2);
+        let second = b.push(1);
+        assert_eq!((first, second), (1, 0));
+        assert_ne!(a.data, b.data);
+
+        let hash = a.content_hash::<DefaultHasher>();
+        assert_eq!(hash, b.content_hash::<DefaultHasher>());
+        b.replace(&b.create_handle(0).unwrap(), 4).unwrap();
+        assert_ne!(hash, b.content_hash::<DefaultHasher>());
+    }
+
+    #[test]
+    fn test_logical_equality() {
+        let mut a = Vector::default();
+        let ids: Vec<_> = (0..3).map(|i| a.push(i)).collect();
+        let mut b = a.clone();
+        b.erase_by_id(ids[0]);
+        assert_ne!(a, b);
+
+        b.push(0);
+        assert_ne!(a.data, b.data);
+        assert_ne!(a.metadata, b.metadata);
+        assert_eq!(a, b);
+
+        b.set_tick(5);
+        b.set_reuse_policy(ReusePolicy::Fifo);
+        assert_eq!(a, b);
+        b.replace(&b.create_handle(ids[1]).unwrap(), 7).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_hash_ignores_layout() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let mut a = Vector::default();
+        let ids: Vec<_> = (0..3).map(|i| a.push(i)).collect();
+        let mut b = a.clone();
+        b.erase_by_id(ids[0]);
+        b.erase_by_id(ids[2]);
+        b.push(2);
+        b.push(0);
+
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(&a), state.hash_one(&b));
+        b.push(3);
+        assert_ne!(state.hash_one(&a), state.hash_one(&b));
+    }
+
+    #[test]
+    fn test_id_reserver() {
+        let mut vec = Vector::default();
+        let id = vec.push(0);
+        vec.erase_by_id(id);
+
+        let reservations: Vec<_> = std::thread::scope(|scope| {
+            let reserver = vec.id_reserver();
+            let workers: Vec<_> = (0..4)
+                .map(|_| scope.spawn(move || reserver.reserve(2)))
+                .collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+        let mut ids: Vec<_> = reservations.iter().map(|r| r.get_id()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, (1..9).collect::<Vec<_>>());
+        assert_eq!(vec.get_next_id(), id);
+
+        let other = vec.push(100);
+        assert_eq!(other, id);
+        assert_eq!(vec.push(200), 9);
+        for reserved in reservations {
+            let value = reserved.get_id();
+            let handle = vec.fulfill(reserved, value).unwrap();
+            assert_eq!(vec.get(&handle), Some(&value));
+        }
+        assert_eq!(vec.len(), 10);
+    }
+
+    #[test]
+    fn test_clone_from_keeps_allocation() {
+        let source: Vector<String> = (0..4).map(|i| i.to_string()).collect();
+        let mut target: Vector<String> = Vector::with_capacity(16);
+        let data = target.data.as_ptr();
+
+        target.clone_from(&source);
+        assert_eq!(target, source);
+        assert_eq!(target.metadata, source.metadata);
+        assert_eq!(target.data.as_ptr(), data);
+    }
+
+    #[test]
+    fn test_handle_invalidation_after_erase() {
+        let mut vec = Vector::default();
+        let id = vec.push(100);
+        let handle = vec.create_handle(id).unwrap();
+
+        assert!(vec.get(&handle).is_some());
+
+        vec.erase_by_handle(&handle);
+
+        assert_eq!(vec.get(&handle), None);
+        assert_eq!(vec.len(), 0);
+    }
+
+    #[test]
+    fn test_reuse_slots_and_stale_handles() {
+        let mut vec = Vector::default();
+
+        let id_a = vec.push(10); // 'A'
+        let handle_a = vec.create_handle(id_a).unwrap();
+
+        vec.erase_by_handle(&handle_a);
+
+        let id_b = vec.push(20);
+        let handle_b = vec.create_handle(id_b).unwrap();
+
+        assert_eq!(vec.get(&handle_a), None, "Old handle accessed new data!");
+        
+        assert_eq!(vec.get(&handle_b), Some(&20));
+    }
+
+    #[test]
+    fn test_swap_behavior() {
+        let mut vec = Vector::default();
+
+        let id1 = vec.push(1);
+        let id2 = vec.push(2);
+        let id3 = vec.push(3);
+
+        let h1 = vec.create_handle(id1).unwrap();
+        let h2 = vec.create_handle(id2).unwrap();
+        let h3 = vec.create_handle(id3).unwrap();
+
+        vec.erase_by_handle(&h1);
+        assert_eq!(vec.len(), 2);
+
+        assert_eq!(vec.get(&h1), None);
+        assert_eq!(vec.get(&h2), Some(&2));
+        assert_eq!(vec.get(&h3), Some(&3));
+    }
+
+    #[test]
+    fn test_mutable_access() {
+        let mut vec = Vector::default();
+        let id = vec.push(5);
+        let handle = vec.create_handle(id).unwrap();
+
+        if let Some(val) = vec.get_mut(&handle) {
+            *val = 10;
+        }
+
+        assert_eq!(vec.get(&handle), Some(&10));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        vec.clear();
+
+        assert!(vec.is_empty());
+        assert_eq!(vec.get(&handle), None);
+    }
+
+    #[test]
+    fn test_push_and_access() {
+        let mut vec = Vector::default();
+        let id = vec.push(42);
+        let handle = vec.create_handle(id).unwrap();
+
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec.get(&handle), Some(&42));
+    }
+
+    #[test]
+    fn test_erase_logic() {
+        let mut vec = Vector::default();
+        let id_a = vec.push(10);
+        let id_b = vec.push(20);
+        let id_c = vec.push(30);
+
+        let h_a = vec.create_handle(id_a).unwrap();
+        let h_b = vec.create_handle(id_b).unwrap();
+        let h_c = vec.create_handle(id_c).unwrap();
+
+        vec.erase_by_handle(&h_a);
+
+        assert_eq!(vec.len(), 2);
+
+        assert_eq!(vec.get(&h_a), None);
+        assert_eq!(vec.get(&h_b), Some(&20));
+        assert_eq!(vec.get(&h_c), Some(&30));
+    }
+
+    #[test]
+    fn test_stale_handle_protection() {
+        let mut vec = Vector::default();
+
+        let id = vec.push(100);
+        let handle_old = vec.create_handle(id).unwrap();
+
+        vec.erase_by_handle(&handle_old);
+
+        let id_new = vec.push(200);
+        let handle_new = vec.create_handle(id_new).unwrap();
+
+        assert_eq!(vec.get(&handle_old), None);
+        assert_eq!(vec.get(&handle_new), Some(&200));
+    }
+
+    #[test]
+    fn test_clear_invalidates_handles() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        vec.clear();
+
+        assert!(vec.is_empty());
+        assert_eq!(vec.get(&handle), None);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut vec = Vector::default();
+        let id = vec.push(5);
+        let handle = vec.create_handle(id).unwrap();
+
+        if let Some(val) = vec.get_mut(&handle) {
+            *val = 99;
+        }
+
+        assert_eq!(vec.get(&handle), Some(&99));
+    }
+    
+    #[test]
+    fn test_invalid_handle_creation() {
+        let vec: Vector<i32> = Vector::default();
+        let result = vec.create_handle(999);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut vec = Vector::default();
+        vec.push(10);
+        vec.push(20);
+        vec.push(30);
+
+        let mut sum = 0;
+        for x in &vec {
+            sum += *x;
+        }
+        assert_eq!(sum, 60);
+
+        for x in &mut vec {
+            *x *= 2;
+        }
+
+        let first = vec.iter().next();
+        assert_eq!(first, Some(&20));
+
+        let mut collected = Vec::new();
+        for x in vec {
+            collected.push(x);
+        }
+        
+        assert_eq!(collected, vec![20, 40, 60]);
+    }
+
+    #[test]
+    fn test_revalidate() {
+        let mut vec = Vector::default();
+        let id1 = vec.push(1);
+        let id2 = vec.push(2);
+        let id3 = vec.push(3);
+        let h1 = vec.create_handle(id1).unwrap();
+        let h2 = vec.create_handle(id2).unwrap();
+        let h3 = vec.create_handle(id3).unwrap();
+
+        vec.erase_by_handle(&h2);
+
+        let mut handles = vec![h1, h2, h3, Handle::new(999, 0)];
+        vec.revalidate(&mut handles);
+
+        assert_eq!(handles, vec![h1, h3]);
+    }
+
+    #[test]
+    fn test_revalidate_taken() {
+        let mut vec = Vector::default();
+        let id1 = vec.push(1);
+        let id2 = vec.push(2);
+        let h1 = vec.create_handle(id1).unwrap();
+        let h2 = vec.create_handle(id2).unwrap();
+        assert_eq!(vec.take(&h2), Some(2));
+
+        let mut handles = vec![h1, h2];
+        vec.revalidate(&mut handles);
+        assert_eq!(handles, vec![h1]);
+        assert_eq!(vec.get(&h2), None);
+    }
+
+    #[test]
+    fn test_slot_generation_and_reuse_count() {
+        let mut vec = Vector::default();
+        vec.set_track_reuses(true);
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        assert_eq!(vec.slot_generation(id), Some(handle.generation()));
+        assert_eq!(vec.slot_reuse_count(id), Some(0));
+
+        vec.erase_by_id(id);
+        let reused = vec.push(2);
+
+        assert_eq!(reused, id);
+        assert_eq!(vec.slot_generation(id), Some(handle.generation() + 2));
+        assert_eq!(vec.slot_reuse_count(id), Some(1));
+        assert_eq!(vec.slot_generation(42), None);
+        assert_eq!(vec.slot_reuse_count(42), None);
+    }
+
+    #[test]
+    fn test_reuses_are_counted_on_demand() {
+        let mut vec: Vector<u32> = (0..4).collect();
+        vec.erase_by_id(2);
+        vec.push(20);
+
+        assert!(!vec.tracks_reuses());
+        assert_eq!(vec.slot_reuse_count(2), Some(0));
+        assert_eq!(vec.memory_usage().extras, 0);
+
+        vec.set_track_reuses(true);
+        vec.erase_by_id(2);
+        vec.push(21);
+        vec.compact();
+        assert_eq!(vec.slot_reuse_count(3), Some(1));
+        assert_eq!(vec.slot_reuse_count(0), Some(0));
+        assert!(vec.memory_usage().extras > 0);
+    }
+
+    #[test]
+    fn test_iter_mut_with_handles() {
+        let mut vec = Vector::default();
+        let id1 = vec.push(1);
+        let id2 = vec.push(2);
+        let id3 = vec.push(3);
+        vec.erase_by_id(id1);
+
+        let mut handles = Vec::new();
+        for (handle, value) in vec.iter_mut_with_handles() {
+            *value *= 10;
+            handles.push(handle);
+        }
+
+        assert_eq!(handles.len(), 2);
+        assert!(handles.contains(&vec.create_handle(id2).unwrap()));
+        assert!(handles.contains(&vec.create_handle(id3).unwrap()));
+        for handle in &handles {
+            assert_eq!(*vec.get(handle).unwrap() % 10, 0);
+        }
+    }
+
+    #[test]
+    fn test_freeze_allows_reads() {
+        let mut vec = Vector::default();
+        let id = vec.push(7);
+        let handle = vec.create_handle(id).unwrap();
+
+        vec.freeze();
+        assert!(vec.is_frozen());
+        assert_eq!(vec.get(&handle), Some(&7));
+        assert_eq!(vec[id], 7);
+
+        vec.unfreeze();
+        assert!(!vec.is_frozen());
+        vec.push(8);
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "frozen")]
+    fn test_freeze_rejects_push() {
+        let mut vec = Vector::default();
+        vec.freeze();
+        vec.push(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "frozen")]
+    fn test_freeze_rejects_get_mut() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+        vec.freeze();
+        vec.get_mut(&handle);
+    }
+
+    #[test]
+    fn test_growth_strategy_applies_to_all_vectors() {
+        let mut vec = Vector::default();
+        vec.set_growth_strategy(GrowthStrategy::Fixed(10));
+
+        for i in 0..11 {
+            vec.push(i);
         }
-        // A new slot has to be created
-        let new_id = self.data.len();
-        self.metadata.push(Metadata::new(new_id, 0));
-        self.indices.push(new_id);
-        new_id
+
+        assert_eq!(vec.growth_strategy(), GrowthStrategy::Fixed(10));
+        assert_eq!(vec.data.capacity(), 20);
+        assert_eq!(vec.metadata.capacity(), 20);
+        assert_eq!(vec.indices.capacity(), 20);
     }
-}
 
-impl<T> Index<usize> for Vector<T> {
-    type Output = T;
+    #[test]
+    fn test_growth_strategy_applies_to_put_back() {
+        let mut vec = Vector::default();
+        vec.set_growth_strategy(GrowthStrategy::Fixed(5));
+        vec.push_many(0..10);
+        let handle = vec.create_handle(0).unwrap();
+        let object = vec.take(&handle).unwrap();
+        vec.push(10);
+        assert_eq!(vec.data.capacity(), 10);
 
-    fn index(&self, index: usize) -> &Self::Output {
-        let data_index = self.indices[index];
-        &self.data[data_index]
+        vec.put_back(handle.id, object).unwrap();
+        assert_eq!(vec.data.capacity(), 15);
     }
-}
 
-impl<T> IndexMut<usize> for Vector<T> {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        let data_index = self.indices[index];
-        &mut self.data[data_index]
-    }
-}
+    #[test]
+    fn test_insertion_tick() {
+        let mut vec = Vector::default();
+        vec.set_tick(5);
+        let id_a = vec.push('a');
+        vec.set_tick(3);
+        let id_b = vec.push('b');
+        vec.set_tick(9);
+        let id_c = vec.push('c');
 
-impl<'a, T> IntoIterator for &'a Vector<T> {
-    type Item = &'a T;
-    type IntoIter = std::slice::Iter<'a, T>;
+        let h_a = vec.create_handle(id_a).unwrap();
+        let h_b = vec.create_handle(id_b).unwrap();
+        let h_c = vec.create_handle(id_c).unwrap();
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.data.iter()
+        assert_eq!(vec.tick(), 9);
+        assert_eq!(vec.insertion_tick(&h_a), Some(5));
+        assert_eq!(vec.insertion_tick(&h_c), Some(9));
+        assert_eq!(vec.oldest(), Some(h_b));
+
+        vec.erase_by_handle(&h_b);
+        assert_eq!(vec.insertion_tick(&h_b), None);
+        assert_eq!(vec.oldest(), Some(h_a));
     }
-}
 
-impl<'a, T> IntoIterator for &'a mut Vector<T> {
-    type Item = &'a mut T;
-    type IntoIter = std::slice::IterMut<'a, T>;
+    #[test]
+    fn test_insertion_ticks_follow_ids() {
+        let mut vec: Vector<u8> = (0..3).collect();
+        assert_eq!(vec.memory_usage().extras, 0);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.data.iter_mut()
+        vec.set_tick(4);
+        vec.push(3);
+        vec.erase_by_id(0);
+        vec.compact();
+        let handle = vec.create_handle(0).unwrap();
+        assert_eq!(vec.get(&handle), Some(&3));
+        assert_eq!(vec.insertion_tick(&handle), Some(4));
+
+        vec.erase_by_handle(&handle);
+        vec.set_tick(0);
+        let reused = vec.push(5);
+        let reused = vec.create_handle(reused).unwrap();
+        assert_eq!(vec.insertion_tick(&reused), Some(0));
     }
-}
 
-impl<T> IntoIterator for Vector<T> {
-    type Item = T;
-    type IntoIter = std::vec::IntoIter<T>;
+    #[test]
+    fn test_modification_count() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let other = vec.push(2);
+        let handle = vec.create_handle(id).unwrap();
+        let other_handle = vec.create_handle(other).unwrap();
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.data.into_iter()
+        vec.get_mut(&handle);
+        assert_eq!(vec.modification_count(&handle), Some(0));
+        assert_eq!(vec.memory_usage().extras, 0);
+
+        vec.set_track_modifications(true);
+        assert!(vec.tracks_modifications());
+        vec.get_mut(&handle);
+        vec[id] = 3;
+        assert_eq!(vec.modification_count(&handle), Some(2));
+        assert_eq!(vec.modification_count(&other_handle), Some(0));
+
+        vec.iter_mut().for_each(|x| *x += 1);
+        assert_eq!(vec.modification_count(&handle), Some(3));
+        assert_eq!(vec.modification_count(&other_handle), Some(1));
+
+        vec.erase_by_handle(&handle);
+        assert_eq!(vec.modification_count(&handle), None);
+        let reused = vec.push(4);
+        let reused_handle = vec.create_handle(reused).unwrap();
+        assert_eq!(vec.modification_count(&reused_handle), Some(0));
     }
-}
 
-impl<T> Default for Vector<T> {
-    fn default() -> Self {
-        Self {
-            data: Vec::new(),
-            metadata: Vec::new(),
-            indices: Vec::new(),
+    #[test]
+    fn test_split_groups_mut() {
+        let mut vec = Vector::default();
+        let ids: Vec<ID> = (0..6).map(|i| vec.push(i)).collect();
+        for &id in &ids {
+            let handle = vec.create_handle(id).unwrap();
+            vec.set_group(&handle, id % 2);
+        }
+        let h0 = vec.create_handle(ids[0]).unwrap();
+        assert_eq!(vec.group_of(&h0), Some(0));
+
+        let [mut even, mut odd] = vec.split_groups_mut([0, 1]).unwrap();
+        assert_eq!(even.len(), 3);
+        assert_eq!(odd.group(), 1);
+        std::thread::scope(|scope| {
+            scope.spawn(|| even.iter_mut().for_each(|(_, x)| *x += 100));
+            scope.spawn(|| odd.iter_mut().for_each(|(_, x)| *x -= 100));
+        });
+
+        assert_eq!(vec[ids[0]], 100);
+        assert_eq!(vec[ids[1]], -99);
+        assert!(vec.split_groups_mut([1, 1]).is_none());
+
+        // Lookups work whatever the data order
+        vec.erase_by_id(ids[0]);
+        let handles: Vec<_> = vec.iter_with_handles().map(|(h, _)| h).collect();
+        let [mut even, mut odd] = vec.split_groups_mut([0, 1]).unwrap();
+        for handle in &handles {
+            let object = even.get_mut(handle).or_else(|| odd.get_mut(handle));
+            assert_eq!(object.map(|x| *x % 2 == 0), Some(handle.id % 2 == 0));
         }
+        assert_eq!(even.get_mut(&h0), None);
+        assert_eq!(odd.get_mut(&handles[0]).is_some(), handles[0].id % 2 == 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_groups_follow_objects() {
+        let mut vec: Vector<u8> = (0..3).collect();
+        let handle = vec.create_handle(2).unwrap();
+        vec.set_group(&handle, 5);
+        vec.erase_by_id(0);
+        vec.compact();
+
+        let moved = vec.create_handle(0).unwrap();
+        assert_eq!(vec.get(&moved), Some(&2));
+        assert_eq!(vec.group_of(&moved), Some(5));
+        vec.erase_by_handle(&moved);
+        let reused = vec.push(3);
+        let reused = vec.create_handle(reused).unwrap();
+        assert_eq!(vec.group_of(&reused), Some(0));
+        assert_eq!(size_of::<Metadata>(), 2 * size_of::<ID>());
+    }
 
     #[test]
-    fn test_basic_push_and_get() {
+    fn test_retire_id() {
         let mut vec = Vector::default();
-        
-        let id1 = vec.push(10);
-        let id2 = vec.push(20);
+        let id_a = vec.push('a');
+        let id_b = vec.push('b');
+        let h_a = vec.create_handle(id_a).unwrap();
 
-        let h1 = vec.create_handle(id1).unwrap();
-        let h2 = vec.create_handle(id2).unwrap();
+        assert!(vec.retire_id(id_a));
+        assert!(vec.is_retired(id_a));
+        assert!(!vec.retire_id(id_a));
+        assert!(!vec.retire_id(42));
+        assert_eq!(vec.retired_count(), 1);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec.get(&h_a), None);
 
-        assert_eq!(vec.get(&h1), Some(&10));
-        assert_eq!(vec.get(&h2), Some(&20));
-        assert_eq!(vec.len(), 2);
+        vec.erase_by_id(id_b);
+        assert_eq!(vec.get_next_id(), id_b);
+        assert_eq!(vec.push('c'), id_b);
+        let id_d = vec.push('d');
+        assert_ne!(id_d, id_a);
+
+        vec.clear();
+        for _ in 0..3 {
+            assert_ne!(vec.push('e'), id_a);
+        }
+        assert!(vec.is_retired(id_a));
+        assert_eq!(vec.iter().filter(|c| **c == 'e').count(), 3);
     }
 
     #[test]
-    fn test_handle_invalidation_after_erase() {
+    fn test_invalidate() {
         let mut vec = Vector::default();
-        let id = vec.push(100);
-        let handle = vec.create_handle(id).unwrap();
+        let id = vec.push(5);
+        let old = vec.create_handle(id).unwrap();
 
-        assert!(vec.get(&handle).is_some());
+        let fresh = vec.invalidate(&old).unwrap();
 
-        vec.erase_by_handle(&handle);
+        assert_eq!(fresh.id, old.id);
+        assert_eq!(vec.get(&old), None);
+        assert_eq!(vec.get(&fresh), Some(&5));
+        assert_eq!(vec.len(), 1);
+        assert!(vec.invalidate(&old).is_none());
+    }
+
+    #[test]
+    fn test_bounded_mode_evicts_oldest() {
+        let mut vec = Vector::default();
+        vec.push(1);
+        vec.push(2);
+        vec.push(3);
+
+        assert_eq!(vec.set_max_len(Some(2)), vec![1]);
+        assert_eq!(vec.max_len(), Some(2));
+
+        let (id, evicted) = vec.push_evicting(4);
+        assert_eq!(evicted, Some(2));
+        let handle = vec.create_handle(id).unwrap();
+
+        let erased = vec.create_handle_from_data(0).unwrap();
+        vec.erase_by_handle(&erased);
+        let (_, evicted) = vec.push_evicting(5);
+        assert_eq!(evicted, None);
 
+        vec.push(6);
+        assert_eq!(vec.len(), 2);
         assert_eq!(vec.get(&handle), None);
-        assert_eq!(vec.len(), 0);
+        let mut remaining: Vec<i32> = vec.iter().copied().collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![5, 6]);
+
+        assert!(vec.set_max_len(None).is_empty());
+        vec.push(7);
+        assert_eq!(vec.len(), 3);
     }
 
     #[test]
-    fn test_reuse_slots_and_stale_handles() {
+    fn test_raw_parts_round_trip() {
         let mut vec = Vector::default();
+        let id_a = vec.push('a');
+        let id_b = vec.push('b');
+        let id_c = vec.push('c');
+        vec.erase_by_id(id_a);
+        vec.retire_id(id_c);
+        let h_b = vec.create_handle(id_b).unwrap();
 
-        let id_a = vec.push(10); // 'A'
-        let handle_a = vec.create_handle(id_a).unwrap();
+        let vec = Vector::from_raw_parts(vec.into_raw_parts()).unwrap();
 
-        vec.erase_by_handle(&handle_a);
+        assert_eq!(vec.get(&h_b), Some(&'b'));
+        assert!(vec.is_retired(id_c));
+        assert_eq!(vec.get_next_id(), id_a);
+    }
 
-        let id_b = vec.push(20);
-        let handle_b = vec.create_handle(id_b).unwrap();
+    #[test]
+    fn test_raw_parts_keep_reset_generations() {
+        let mut vec = Vector::default();
+        let old: Vec<Handle<i32>> = (0..2)
+            .map(|i| {
+                let id = vec.push(i);
+                vec.create_handle(id).unwrap()
+            })
+            .collect();
+        vec.reset();
+        vec.push(100);
 
-        assert_eq!(vec.get(&handle_a), None, "Old handle accessed new data!");
-        
-        assert_eq!(vec.get(&handle_b), Some(&20));
+        let mut vec = Vector::from_raw_parts(vec.into_raw_parts()).unwrap();
+        let id = vec.push(200);
+
+        assert_eq!(id, old[1].id);
+        assert_eq!(vec.get(&old[0]), None);
+        assert_eq!(vec.get(&old[1]), None);
     }
 
     #[test]
-    fn test_swap_behavior() {
+    fn test_from_raw_parts_rejects_corruption() {
         let mut vec = Vector::default();
+        vec.push(1);
+        vec.push(2);
+        let mut parts = vec.into_raw_parts();
+        parts.indices.swap(0, 1);
 
-        let id1 = vec.push(1);
-        let id2 = vec.push(2);
-        let id3 = vec.push(3);
+        assert_eq!(
+            Vector::from_raw_parts(parts.clone()),
+            Err(RawPartsError::InconsistentSlot { position: 0 })
+        );
 
-        let h1 = vec.create_handle(id1).unwrap();
-        let h2 = vec.create_handle(id2).unwrap();
-        let h3 = vec.create_handle(id3).unwrap();
+        parts.indices.pop();
+        assert_eq!(
+            Vector::from_raw_parts(parts),
+            Err(RawPartsError::LengthMismatch { metadata: 2, indices: 1 })
+        );
+    }
 
-        vec.erase_by_handle(&h1);
-        assert_eq!(vec.len(), 2);
+    #[test]
+    fn test_from_raw_parts_rejects_reserved_generation() {
+        let mut vec = Vector::default();
+        vec.push(1);
+        vec.push(2);
+        let mut parts = vec.into_raw_parts();
+        parts.metadata[1].validity_id = ID::MAX;
+        parts.generation_floor = ID::MAX;
 
-        assert_eq!(vec.get(&h1), None);
-        assert_eq!(vec.get(&h2), Some(&2));
-        assert_eq!(vec.get(&h3), Some(&3));
+        assert_eq!(
+            Vector::from_raw_parts(parts.clone()),
+            Err(RawPartsError::GenerationOutOfRange { position: 1 })
+        );
+        let (mut vec, broken) = Vector::from_raw_parts_lenient(parts);
+        assert_eq!(broken, vec![1]);
+        let id = vec.push(3);
+        assert_eq!(vec.get_validity_id(id), MAX_GENERATION);
+        vec.set_generation_limit(ID::MAX);
+        assert_eq!(vec.generation_limit(), MAX_GENERATION);
     }
 
     #[test]
-    fn test_mutable_access() {
+    fn test_raw_parts_keep_reuse_counts() {
+        let mut vec: Vector<u32> = (0..2).collect();
+        vec.set_track_reuses(true);
+        vec.erase_by_id(1);
+        vec.push(10);
+        let mut parts = vec.into_raw_parts();
+        assert_eq!(parts.extras.reuse_counts, [0, 1]);
+
+        let vec = Vector::from_raw_parts(parts.clone()).unwrap();
+        assert_eq!(vec.slot_reuse_count(1), Some(1));
+        parts.extras.reuse_counts.push(0);
+        assert_eq!(
+            Vector::from_raw_parts(parts.clone()),
+            Err(RawPartsError::TooManyExtras { extras: 3, ids: 2 })
+        );
+        let (vec, broken) = Vector::from_raw_parts_lenient(parts);
+        assert!(broken.is_empty());
+        assert_eq!(vec.slot_reuse_count(1), Some(1));
+    }
+
+    #[test]
+    fn test_from_raw_parts_lenient() {
         let mut vec = Vector::default();
-        let id = vec.push(5);
-        let handle = vec.create_handle(id).unwrap();
+        let ids: Vec<ID> = (0..4).map(|i| vec.push(i * 10)).collect();
+        let handles: Vec<Handle<i32>> =
+            ids.iter().map(|&id| vec.create_handle(id).unwrap()).collect();
+        let mut parts = vec.into_raw_parts();
+        parts.metadata[1].reverse_id = 3;
 
-        if let Some(val) = vec.get_mut(&handle) {
-            *val = 10;
-        }
+        let (mut vec, broken) = Vector::from_raw_parts_lenient(parts);
 
-        assert_eq!(vec.get(&handle), Some(&10));
+        assert_eq!(broken, vec![ids[1]]);
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.get(&handles[0]), Some(&0));
+        assert_eq!(vec.get(&handles[1]), None);
+        assert_eq!(vec.get(&handles[2]), Some(&20));
+        assert_eq!(vec.get(&handles[3]), Some(&30));
+        assert!(vec.is_retired(ids[1]));
+        assert_ne!(vec.push(40), ids[1]);
     }
 
     #[test]
-    fn test_clear() {
+    fn test_stamped_handles() {
         let mut vec = Vector::default();
+        let mut other = Vector::default();
         let id = vec.push(1);
+        other.push(1);
+        let identity = vec.assign_identity();
+        other.assign_identity();
+
+        assert_eq!(vec.assign_identity(), identity);
+        assert_eq!(vec.identity(), Some(identity));
+
         let handle = vec.create_handle(id).unwrap();
+        let stamped = vec.stamp(handle);
 
-        vec.clear();
+        assert_eq!(vec.resolve_stamped(&stamped), Some(handle));
+        assert_eq!(other.resolve_stamped(&stamped), None);
 
-        assert!(vec.is_empty());
-        assert_eq!(vec.get(&handle), None);
+        let restored = Vector::from_raw_parts(vec.into_raw_parts()).unwrap();
+        assert_eq!(restored.resolve_stamped(&stamped), Some(handle));
     }
 
     #[test]
-    fn test_push_and_access() {
+    fn test_clone_gets_new_identity() {
         let mut vec = Vector::default();
-        let id = vec.push(42);
-        let handle = vec.create_handle(id).unwrap();
+        let id = vec.push(1);
+        let identity = vec.assign_identity();
+        let stamped = vec.stamp(vec.create_handle(id).unwrap());
 
-        assert_eq!(vec.len(), 1);
-        assert_eq!(vec.get(&handle), Some(&42));
+        let clone = vec.clone();
+        assert!(clone.identity().is_some_and(|other| other != identity));
+        assert_eq!(clone.resolve_stamped(&stamped), None);
+        let mut target = Vector::default();
+        target.clone_from(&vec);
+        assert_eq!(target.resolve_stamped(&stamped), None);
+        assert_eq!(vec.resolve_stamped(&stamped), Some(stamped.handle));
+        assert_eq!(Vector::<i32>::default().clone().identity(), None);
     }
 
     #[test]
-    fn test_erase_logic() {
+    fn test_replace() {
         let mut vec = Vector::default();
-        let id_a = vec.push(10);
-        let id_b = vec.push(20);
-        let id_c = vec.push(30);
-
-        let h_a = vec.create_handle(id_a).unwrap();
-        let h_b = vec.create_handle(id_b).unwrap();
-        let h_c = vec.create_handle(id_c).unwrap();
-
-        vec.erase_by_handle(&h_a);
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
 
-        assert_eq!(vec.len(), 2);
+        assert_eq!(vec.replace(&handle, 2), Ok(1));
+        assert_eq!(vec.get(&handle), Some(&2));
 
-        assert_eq!(vec.get(&h_a), None);
-        assert_eq!(vec.get(&h_b), Some(&20));
-        assert_eq!(vec.get(&h_c), Some(&30));
+        vec.erase_by_handle(&handle);
+        assert_eq!(vec.replace(&handle, 3), Err(3));
     }
 
     #[test]
-    fn test_stale_handle_protection() {
-        let mut vec = Vector::default();
+    fn test_deep_size_tracking() {
+        let mut vec: Vector<String> = Vector::default();
+        let string_size = std::mem::size_of::<String>();
+        vec.push(String::with_capacity(10));
+        assert_eq!(vec.deep_size(), None);
 
-        let id = vec.push(100);
-        let handle_old = vec.create_handle(id).unwrap();
+        vec.track_heap_size();
+        assert_eq!(vec.deep_size(), Some(string_size + 10));
 
-        vec.erase_by_handle(&handle_old);
+        let id = vec.push(String::with_capacity(20));
+        let handle = vec.create_handle(id).unwrap();
+        assert_eq!(vec.deep_size(), Some(2 * string_size + 30));
 
-        let id_new = vec.push(200);
-        let handle_new = vec.create_handle(id_new).unwrap();
+        vec.replace(&handle, String::with_capacity(5)).unwrap();
+        assert_eq!(vec.deep_size(), Some(2 * string_size + 15));
 
-        assert_eq!(vec.get(&handle_old), None);
-        assert_eq!(vec.get(&handle_new), Some(&200));
+        vec.erase_by_handle(&handle);
+        assert_eq!(vec.deep_size(), Some(string_size + 10));
+
+        vec.clear();
+        assert_eq!(vec.deep_size(), Some(0));
+        vec.untrack_heap_size();
+        assert_eq!(vec.deep_size(), None);
     }
 
     #[test]
-    fn test_clear_invalidates_handles() {
+    fn test_telemetry() {
         let mut vec = Vector::default();
         let id = vec.push(1);
+        vec.push(2);
         let handle = vec.create_handle(id).unwrap();
+        vec.erase_by_handle(&handle);
 
+        assert_eq!(vec.get(&handle), None);
+        assert_eq!(vec.get(&Handle::new(99, 0)), None);
         vec.clear();
 
-        assert!(vec.is_empty());
-        assert_eq!(vec.get(&handle), None);
+        let telemetry = vec.telemetry();
+        assert_eq!(telemetry.live, 0);
+        assert_eq!(telemetry.inserts, 2);
+        assert_eq!(telemetry.erases, 2);
+        assert_eq!(telemetry.stale_accesses, 2);
+        assert_eq!(telemetry.capacity, vec.capacity());
     }
 
+    #[cfg(feature = "metrics")]
     #[test]
-    fn test_get_mut() {
+    fn test_publish_metrics() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
         let mut vec = Vector::default();
-        let id = vec.push(5);
-        let handle = vec.create_handle(id).unwrap();
+        vec.push(1);
+        vec.push(2);
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
 
-        if let Some(val) = vec.get_mut(&handle) {
-            *val = 99;
-        }
+        metrics::with_local_recorder(&recorder, || {
+            vec.publish_metrics("entities");
+        });
 
-        assert_eq!(vec.get(&handle), Some(&99));
+        let values: Vec<(String, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+            .collect();
+        assert!(values.contains(&(
+            "entities_inserts_total".to_string(),
+            DebugValue::Counter(2)
+        )));
+        assert!(values.iter().any(|(name, value)| {
+            name == "entities_live_objects"
+                && *value == DebugValue::Gauge(2.0.into())
+        }));
     }
-    
+
+    #[cfg(feature = "rayon")]
     #[test]
-    fn test_invalid_handle_creation() {
-        let vec: Vector<i32> = Vector::default();
-        let result = vec.create_handle(999);
-        assert!(result.is_none());
+    fn test_par_from_iter() {
+        let vec = Vector::par_from_iter(
+            (0..10_000).into_par_iter().map(|i| i * 2),
+        );
+
+        assert_eq!(vec.len(), 10_000);
+        for id in [0, 1, 4_999, 9_999] {
+            let handle = vec.create_handle(id).unwrap();
+            assert_eq!(vec.get(&handle), Some(&(id * 2)));
+        }
+        assert_eq!(vec.get_next_id(), 10_000);
+        assert!(Vector::<u8>::par_from_iter(Vec::new()).is_empty());
     }
 
+    #[cfg(feature = "rayon")]
     #[test]
-    fn test_iterators() {
+    fn test_par_extend_reuses_slots() {
         let mut vec = Vector::default();
-        vec.push(10);
-        vec.push(20);
-        vec.push(30);
+        let ids: Vec<_> = (0..4).map(|i| vec.push(i)).collect();
+        vec.erase_by_id(ids[1]);
+        vec.erase_by_id(ids[3]);
+        vec.retire_id(ids[3]);
 
-        let mut sum = 0;
-        for x in &vec {
-            sum += *x;
+        vec.par_extend(vec![10, 11, 12]);
+
+        assert_eq!(vec.len(), 5);
+        assert!(vec.is_retired(ids[3]));
+        assert_eq!(vec[ids[1]], 10);
+        for id in [4, 5] {
+            let handle = vec.create_handle(id).unwrap();
+            assert_eq!(vec.get(&handle), Some(&(id + 7)));
         }
-        assert_eq!(sum, 60);
 
-        for x in &mut vec {
-            *x *= 2;
+        let id = vec.push(20);
+        assert_eq!(id, 6);
+        assert_eq!(vec.create_handle(ids[3]), None);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_extend_after_reset() {
+        let mut vec: Vector<i32> = (0..2).collect();
+        let old = vec.create_handle(0).unwrap();
+
+        vec.reset();
+        vec.par_extend(vec![5, 6]);
+        assert_eq!(vec.get_validity_id(0), 1);
+        assert!(!vec.contains(&old));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() {
+        let mut vec: Vector<u64> = (0..1_000).collect();
+        vec.erase_by_id(3);
+        vec.set_track_modifications(true);
+
+        vec.par_iter_mut().for_each(|object| *object *= 2);
+        assert_eq!(vec.par_iter().sum::<u64>(), vec.iter().sum::<u64>());
+        let (first, _) = vec.first_handle().unwrap();
+        assert_eq!(vec.modification_count(&first), Some(1));
+
+        let pairs: Vec<_> = vec.par_iter_with_handles().collect();
+        assert_eq!(pairs, vec.iter_with_handles().collect::<Vec<_>>());
+        assert!(pairs.iter().all(|(handle, object)| {
+            **object == handle.id as u64 * 2
+        }));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_collect_and_extend() {
+        let mut vec: Vector<usize> = (0..1_000).into_par_iter().collect();
+        assert_eq!(vec.len(), 1_000);
+        assert_eq!(vec[999], 999);
+
+        vec.erase_by_id(10);
+        ParallelExtend::par_extend(&mut vec, (0..5).into_par_iter());
+        assert_eq!(vec.len(), 1_004);
+        assert_eq!(vec[10], 0);
+        assert_eq!(vec.get_next_id(), 1_004);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_chunks_mut() {
+        use std::sync::Mutex;
+
+        let mut vec: Vector<usize> = (0..100).collect();
+        vec.erase_by_id(0);
+        let results = Mutex::new(Vec::new());
+
+        vec.par_chunks_mut(16).for_each(|(handles, chunk)| {
+            assert_eq!(handles.len(), chunk.len());
+            for (handle, object) in handles.iter().zip(chunk.iter_mut()) {
+                *object += 1;
+                results.lock().unwrap().push((handle, *object));
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(handle, _)| handle.id);
+        assert_eq!(results.len(), 99);
+        for (handle, object) in results {
+            assert_eq!(vec.get(&handle), Some(&object));
+            assert_eq!(object, handle.id + 1);
         }
+    }
 
-        let first = vec.iter().next();
-        assert_eq!(first, Some(&20));
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_custom_allocator() {
+        use allocator_api2::alloc::AllocError;
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+        use std::rc::Rc;
 
-        let mut collected = Vec::new();
-        for x in vec {
-            collected.push(x);
+        // Counts the allocations and the blocks not freed yet
+        #[derive(Clone, Default)]
+        struct Tracking(Rc<(Cell<usize>, Cell<usize>)>);
+
+        unsafe impl Allocator for Tracking {
+            fn allocate(
+                &self,
+                layout: Layout,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.0.set(self.0.0.get() + 1);
+                self.0.1.set(self.0.1.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.0.1.set(self.0.1.get() - 1);
+                unsafe { Global.deallocate(ptr, layout) }
+            }
         }
-        
-        assert_eq!(collected, vec![20, 40, 60]);
+
+        let tracking = Tracking::default();
+        let mut vec = Vector::new_in(tracking.clone());
+        assert_eq!(tracking.0.0.get(), 0);
+        let ids: Vec<_> = (0..100).map(|i| vec.push(i)).collect();
+        assert!(tracking.0.0.get() >= 3);
+        assert_eq!(tracking.0.1.get(), 3);
+
+        let allocations = tracking.0.0.get();
+        vec.erase_by_id(ids[10]);
+        vec.push(10);
+        assert_eq!(tracking.0.0.get(), allocations);
+        let copy = vec.clone();
+        assert_eq!(tracking.0.1.get(), 6);
+        assert_eq!(copy.allocator().0.0.get(), allocations + 3);
+        let (other, _) = vec.split_off(50);
+        assert_eq!(other.allocator().0.1.get(), 9);
+        drop((copy, other));
+        assert_eq!(tracking.0.1.get(), 3);
+        drop(vec);
+        assert_eq!(tracking.0.1.get(), 0);
+
+        let mut narrow = Vector::<u8, u32, _>::with_index_type_in(&tracking);
+        narrow.push(1);
+        assert_eq!(tracking.0.1.get(), 3);
     }
 }
@@ -0,0 +1,132 @@
+use crate::vector::Vector;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A borrowed, read-only view over a Vector. Only the non-mutating API of
+/// the vector is reachable through it.
+#[derive(Debug)]
+pub struct VectorView<'a, T> {
+    /// The viewed vector
+    vector: &'a Vector<T>,
+}
+
+impl<T> Copy for VectorView<'_, T> {}
+
+impl<T> Clone for VectorView<'_, T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, T> VectorView<'a, T> {
+    /// Factory constructor
+    pub fn new(vector: &'a Vector<T>) -> Self {
+        Self { vector }
+    }
+}
+
+impl<T> Deref for VectorView<'_, T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.vector
+    }
+}
+
+impl<'a, T> IntoIterator for VectorView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vector.iter()
+    }
+}
+
+/// An owning, reference counted read-only view over a Vector. Cloning it is
+/// cheap and every clone shares the same vector.
+#[derive(Debug)]
+pub struct SharedView<T> {
+    /// The shared vector
+    vector: Arc<Vector<T>>,
+}
+
+impl<T> Clone for SharedView<T> {
+    fn clone(&self) -> Self {
+        Self {
+            vector: Arc::clone(&self.vector),
+        }
+    }
+}
+
+impl<T> SharedView<T> {
+    /// Factory constructor
+    pub fn new(vector: Vector<T>) -> Self {
+        Self {
+            vector: Arc::new(vector),
+        }
+    }
+
+    /// Returns a borrowed view over the shared vector
+    pub fn view(&self) -> VectorView<'_, T> {
+        VectorView::new(&self.vector)
+    }
+
+    /// Shares an already reference counted vector
+    pub(crate) fn from_arc(vector: Arc<Vector<T>>) -> Self {
+        Self { vector }
+    }
+
+    /// Gets the vector back if this is the last reference to it
+    pub fn try_unwrap(self) -> Result<Vector<T>, Self> {
+        Arc::try_unwrap(self.vector).map_err(|vector| Self { vector })
+    }
+}
+
+impl<T> Deref for SharedView<T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vector
+    }
+}
+
+impl<T> From<Vector<T>> for SharedView<T> {
+    fn from(vector: Vector<T>) -> Self {
+        Self::new(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_reads() {
+        let mut vec = Vector::default();
+        let id = vec.push(10);
+        vec.push(20);
+        let handle = vec.create_handle(id).unwrap();
+
+        let view = vec.view();
+
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.get(&handle), Some(&10));
+        assert_eq!(view.into_iter().sum::<i32>(), 30);
+    }
+
+    #[test]
+    fn test_shared_view() {
+        let mut vec = Vector::default();
+        let id = vec.push(1);
+        let handle = vec.create_handle(id).unwrap();
+
+        let shared = vec.into_shared();
+        let other = shared.clone();
+
+        assert_eq!(other.get(&handle), Some(&1));
+        assert_eq!(shared.view().len(), 1);
+
+        let shared = shared.try_unwrap().unwrap_err();
+        drop(other);
+        let vec = shared.try_unwrap().unwrap();
+        assert_eq!(vec.len(), 1);
+    }
+}